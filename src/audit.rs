@@ -0,0 +1,180 @@
+// Auditing a student's readiness for the course, from the courses
+// they've completed. The concepts the course doesn't teach (those
+// without weight in any modality, other than milestones) are its
+// prerequisites, and the
+// student has one if a completed course covers it. They're prepared for
+// each concept whose (transitive) prerequisites they all have, and the
+// rest are held up by the gaps.
+
+use crate::{input, normalize, plural, Concept, ConceptMap, Status};
+use clap::Args;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Csv of the prior courses, with "course" and "concepts" columns
+    /// (the concepts each covers, separated by semicolons)
+    #[arg(long, value_name = "FILE")]
+    pub courses: PathBuf,
+    /// The student's completed courses, one per line
+    #[arg(long, value_name = "FILE")]
+    pub transcript: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct CourseRecord {
+    course: String,
+    concepts: String,
+}
+
+fn is_prerequisite(c: &Concept) -> bool {
+    !c.is_taught() && !c.kind.is_milestone() && c.status != Status::Retired
+}
+
+// Each concept the completed courses cover, mapped to the first course
+// covering it.
+fn covered(args: &AuditArgs) -> anyhow::Result<HashMap<String, String>> {
+    let transcript = fs::read_to_string(&args.transcript)?;
+    let completed: Vec<String> = transcript
+        .lines()
+        .map(normalize::key)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let mut covered = HashMap::new();
+
+    for r in csv::Reader::from_path(&args.courses)?.deserialize() {
+        let r: CourseRecord = r?;
+        if !completed.contains(&normalize::key(&r.course)) {
+            continue;
+        }
+        for c in input::split_list(&r.concepts, ';') {
+            covered
+                .entry(normalize::key(&c))
+                .or_insert_with(|| r.course.trim().to_string());
+        }
+    }
+    Ok(covered)
+}
+
+/// The prerequisites the student has and is missing, which concepts
+/// they're prepared for, and what holds up the rest. Requires the
+/// dependencies to be acyclic.
+pub fn report(m: &ConceptMap, args: &AuditArgs) -> anyhow::Result<String> {
+    let covered = covered(args)?;
+    let course_of = |c: &Concept| c.names().find_map(|n| covered.get(&normalize::key(n)));
+    // the missing prerequisites each concept needs, by offset
+    let mut gaps: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); m.concepts.len()];
+    for n in &m.dependency_order {
+        let c = m.dependency_to_concept(n);
+        let mut needs = BTreeSet::new();
+        if is_prerequisite(c) && course_of(c).is_none() {
+            needs.insert(c.offset);
+        }
+        for d in &c.dependencies {
+            needs.extend(&gaps[m.dependency_to_concept(d).offset]);
+        }
+        gaps[c.offset] = needs;
+    }
+    let mut out = String::new();
+
+    out.push_str("Prerequisites:\n");
+    for c in m.concepts.iter().filter(|c| is_prerequisite(c)) {
+        match course_of(c) {
+            Some(course) => out.push_str(&format!("- \"{}\": covered by {}\n", c.concept, course)),
+            None => out.push_str(&format!("- \"{}\": missing\n", c.concept)),
+        }
+    }
+
+    let taught: Vec<&Concept> = m
+        .concepts
+        .iter()
+        .filter(|c| c.is_taught() && !c.kind.is_milestone())
+        .collect();
+    let blocked: Vec<&&Concept> = taught
+        .iter()
+        .filter(|c| !gaps[c.offset].is_empty())
+        .collect();
+    out.push_str(&format!(
+        "\nPrepared for {} of the course's {}.\n",
+        taught.len() - blocked.len(),
+        plural(taught.len(), "concept", "concepts")
+    ));
+    if !blocked.is_empty() {
+        out.push_str("\nNot prepared for:\n");
+        for c in blocked {
+            let missing: Vec<String> = gaps[c.offset]
+                .iter()
+                .map(|&o| format!("\"{}\"", m.concepts[o].concept))
+                .collect();
+            out.push_str(&format!(
+                "- \"{}\", missing {}\n",
+                c.concept,
+                missing.join(", ")
+            ));
+        }
+    }
+
+    let review: Vec<String> = taught
+        .iter()
+        .filter_map(|c| course_of(c).map(|course| format!("\"{}\" ({})", c.concept, course)))
+        .collect();
+    if !review.is_empty() {
+        out.push_str(&format!(
+            "\nAlready covered by completed courses: {}\n",
+            review.join(", ")
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaps_hold_up_the_concepts_needing_them() {
+        let dir = std::env::temp_dir().join(format!("concept_map_audit_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let args = AuditArgs {
+            courses: dir.join("courses.csv"),
+            transcript: dir.join("transcript.txt"),
+        };
+        fs::write(
+            &args.courses,
+            "course,concepts\nCS 1,C; Pointers\nMath 2,Proofs\n",
+        )
+        .unwrap();
+        fs::write(&args.transcript, "cs 1\n\n").unwrap();
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight,status
+C,,0,
+proofs,,0,
+old,,0,retired
+pointers,C,1,
+paging,pointers,1,
+induction,proofs,1,
+",
+        );
+        let report = report(&m, &args).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            report.lines().collect::<Vec<_>>(),
+            [
+                "Prerequisites:",
+                "- \"C\": covered by CS 1",
+                "- \"proofs\": missing",
+                "",
+                "Prepared for 2 of the course's 3 concepts.",
+                "",
+                "Not prepared for:",
+                "- \"induction\", missing \"proofs\"",
+                "",
+                "Already covered by completed courses: \"pointers\" (CS 1)",
+            ]
+        );
+    }
+}
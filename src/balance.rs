@@ -0,0 +1,72 @@
+// Checking the balance between modalities. A concept that's lectured
+// on at length but never practiced in labs or homework (or practiced,
+// but never lectured on) usually means forgotten coverage, rather than
+// a deliberate choice.
+
+use crate::{Concept, ConceptMap};
+
+fn practice(c: &Concept) -> f64 {
+    c.modes[1].weight + c.modes[2].weight
+}
+
+/// Warnings for the concepts whose lab and HW weight is less than
+/// `min_practice` times their lecture weight, and whose lecture weight
+/// is less than `min_lecture` times their lab and HW weight.
+pub fn check(m: &ConceptMap, min_practice: Option<f64>, min_lecture: Option<f64>) -> String {
+    let mut warnings = String::new();
+
+    for c in m.concepts.iter().filter(|c| !c.kind.is_milestone()) {
+        let (lecture, practice) = (c.modes[0].weight, practice(c));
+
+        if let Some(ratio) = min_practice {
+            if lecture > 0.0 && practice < ratio * lecture {
+                warnings.push_str(&format!(
+                    "- \"{}\" on {} has {} of lecture, but only {} of lab and HW (under {} times the lecture).\n",
+                    c.concept,
+                    c.location,
+                    m.units.weeks(lecture),
+                    m.units.weeks(practice),
+                    ratio
+                ));
+            }
+        }
+        if let Some(ratio) = min_lecture {
+            if practice > 0.0 && lecture < ratio * practice {
+                warnings.push_str(&format!(
+                    "- \"{}\" on {} has {} of lab and HW, but only {} of lecture (under {} times the lab and HW).\n",
+                    c.concept,
+                    c.location,
+                    m.units.weeks(practice),
+                    m.units.weeks(lecture),
+                    ratio
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpracticed_and_unlectured_concepts_are_flagged() {
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight,lab weight,hw weight,type
+lectured,,1,0.25,,
+practiced,,0.25,0.5,0.5,
+even,,1,0.5,0.5,
+exam,,1,,,exam
+",
+        );
+
+        assert_eq!(check(&m, None, None), "");
+        assert_eq!(
+            check(&m, Some(0.5), Some(0.5)),
+            "- \"lectured\" on line 2 has 1.00 weeks of lecture, but only 0.25 weeks of lab and HW (under 0.5 times the lecture).\n\
+             - \"practiced\" on line 3 has 1.00 weeks of lab and HW, but only 0.25 weeks of lecture (under 0.5 times the lab and HW).\n"
+        );
+    }
+}
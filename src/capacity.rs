@@ -0,0 +1,86 @@
+// Checking that the map fits in the semester. For each modality whose
+// weights add up to more than the semester, we report how much has to
+// be cut, and suggest the concepts that the fewest others depend on.
+
+use crate::{plural, ConceptMap, MODE_NAMES};
+
+// Don't flood the warning with every leaf of the map.
+const MAX_CANDIDATES: usize = 10;
+
+pub fn check(m: &ConceptMap, semester_weeks: f64) -> String {
+    let dependents = m.dependents();
+    let mut warnings = String::new();
+
+    for (mode, name) in MODE_NAMES.iter().enumerate() {
+        let total = m.total_weights[mode];
+        let over = total - semester_weeks;
+        if over <= 0.0 {
+            continue;
+        }
+
+        let mut candidates: Vec<_> = m
+            .concepts
+            .iter()
+            // retired concepts are already cut
+            .filter(|c| c.is_taught() && c.modes[mode].weight > 0.0)
+            .collect();
+        // fewest dependents first, and then the largest savings
+        candidates.sort_by(|a, b| {
+            dependents[a.offset]
+                .len()
+                .cmp(&dependents[b.offset].len())
+                .then(b.modes[mode].weight.total_cmp(&a.modes[mode].weight))
+        });
+
+        let mut saved = 0.0;
+        let mut cuts = Vec::new();
+        for c in candidates {
+            if saved >= over || cuts.len() == MAX_CANDIDATES {
+                break;
+            }
+            saved += c.modes[mode].weight;
+            cuts.push(format!(
+                "\"{}\" ({}, {})",
+                c.concept,
+                m.units.weeks(c.modes[mode].weight),
+                plural(dependents[c.offset].len(), "dependent", "dependents")
+            ));
+        }
+
+        warnings.push_str(&format!(
+            "- {} weights total {}, {} over the {}-week semester. Candidates to cut, with the fewest dependents first: {} (saving {}).\n",
+            name,
+            m.units.weeks(total),
+            m.units.weeks(over),
+            semester_weeks,
+            cuts.join(", "),
+            m.units.weeks(saved)
+        ));
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuts_start_with_the_fewest_dependents() {
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight,lab weight,status
+a,,2,1,
+b,a,1,,
+c,a,1.5,,
+d,b,0.5,,
+old,,3,,retired
+",
+        );
+
+        assert_eq!(check(&m, 5.0), "");
+        assert_eq!(
+            check(&m, 3.0),
+            "- Lecture weights total 5.00 weeks, 2.00 weeks over the 3-week semester. Candidates to cut, with the fewest dependents first: \"c\" (1.50 weeks, 0 dependents), \"d\" (0.50 weeks, 0 dependents) (saving 2.00 weeks).\n"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+// The longest chains of dependencies, by their lecture weight: each
+// chain's lectures have to be taught one after the other, so the
+// heaviest one is the least time in which the course can be taught,
+// however the rest is scheduled.
+
+use crate::{plural, Concept, ConceptMap, Status};
+use clap::Args;
+
+#[derive(Args)]
+pub struct ChainsArgs {
+    /// How many chains to list
+    #[arg(long, short = 'n', value_name = "N", default_value_t = 5)]
+    pub top: usize,
+}
+
+// Retired concepts aren't taught anymore.
+fn lecture_weight(c: &Concept) -> f64 {
+    if c.status == Status::Retired {
+        0.0
+    } else {
+        c.modes[0].weight
+    }
+}
+
+/// The `top` heaviest chains, one ending at each of the concepts
+/// nothing depends on (but milestones), prerequisites first. Concepts
+/// in dependency cycles aren't in any.
+pub fn report(m: &ConceptMap, top: usize) -> String {
+    // the heaviest chain ending at each concept: its weight, and the
+    // dependency it continues, by offset
+    let mut heaviest: Vec<(f64, Option<usize>)> = vec![(0.0, None); m.concepts.len()];
+    for n in &m.dependency_order {
+        let c = m.dependency_to_concept(n);
+        let before = c
+            .dependencies
+            .iter()
+            .map(|d| m.dependency_to_concept(d).offset)
+            .max_by(|a, b| heaviest[*a].0.total_cmp(&heaviest[*b].0));
+        heaviest[c.offset] = (
+            lecture_weight(c) + before.map_or(0.0, |d| heaviest[d].0),
+            before,
+        );
+    }
+
+    let dependents = m.dependents();
+    let mut ends: Vec<&Concept> = m
+        .dependency_order
+        .iter()
+        .map(|n| m.dependency_to_concept(n))
+        .filter(|c| {
+            !c.kind.is_milestone()
+                && dependents[c.offset]
+                    .iter()
+                    .all(|&d| m.concepts[d].kind.is_milestone())
+        })
+        .collect();
+    ends.sort_by(|a, b| {
+        heaviest[b.offset]
+            .0
+            .total_cmp(&heaviest[a.offset].0)
+            .then(a.offset.cmp(&b.offset))
+    });
+
+    if ends.is_empty() {
+        return String::from("No dependency chains.\n");
+    }
+    let mut out = String::new();
+
+    out.push_str("The heaviest dependency chains, by lecture weight (prerequisites first):\n");
+    for (i, end) in ends.iter().take(top).enumerate() {
+        let mut chain = vec![end.concept.as_str()];
+        let mut o = end.offset;
+        while let Some(d) = heaviest[o].1 {
+            chain.push(&m.concepts[d].concept);
+            o = d;
+        }
+        chain.reverse();
+        out.push_str(&format!(
+            "{}. {}, {}: {}\n",
+            i + 1,
+            m.weight_label(0, heaviest[end.offset].0),
+            plural(chain.len(), "concept", "concepts"),
+            chain.join(" -> ")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_end_at_the_concepts_nothing_depends_on() {
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight,type,status
+a,,1,,
+b,a,1,,
+c,b,0.5,,
+old,a,3,,retired
+d,old,2,,
+final,c;d,0,exam,
+",
+        );
+
+        assert_eq!(
+            report(&m, 5).lines().collect::<Vec<_>>(),
+            [
+                "The heaviest dependency chains, by lecture weight (prerequisites first):",
+                "1. 3.00 weeks, 3 concepts: a -> old -> d",
+                "2. 2.50 weeks, 3 concepts: a -> b -> c",
+            ]
+        );
+        assert_eq!(report(&m, 1).lines().count(), 2);
+        assert_eq!(
+            report(&crate::test_map("concept,dependencies\n"), 5),
+            "No dependency chains.\n"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// The department-wide curriculum map: several courses' csv files read
+// into one map, each course's concepts namespaced by its name, e.g.,
+// "OS::scheduling" and "Networks::scheduling". A course's dependencies
+// are on its own concepts, unless they're qualified with another
+// course's name, and each course is drawn as a cluster (its units are
+// the clusters' topics), so the prerequisites between courses cross
+// the clusters' boundaries.
+
+use crate::{input, ConceptRecord};
+use clap::Args;
+use std::path::PathBuf;
+
+const SEPARATOR: &str = "::";
+
+#[derive(Args)]
+pub struct CurriculumArgs {
+    /// The courses' csv files, each named after its file unless given
+    /// as COURSE=FILE
+    #[arg(required = true, value_name = "[COURSE=]FILE", value_parser = parse_course)]
+    pub courses: Vec<(String, PathBuf)>,
+}
+
+fn parse_course(s: &str) -> Result<(String, PathBuf), String> {
+    match s.split_once('=') {
+        Some((course, file)) if !course.trim().is_empty() => {
+            Ok((course.trim().to_string(), PathBuf::from(file)))
+        }
+        Some(_) => Err(format!("expected COURSE=FILE, found \"{}\"", s)),
+        None => {
+            let file = PathBuf::from(s);
+            Ok((crate::program::course_name(&file), file))
+        }
+    }
+}
+
+// The name, in `course` unless it's already qualified. Soft
+// dependencies keep their "?".
+fn qualify(course: &str, name: &str) -> String {
+    if name.contains(SEPARATOR) {
+        name.to_string()
+    } else {
+        format!("{}{}{}", course, SEPARATOR, name)
+    }
+}
+
+fn qualify_list(course: &str, cell: &str, sep: char) -> String {
+    let items: Vec<String> = input::split_list(cell, sep)
+        .iter()
+        .map(|i| qualify(course, i))
+        .collect();
+
+    input::join_list(&items, sep)
+}
+
+/// The record with its names qualified by `course`, which becomes its
+/// unit, its own unit its topic.
+pub fn qualify_record(course: &str, mut r: ConceptRecord, sep: char) -> ConceptRecord {
+    r.concept = qualify(course, r.concept.trim());
+    r.dependencies = qualify_list(course, &r.dependencies, sep);
+    r.corequisites = r.corequisites.map(|c| qualify_list(course, &c, sep));
+    r.aliases = r.aliases.map(|a| qualify_list(course, &a, ';'));
+    r.dependency_notes = r.dependency_notes.map(|notes| {
+        let notes: Vec<String> = input::split_list(&notes, sep)
+            .iter()
+            .map(|n| match n.split_once('=') {
+                Some((d, reason)) => format!("{}={}", qualify(course, d.trim()), reason),
+                None => n.clone(),
+            })
+            .collect();
+        input::join_list(&notes, sep)
+    });
+    r.topic = r.unit.take();
+    r.unit = Some(course.to_string());
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn courses_are_named_after_their_files() {
+        assert_eq!(
+            parse_course("os/cs453.csv"),
+            Ok((String::from("cs453"), PathBuf::from("os/cs453.csv")))
+        );
+        assert_eq!(
+            parse_course(" OS =os.csv"),
+            Ok((String::from("OS"), PathBuf::from("os.csv")))
+        );
+        assert!(parse_course("=os.csv").is_err());
+    }
+
+    #[test]
+    fn names_are_qualified_by_their_course() {
+        let r = qualify_record(
+            "OS",
+            ConceptRecord {
+                concept: String::from(" paging "),
+                dependencies: String::from("pointers; Intro::C; recursion?"),
+                aliases: Some(String::from("virtual memory")),
+                dependency_notes: Some(String::from("pointers=for page tables")),
+                unit: Some(String::from("memory")),
+                ..ConceptRecord::default()
+            },
+            ';',
+        );
+
+        assert_eq!(r.concept, "OS::paging");
+        assert_eq!(r.dependencies, "OS::pointers;Intro::C;OS::recursion?");
+        assert_eq!(r.aliases.as_deref(), Some("OS::virtual memory"));
+        assert_eq!(
+            r.dependency_notes.as_deref(),
+            Some("OS::pointers=for page tables")
+        );
+        assert_eq!(
+            (r.unit.as_deref(), r.topic.as_deref()),
+            (Some("OS"), Some("memory"))
+        );
+    }
+}
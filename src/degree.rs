@@ -0,0 +1,202 @@
+// How connected each concept is. A concept with many direct
+// dependencies is probably a row conflating several ideas, and one
+// with many direct dependents is likely an overloaded foundational
+// topic, and one with neither was likely never wired into the map.
+// Also, the degree distributions and density, for comparing courses'
+// graphs.
+
+use crate::{plural, ConceptMap};
+use serde::Serialize;
+use std::io;
+
+/// Warnings for the concepts with more than `max_dependencies` direct
+/// dependencies, or more than `max_dependents` direct dependents.
+pub fn check(
+    m: &ConceptMap,
+    max_dependencies: Option<usize>,
+    max_dependents: Option<usize>,
+) -> String {
+    let dependents = m.dependents();
+    let mut warnings = String::new();
+
+    for c in &m.concepts {
+        if let Some(max) = max_dependencies {
+            if c.dependencies.len() > max {
+                warnings.push_str(&format!(
+                    "- \"{}\" on {} has {}, over {}; does it conflate several ideas?\n",
+                    c.concept,
+                    c.location,
+                    plural(
+                        c.dependencies.len(),
+                        "direct dependency",
+                        "direct dependencies"
+                    ),
+                    max
+                ));
+            }
+        }
+        if let Some(max) = max_dependents {
+            let n = dependents[c.offset].len();
+            if n > max {
+                warnings.push_str(&format!(
+                    "- \"{}\" on {} has {}, over {}; is it an overloaded foundation?\n",
+                    c.concept,
+                    c.location,
+                    plural(n, "direct dependent", "direct dependents"),
+                    max
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Warnings for the concepts without dependencies or dependents (of
+/// any kind), or corequisites. Milestones can stand alone.
+pub fn check_orphans(m: &ConceptMap) -> String {
+    let dependents = m.dependents();
+    let mut warnings = String::new();
+
+    for c in m.concepts.iter().filter(|c| !c.kind.is_milestone()) {
+        let connected = !c.dependencies.is_empty()
+            || !c.soft_dependencies.is_empty()
+            || !c.corequisites.is_empty()
+            || !dependents[c.offset].is_empty()
+            || m.concepts
+                .iter()
+                .any(|d| d.soft_dependencies.contains(&c.concept));
+        if !connected {
+            warnings.push_str(&format!(
+                "- \"{}\" on {} has no dependencies or dependents; was it left out of the map? (See --allow-orphans.)\n",
+                c.concept, c.location
+            ));
+        }
+    }
+
+    warnings
+}
+
+pub fn edges(m: &ConceptMap) -> usize {
+    m.concepts.iter().map(|c| c.dependencies.len()).sum()
+}
+
+/// The fraction of the possible dependencies (between distinct
+/// concepts) that are in the map.
+pub fn density(m: &ConceptMap) -> f64 {
+    let n = m.concepts.len() as f64;
+
+    if n < 2.0 {
+        0.0
+    } else {
+        edges(m) as f64 / (n * (n - 1.0))
+    }
+}
+
+#[derive(Serialize)]
+struct Row {
+    measure: &'static str,
+    degree: Option<usize>,
+    value: String,
+}
+
+/// As csv, the number of concepts with each in-degree (direct
+/// dependents) and out-degree (direct dependencies), as the edges point
+/// from concepts to their dependencies, followed by the edge count and
+/// density.
+pub fn distributions_csv<W: io::Write>(m: &ConceptMap, out: W) -> anyhow::Result<()> {
+    let dependents = m.dependents();
+    let mut w = csv::Writer::from_writer(out);
+    let histogram = |degrees: Vec<usize>| {
+        let mut counts = vec![0; degrees.iter().copied().max().map_or(0, |d| d + 1)];
+        for d in degrees {
+            counts[d] += 1;
+        }
+        counts
+    };
+    let ins = histogram(dependents.iter().map(Vec::len).collect());
+    let outs = histogram(m.concepts.iter().map(|c| c.dependencies.len()).collect());
+
+    for (measure, counts) in [("in-degree", ins), ("out-degree", outs)] {
+        for (degree, n) in counts.into_iter().enumerate() {
+            w.serialize(Row {
+                measure,
+                degree: Some(degree),
+                value: n.to_string(),
+            })?;
+        }
+    }
+    w.serialize(Row {
+        measure: "edges",
+        degree: None,
+        value: edges(m).to_string(),
+    })?;
+    w.serialize(Row {
+        measure: "density",
+        degree: None,
+        value: format!("{:.6}", density(m)),
+    })?;
+    w.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HUB: &str = "concept,dependencies,corequisites,type
+C,,,
+a,C,,
+b,C,,
+c,C;a;b,,
+alone,,,
+paired,,x,
+x,,,
+exam,,,exam
+";
+
+    #[test]
+    fn concepts_with_too_many_edges_are_flagged() {
+        let m = crate::test_map(HUB);
+
+        assert_eq!(check(&m, None, None), "");
+        assert_eq!(
+            check(&m, Some(2), Some(2)),
+            "- \"C\" on line 2 has 3 direct dependents, over 2; is it an overloaded foundation?\n\
+             - \"c\" on line 5 has 3 direct dependencies, over 2; does it conflate several ideas?\n"
+        );
+    }
+
+    #[test]
+    fn unconnected_concepts_are_orphans() {
+        let m = crate::test_map(HUB);
+
+        // corequisites and milestones aren't
+        assert_eq!(
+            check_orphans(&m),
+            "- \"alone\" on line 6 has no dependencies or dependents; was it left out of the map? (See --allow-orphans.)\n"
+        );
+    }
+
+    #[test]
+    fn distributions_count_concepts_by_degree() {
+        let m = crate::test_map("concept,dependencies\nC,\na,C\nb,C;a\n");
+        let mut out = Vec::new();
+        distributions_csv(&m, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "measure,degree,value
+in-degree,0,1
+in-degree,1,1
+in-degree,2,1
+out-degree,0,1
+out-degree,1,1
+out-degree,2,1
+edges,,3
+density,,0.500000
+"
+        );
+        assert_eq!(density(&crate::test_map("concept,dependencies\nC,\n")), 0.0);
+    }
+}
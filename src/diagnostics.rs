@@ -0,0 +1,263 @@
+// Printing diagnostics. They always go to stderr, so that they never
+// end up in the graph (or whatever else is printed) on stdout. On a
+// terminal, the headings and bullets are colored, red for errors and
+// yellow for warnings; when stderr is piped, or NO_COLOR is set
+// (https://no-color.org), they're plain text. They're also kept, for
+// the json report, and can be printed as csv instead, to paste next to
+// the spreadsheet, in which case the one-line notes are left out.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Whether to print the notes, which aren't csv rows.
+static NOTES: AtomicBool = AtomicBool::new(true);
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Level {
+    fn color(&self) -> &'static str {
+        match self {
+            Level::Error => "\x1b[1;31m",
+            Level::Warning => "\x1b[1;33m",
+            Level::Note => "\x1b[1;36m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+fn colored() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stderr().is_terminal()
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Note => "note",
+        }
+    }
+}
+
+/// Which diagnostics fail the run.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FailOn {
+    Never,
+    Errors,
+    Warnings,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Headings and bulleted messages
+    Text,
+    /// One severity, code, concept, line, and message row per message
+    Csv,
+}
+
+#[derive(Serialize)]
+pub struct Section {
+    pub level: Level,
+    code: &'static str,
+    heading: String,
+    pub messages: Vec<String>,
+}
+
+/// The diagnostics found so far.
+#[derive(Default, Serialize)]
+#[serde(transparent)]
+pub struct Diagnostics(Vec<Section>);
+
+impl Diagnostics {
+    /// Add the "- " bulleted `lines` under `heading`, if there are any.
+    /// The `code` names the check, for the csv.
+    pub fn add(&mut self, level: Level, code: &'static str, heading: &str, lines: &str) {
+        if lines.is_empty() {
+            return;
+        }
+        self.0.push(Section {
+            level,
+            code,
+            heading: heading.to_string(),
+            messages: lines
+                .lines()
+                .map(|l| l.strip_prefix("- ").unwrap_or(l).to_string())
+                .collect(),
+        });
+    }
+
+    /// Print each heading, and then its messages.
+    pub fn print(&self) {
+        for s in &self.0 {
+            let lines: String = s.messages.iter().map(|m| format!("- {}\n", m)).collect();
+            print(s.level, &format!("{}:", s.heading), &lines);
+        }
+    }
+
+    /// Print them in `format`.
+    pub fn print_as(&self, format: Format) -> anyhow::Result<()> {
+        match format {
+            Format::Text => self.print(),
+            Format::Csv => self.write_csv(io::stderr())?,
+        }
+        Ok(())
+    }
+
+    fn write_csv<W: io::Write>(&self, out: W) -> anyhow::Result<()> {
+        #[derive(Serialize)]
+        struct Row<'a> {
+            severity: &'static str,
+            code: &'static str,
+            concept: Option<&'a str>,
+            line: Option<&'a str>,
+            message: &'a str,
+        }
+
+        let mut w = csv::Writer::from_writer(out);
+        for s in &self.0 {
+            for m in &s.messages {
+                w.serialize(Row {
+                    severity: s.level.name(),
+                    code: s.code,
+                    concept: concept(m),
+                    line: line(m),
+                    message: m,
+                })?;
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Add `other`'s, after these.
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.0.extend(other.0);
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.0
+    }
+
+    /// The number of messages at `level`.
+    pub fn count(&self, level: Level) -> usize {
+        self.0
+            .iter()
+            .filter(|s| s.level == level)
+            .map(|s| s.messages.len())
+            .sum()
+    }
+}
+
+/// The line of the concept the message is about, its first " on line
+/// N" outside of the quoted names, which can say anything.
+pub fn line(message: &str) -> Option<&str> {
+    let mut quoted = false;
+
+    for (i, ch) in message.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if let Some(rest) = message[i..].strip_prefix(" on line ") {
+                    let end = rest
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(rest.len());
+                    if end > 0 {
+                        return Some(&rest[..end]);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+// The concept the message is about: the one named as `concept "X"`,
+// or else the first quoted name, if any.
+fn concept(message: &str) -> Option<&str> {
+    let start = match message.find("concept \"") {
+        Some(i) => i + "concept \"".len(),
+        None => message.find('"')? + 1,
+    };
+    let rest = &message[start..];
+
+    Some(&rest[..rest.find('"')?])
+}
+
+fn print(level: Level, heading: &str, lines: &str) {
+    if !colored() {
+        eprint!("{}\n{}", heading, lines);
+        return;
+    }
+    let mut out = format!("{}{}{}\n", level.color(), heading, RESET);
+    for line in lines.lines() {
+        match line.strip_prefix("- ") {
+            Some(rest) => out.push_str(&format!("{}-{} {}\n", level.color(), RESET, rest)),
+            None => out.push_str(&format!("{}\n", line)),
+        }
+    }
+    eprint!("{}", out);
+}
+
+/// Print the notes (the default), or leave them out, for the csv.
+pub fn show_notes(show: bool) {
+    NOTES.store(show, Ordering::Relaxed);
+}
+
+/// Print a one-line message.
+pub fn note(message: &str) {
+    if !NOTES.load(Ordering::Relaxed) {
+        return;
+    }
+    if colored() {
+        eprintln!("{}note:{} {}", Level::Note.color(), RESET, message);
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_the_concepts_not_their_names() {
+        assert_eq!(
+            line("- \"Pipeline 3\" on line 7 has no dependencies."),
+            Some("7")
+        );
+        assert_eq!(
+            line("Dependency on \"x\" in concept \"Pipeline stages\" on line 12 of a.csv does not correspond to a concept."),
+            Some("12")
+        );
+        assert_eq!(line("\"Run on line 4\" on record 2 is empty."), None);
+        assert_eq!(line("The csv is missing the concept column."), None);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_message() {
+        let mut diags = Diagnostics::default();
+        diags.add(
+            Level::Warning,
+            "orphan",
+            "Orphaned concept warnings",
+            "- \"Pipeline 3\" on line 7 has no dependencies.\n",
+        );
+        let mut out = Vec::new();
+        diags.write_csv(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "severity,code,concept,line,message\nwarning,orphan,Pipeline 3,7,\"\"\"Pipeline 3\"\" on line 7 has no dependencies.\"\n"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+// Semantic diffs between two versions of the concept csv, for
+// curriculum reviews: which concepts were added or removed, and, for
+// the rest, what changed in their categories, dependencies, weights,
+// and scheduled weeks. The old version can be read from git.
+
+use crate::schedule::Schedule;
+use crate::{input, ConceptMap, MODE_NAMES};
+use clap::Args;
+use std::path::{Path, PathBuf};
+use std::process;
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Compare the csv files (or URLs) OLD and NEW, or with --git, FILE
+    /// as of REV with FILE now
+    #[arg(value_name = "FILES", required = true, num_args = 1..=2)]
+    pub files: Vec<PathBuf>,
+    /// The git revision to read the old version of FILE from
+    #[arg(long = "git", value_name = "REV")]
+    pub rev: Option<String>,
+}
+
+/// The old and new versions of the csv.
+pub fn versions(args: &DiffArgs) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    match (&args.rev, args.files.as_slice()) {
+        (Some(rev), [file]) => Ok((git_show(rev, file)?, std::fs::read(file)?)),
+        (None, [old, new]) => Ok((input::load(old)?, input::load(new)?)),
+        (Some(_), _) => anyhow::bail!("With --git, give just the one csv file."),
+        (None, _) => anyhow::bail!("Give the old and new csv files, or --git REV and a csv file."),
+    }
+}
+
+/// What to call the old and new versions in diagnostics.
+pub fn version_names(args: &DiffArgs) -> (String, String) {
+    match (&args.rev, args.files.as_slice()) {
+        (Some(rev), [file]) => (
+            format!("{} at {}", file.display(), rev),
+            file.display().to_string(),
+        ),
+        (_, [old, new]) => (old.display().to_string(), new.display().to_string()),
+        _ => (String::from("old"), String::from("new")),
+    }
+}
+
+fn git_show(rev: &str, file: &Path) -> anyhow::Result<Vec<u8>> {
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    // "./" makes the path relative to `dir`, rather than to the root
+    // of the repository
+    let out = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("{}:./{}", rev, name))
+        .output()?;
+
+    if !out.status.success() {
+        anyhow::bail!(
+            "Couldn't read {} at {} from git: {}",
+            file.display(),
+            rev,
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(out.stdout)
+}
+
+fn names(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|i| format!("\"{}\"", i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Weeks are only compared when both maps could be scheduled.
+fn schedule(m: &mut ConceptMap) -> Option<Schedule> {
+    if m.dependency_order.len() != m.concepts.len() {
+        return None;
+    }
+    let result = m.solve();
+    m.apply(&result);
+    Some(Schedule::new(m))
+}
+
+/// The changes from `old` to `new`, as a Markdown list, for pasting
+/// into reviews.
+pub fn report(old: &mut ConceptMap, new: &mut ConceptMap) -> String {
+    let (old_s, new_s) = (schedule(old), schedule(new));
+    let mut out = String::new();
+    let added: Vec<String> = new
+        .concepts
+        .iter()
+        .filter(|c| !old.lookup.contains_key(&c.concept))
+        .map(|c| c.concept.clone())
+        .collect();
+    let removed: Vec<String> = old
+        .concepts
+        .iter()
+        .filter(|c| !new.lookup.contains_key(&c.concept))
+        .map(|c| c.concept.clone())
+        .collect();
+
+    if !added.is_empty() {
+        out.push_str(&format!("- Added {}\n", names(&added)));
+    }
+    if !removed.is_empty() {
+        out.push_str(&format!("- Removed {}\n", names(&removed)));
+    }
+    for c in &new.concepts {
+        let Some(&offset) = old.lookup.get(&c.concept) else {
+            continue;
+        };
+        let o = &old.concepts[offset];
+        let mut changes = Vec::new();
+
+        if o.kind != c.kind {
+            changes.push(format!("type {} → {}", o.kind.name(), c.kind.name()));
+        }
+        if o.category != c.category {
+            changes.push(format!("category \"{}\" → \"{}\"", o.category, c.category));
+        }
+        let deps_added: Vec<String> = c
+            .dependencies
+            .iter()
+            .filter(|d| !o.dependencies.contains(d))
+            .cloned()
+            .collect();
+        let deps_removed: Vec<String> = o
+            .dependencies
+            .iter()
+            .filter(|d| !c.dependencies.contains(d))
+            .cloned()
+            .collect();
+        if !deps_added.is_empty() {
+            changes.push(format!("now depends on {}", names(&deps_added)));
+        }
+        if !deps_removed.is_empty() {
+            changes.push(format!("no longer depends on {}", names(&deps_removed)));
+        }
+        for (mode, name) in MODE_NAMES.iter().enumerate() {
+            let (was, is) = (o.modes[mode].weight, c.modes[mode].weight);
+            if (was - is).abs() >= 0.005 {
+                changes.push(format!(
+                    "{} weight {} → {}",
+                    name.to_lowercase(),
+                    old.weight_label(mode, was),
+                    new.weight_label(mode, is)
+                ));
+            }
+        }
+        if let (Some(os), Some(ns)) = (&old_s, &new_s) {
+            if os.week(o) != ns.week(c) {
+                changes.push(format!("week {} → {}", os.week(o), ns.week(c)));
+            }
+        }
+        if !changes.is_empty() {
+            out.push_str(&format!(
+                "- Changed \"{}\": {}\n",
+                c.concept,
+                changes.join("; ")
+            ));
+        }
+    }
+
+    let totals: Vec<String> = MODE_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(mode, _)| (old.total_weights[*mode] - new.total_weights[*mode]).abs() >= 0.005)
+        .map(|(mode, name)| {
+            let change = new.total_weights[mode] - old.total_weights[mode];
+            format!(
+                "{} {} → {} ({}{})",
+                name.to_lowercase(),
+                old.units.weeks(old.total_weights[mode]),
+                new.units.weeks(new.total_weights[mode]),
+                if change < 0.0 { "-" } else { "+" },
+                new.units.weeks(change.abs())
+            )
+        })
+        .collect();
+    if !totals.is_empty() {
+        out.push_str(&format!("- Total weights: {}\n", totals.join(", ")));
+    }
+    if let (Some(os), Some(ns)) = (&old_s, &new_s) {
+        if os.nweeks() != ns.nweeks() {
+            out.push_str(&format!(
+                "- The schedule runs {} weeks, rather than {}\n",
+                ns.nweeks(),
+                os.nweeks()
+            ));
+        }
+    }
+    if out.is_empty() {
+        out.push_str("No changes to the concept map.\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "concept,dependencies,category,lecture weight
+a,,x,1
+b,a,x,1
+gone,,x,0.5
+";
+
+    #[test]
+    fn changes_are_listed_by_concept() {
+        let mut old = crate::test_map(OLD);
+        let mut new = crate::test_map(
+            "concept,dependencies,category,lecture weight,type
+a,,y,1,
+b,,x,1.5,
+new,a;b,x,0.5,
+midterm,new,x,0,exam
+",
+        );
+
+        assert_eq!(
+            report(&mut old, &mut new).lines().collect::<Vec<_>>(),
+            [
+                "- Added \"new\", \"midterm\"",
+                "- Removed \"gone\"",
+                "- Changed \"a\": category \"x\" → \"y\"",
+                "- Changed \"b\": no longer depends on \"a\"; lecture weight 1.00 weeks → 1.50 weeks; week 2 → 1",
+                "- Total weights: lecture 2.50 weeks → 3.00 weeks (+0.50 weeks)",
+                "- The schedule runs 4 weeks, rather than 2",
+            ]
+        );
+        assert_eq!(
+            report(&mut old, &mut crate::test_map(OLD)),
+            "No changes to the concept map.\n"
+        );
+    }
+
+    #[test]
+    fn versions_are_named_by_their_files_and_revisions() {
+        let args = |files: &[&str], rev: Option<&str>| DiffArgs {
+            files: files.iter().map(PathBuf::from).collect(),
+            rev: rev.map(String::from),
+        };
+
+        assert_eq!(
+            version_names(&args(&["map.csv"], Some("HEAD~1"))),
+            (String::from("map.csv at HEAD~1"), String::from("map.csv"))
+        );
+        assert_eq!(
+            version_names(&args(&["old.csv", "new.csv"], None)),
+            (String::from("old.csv"), String::from("new.csv"))
+        );
+        assert!(versions(&args(&["old.csv", "new.csv"], Some("HEAD"))).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+// Reading a graphviz prerequisite graph as the concept table, so that
+// hand-drawn maps can be validated and scheduled. Nodes in a labeled
+// subgraph (or a `cluster_<name>` one) get it as their category. All
+// other attributes are ignored.
+
+use crate::graph_input::Graph;
+use crate::input::InputOpts;
+use csv::StringRecord;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Edge, // -> or --
+    Sym(char),
+}
+
+fn tokenize(src: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\n' => {
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // preprocessor output lines
+            '#' if line_start => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '-' if matches!(chars.peek(), Some('>') | Some('-')) => {
+                chars.next();
+                tokens.push(Token::Edge);
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                            // unwrap: just peeked
+                            s.push(chars.next().unwrap());
+                        }
+                        // line continuations
+                        Some('\\') if chars.peek() == Some(&'\n') => {
+                            chars.next();
+                        }
+                        Some(c) => s.push(c),
+                        None => anyhow::bail!("Unterminated string in the dot input."),
+                    }
+                }
+                tokens.push(Token::Id(s));
+            }
+            '<' => {
+                let (mut s, mut depth) = (String::new(), 1);
+                for c in chars.by_ref() {
+                    match c {
+                        '<' => depth += 1,
+                        '>' => depth -= 1,
+                        _ => (),
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    s.push(c);
+                }
+                tokens.push(Token::Id(s));
+            }
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' => tokens.push(Token::Sym(ch)),
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut s = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Id(s));
+            }
+            c => anyhow::bail!("Unexpected \"{}\" in the dot input.", c),
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    graph: Graph,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let t = self.tokens.get(self.pos);
+        self.pos += 1;
+        t
+    }
+
+    fn eat(&mut self, sym: char) -> bool {
+        if self.peek() == Some(&Token::Sym(sym)) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn id(&mut self) -> anyhow::Result<&'a str> {
+        match self.next() {
+            Some(Token::Id(id)) => Ok(id),
+            t => anyhow::bail!("Expected a name in the dot input, found {:?}.", t),
+        }
+    }
+
+    fn expect(&mut self, sym: char) -> anyhow::Result<()> {
+        if !self.eat(sym) {
+            anyhow::bail!(
+                "Expected \"{}\" in the dot input, found {:?}.",
+                sym,
+                self.peek()
+            );
+        }
+        Ok(())
+    }
+
+    // [a=b, c=d][...] as (name, value) pairs
+    fn attributes(&mut self) -> anyhow::Result<Vec<(&'a str, &'a str)>> {
+        let mut attrs = Vec::new();
+
+        while self.eat('[') {
+            while !self.eat(']') {
+                let name = self.id()?;
+                self.expect('=')?;
+                attrs.push((name, self.id()?));
+                if !self.eat(',') {
+                    self.eat(';');
+                }
+            }
+        }
+        Ok(attrs)
+    }
+
+    // The statements up to the closing brace, returning the nodes in
+    // them.
+    fn statements(&mut self) -> anyhow::Result<Vec<String>> {
+        let mut members = Vec::new();
+
+        while !self.eat('}') {
+            match self.peek() {
+                None => anyhow::bail!("Missing \"}}\" in the dot input."),
+                Some(Token::Id(kw))
+                    if ["graph", "node", "edge"].contains(&kw.to_lowercase().as_str())
+                        && self.tokens.get(self.pos + 1) == Some(&Token::Sym('[')) =>
+                {
+                    self.pos += 1;
+                    self.attributes()?;
+                }
+                Some(Token::Id(_)) if self.tokens.get(self.pos + 1) == Some(&Token::Sym('=')) => {
+                    self.pos += 2;
+                    self.id()?;
+                }
+                _ => members.extend(self.edges()?),
+            }
+            if !self.eat(';') {
+                self.eat(',');
+            }
+        }
+        Ok(members)
+    }
+
+    // A node or subgraph, returning its nodes.
+    fn operand(&mut self) -> anyhow::Result<Vec<String>> {
+        let is_subgraph =
+            matches!(self.peek(), Some(Token::Id(kw)) if kw.to_lowercase() == "subgraph");
+        if !is_subgraph && !matches!(self.peek(), Some(Token::Sym('{'))) {
+            let id = self.id()?;
+            // ports
+            while self.eat(':') {
+                self.id()?;
+            }
+            self.graph.node(id);
+            return Ok(vec![id.to_string()]);
+        }
+
+        let mut name = None;
+        if is_subgraph {
+            self.pos += 1;
+            if let Some(Token::Id(id)) = self.peek() {
+                name = Some(id.as_str());
+                self.pos += 1;
+            }
+        }
+        self.expect('{')?;
+        let start = self.pos;
+        let members = self.statements()?;
+        // the subgraph's own label, not its nodes' labels
+        let mut label = None;
+        let mut depth = 0;
+        for (i, t) in self.tokens[start..self.pos].iter().enumerate() {
+            match t {
+                Token::Sym('{') | Token::Sym('[') => depth += 1,
+                Token::Sym('}') | Token::Sym(']') => depth -= 1,
+                Token::Id(l) if depth == 0 && l == "label" => {
+                    if let Some(Token::Id(v)) = self.tokens.get(start + i + 2) {
+                        label = Some(v.clone());
+                    }
+                }
+                _ => (),
+            }
+        }
+        let category = label.or_else(|| {
+            name.and_then(|n| n.strip_prefix("cluster"))
+                .map(|n| n.trim_start_matches('_').to_string())
+        });
+        if let Some(cat) = category.filter(|c| !c.is_empty()) {
+            for m in &members {
+                self.graph.category(m, &cat);
+            }
+        }
+        Ok(members)
+    }
+
+    // A node statement, or chain of edges, and its attributes.
+    fn edges(&mut self) -> anyhow::Result<Vec<String>> {
+        let mut from = self.operand()?;
+        let mut members = from.clone();
+        let mut edge = false;
+
+        while self.peek() == Some(&Token::Edge) {
+            self.pos += 1;
+            edge = true;
+            let to = self.operand()?;
+            for f in &from {
+                for t in &to {
+                    self.graph.edge(f, t);
+                }
+            }
+            members.extend(to.iter().cloned());
+            from = to;
+        }
+        for (name, value) in self.attributes()? {
+            if !edge && name == "label" {
+                self.graph.label(&members[0], value);
+            }
+        }
+        Ok(members)
+    }
+}
+
+/// The concept table from a dot graph.
+pub fn read(src: &str, opts: &InputOpts) -> anyhow::Result<(StringRecord, Vec<StringRecord>)> {
+    let tokens = tokenize(src)?;
+    let mut p = Parser {
+        tokens: &tokens,
+        pos: 0,
+        graph: Graph::new(opts.edges_from_prerequisites),
+    };
+
+    if matches!(p.peek(), Some(Token::Id(kw)) if kw.to_lowercase() == "strict") {
+        p.pos += 1;
+    }
+    match p.id()?.to_lowercase().as_str() {
+        "graph" | "digraph" => (),
+        other => anyhow::bail!(
+            "Expected a graph or digraph in the dot input, found \"{}\".",
+            other
+        ),
+    }
+    if let Some(Token::Id(_)) = p.peek() {
+        p.pos += 1;
+    }
+    p.expect('{')?;
+    p.statements()?;
+
+    Ok(p.graph.table(opts.dep_separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_map, Cli};
+    use clap::Parser as _;
+
+    fn table(src: &str) -> Vec<Vec<String>> {
+        let cli = Cli::parse_from(["concept_map", "--from", "dot"]);
+        let (_, rows) = read(src, &cli.input).unwrap();
+        rows.iter()
+            .map(|r| r.iter().map(String::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn generated_graphs_read_back_as_their_concepts() {
+        let m = test_map(
+            r#"concept,category,dependencies
+arrays,x,
+C:\,y,arrays
+"say ""hi""",y,C:\;arrays
+"#,
+        );
+        let dot = m.render(&Cli::parse_from(["concept_map"]).render);
+
+        assert_eq!(
+            table(&dot),
+            [
+                ["arrays", "", ""],
+                ["C:\\", "", "arrays"],
+                ["say \"hi\"", "", "C:\\;arrays"],
+            ]
+        );
+    }
+
+    #[test]
+    fn subgraphs_are_categories() {
+        let rows = table(
+            "digraph {
+  subgraph cluster_c { label=\"C\"; b -> a [color=red]; }
+  // a comment
+  c -> b;
+  summary;
+}",
+        );
+
+        assert_eq!(rows, [["b", "C", "a"], ["a", "C", ""], ["c", "", "b"]]);
+    }
+
+    #[test]
+    fn comments_and_preprocessor_lines_are_skipped() {
+        let tokens = tokenize("# 1 \"map.gv\"\na /* b -> */ -- \"c\\\"d\" // e\n;").unwrap();
+
+        assert_eq!(
+            tokens,
+            [
+                Token::Id(String::from("a")),
+                Token::Edge,
+                Token::Id(String::from("c\"d")),
+                Token::Sym(';'),
+            ]
+        );
+        assert!(tokenize("a -> \"b").is_err());
+        assert!(tokenize("a -> !b").is_err());
+    }
+}
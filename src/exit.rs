@@ -0,0 +1,96 @@
+// Exit codes, so that Makefiles and CI can tell why a run failed:
+//
+// 0  success
+// 1  any other error
+// 2  bad command line (from clap)
+// 3  I/O error: reading the input, or writing an output file
+// 4  parse error: the input isn't a valid csv, dot, or Mermaid map
+// 5  validation error: with --strict (or --fail-on warnings), errors
+//    in the csv file or warnings about it, and with --fail-on errors,
+//    just errors
+// 6  infeasible schedule: dependency cycles, when scheduling, and with
+//    --strict, concepts that can't be taught in time or milestones
+//    before what they depend on
+
+use std::fmt;
+use std::io;
+
+pub const IO: u8 = 3;
+pub const PARSE: u8 = 4;
+pub const VALIDATION: u8 = 5;
+pub const INFEASIBLE: u8 = 6;
+
+/// An error tagged with its exit code.
+#[derive(Debug)]
+pub struct Failure {
+    code: u8,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for Failure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+pub fn fail(code: u8, error: anyhow::Error) -> anyhow::Error {
+    anyhow::Error::new(Failure { code, error })
+}
+
+/// Errors reading the input are parse errors, unless they're I/O
+/// errors.
+pub fn parse(error: anyhow::Error) -> anyhow::Error {
+    if code(&error) == IO {
+        error
+    } else {
+        fail(PARSE, error)
+    }
+}
+
+pub fn code(error: &anyhow::Error) -> u8 {
+    for e in error.chain() {
+        if let Some(f) = e.downcast_ref::<Failure>() {
+            return f.code;
+        }
+        if e.is::<io::Error>() {
+            return IO;
+        }
+        if let Some(e) = e.downcast_ref::<csv::Error>() {
+            return if e.is_io_error() { IO } else { PARSE };
+        }
+    }
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_found_anywhere_in_the_chain() {
+        let io = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound));
+        let csv = csv::Reader::from_reader("a\n1,2\n".as_bytes())
+            .records()
+            .find_map(Result::err)
+            .unwrap(); // the second record is too long
+
+        assert_eq!(code(&anyhow::anyhow!("other")), 1);
+        assert_eq!(code(&io.context("reading the map")), IO);
+        assert_eq!(code(&parse(anyhow::Error::new(csv))), PARSE);
+        assert_eq!(code(&parse(anyhow::anyhow!("bad dot"))), PARSE);
+        assert_eq!(
+            code(&fail(VALIDATION, anyhow::anyhow!("strict")).context("checking")),
+            VALIDATION
+        );
+        assert_eq!(
+            fail(INFEASIBLE, anyhow::anyhow!("a cycle")).to_string(),
+            "a cycle"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+// Machine-readable exports of the solved schedule, for the scripts
+// that import it into the LMS and the gradebook, and for setting up
+// Canvas course shells.
+
+use crate::diagnostics::Diagnostics;
+use crate::schedule::Schedule;
+use crate::{hundredths, ConceptMap};
+use serde::Serialize;
+use std::io;
+
+#[derive(Serialize)]
+pub struct ScheduleRow<'a> {
+    concept: &'a str,
+    category: &'a str,
+    week: u64,
+    #[serde(rename = "earliest start")]
+    earliest_start: f64,
+    #[serde(rename = "latest start")]
+    latest_start: f64,
+    slack: f64,
+    #[serde(rename = "lecture weight")]
+    lecture_weight: f64,
+    #[serde(rename = "lab weight")]
+    lab_weight: f64,
+    #[serde(rename = "hw weight")]
+    hw_weight: f64,
+}
+
+// The concepts the course teaches, in week order, and then csv order.
+// Requires the map to be solved.
+pub fn rows(m: &ConceptMap) -> Vec<ScheduleRow<'_>> {
+    let schedule = Schedule::new(m);
+    let mut rows: Vec<_> = m
+        .concepts
+        .iter()
+        .filter(|c| c.is_taught())
+        .map(|c| ScheduleRow {
+            concept: &c.concept,
+            category: &c.category,
+            week: schedule.week(c),
+            earliest_start: hundredths(c.modes[0].range.earliest_start),
+            latest_start: hundredths(c.latest_start()),
+            slack: hundredths(c.slack()),
+            lecture_weight: hundredths(m.shown_weight(0, c.modes[0].weight)),
+            lab_weight: hundredths(m.shown_weight(1, c.modes[1].weight)),
+            hw_weight: hundredths(m.shown_weight(2, c.modes[2].weight)),
+        })
+        .collect();
+
+    rows.sort_by_key(|r| r.week);
+    rows
+}
+
+pub fn schedule_csv<W: io::Write>(m: &ConceptMap, out: W) -> anyhow::Result<()> {
+    let mut w = csv::Writer::from_writer(out);
+
+    for r in rows(m) {
+        w.serialize(r)?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+pub fn schedule_json<W: io::Write>(m: &ConceptMap, mut out: W) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(&mut out, &rows(m))?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct LmsRow<'a> {
+    week: u64,
+    topic: &'a str,
+    description: &'a str,
+    link: &'a str,
+}
+
+/// A flat week, topic, description, and link csv, in the shape that
+/// Moodle's and Blackboard's imports take, of the concepts the course
+/// teaches. Requires the map to be solved.
+pub fn lms_csv<W: io::Write>(m: &ConceptMap, out: W) -> anyhow::Result<()> {
+    let schedule = Schedule::new(m);
+    let mut concepts: Vec<_> = m.concepts.iter().filter(|c| c.is_taught()).collect();
+    concepts.sort_by_key(|c| schedule.week(c));
+    let mut w = csv::Writer::from_writer(out);
+
+    for c in concepts {
+        w.serialize(LmsRow {
+            week: schedule.week(c),
+            topic: &c.concept,
+            description: &c.description,
+            link: c.url.as_deref().unwrap_or(""),
+        })?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+// The shape of Canvas' module and module item APIs, so each module can
+// be posted as is.
+#[derive(Serialize)]
+struct CanvasModule<'a> {
+    name: String,
+    position: u64,
+    items: Vec<CanvasItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct CanvasItem<'a> {
+    title: &'a str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_tab: Option<bool>,
+}
+
+/// A module per week with an item per concept: links to the concepts'
+/// `url`s, and text headers for the concepts without one. Requires
+/// the map to be solved.
+pub fn canvas_modules<W: io::Write>(m: &ConceptMap, mut out: W) -> anyhow::Result<()> {
+    let schedule = Schedule::new(m);
+    let modules: Vec<CanvasModule> = (1..=schedule.nweeks())
+        .map(|week| CanvasModule {
+            name: format!("Week {}", week),
+            position: week,
+            items: m
+                .concepts
+                .iter()
+                .filter(|c| c.is_taught() && schedule.week(c) == week)
+                .map(|c| CanvasItem {
+                    title: &c.concept,
+                    kind: if c.url.is_some() {
+                        "ExternalUrl"
+                    } else {
+                        "SubHeader"
+                    },
+                    external_url: c.url.as_deref(),
+                    new_tab: c.url.as_ref().map(|_| true),
+                })
+                .collect(),
+        })
+        .filter(|module| !module.items.is_empty())
+        .collect();
+
+    serde_json::to_writer_pretty(&mut out, &modules)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Weights {
+    lecture: f64,
+    lab: f64,
+    hw: f64,
+}
+
+impl Weights {
+    fn new(m: &ConceptMap, ws: [f64; 3]) -> Self {
+        let w = |mode: usize| hundredths(m.shown_weight(mode, ws[mode]));
+
+        Weights {
+            lecture: w(0),
+            lab: w(1),
+            hw: w(2),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReportConcept<'a> {
+    concept: &'a str,
+    #[serde(rename = "type")]
+    kind: String,
+    category: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instructor: Option<&'a str>,
+    dependencies: &'a [String],
+    depth: usize,
+    weights: Weights,
+    #[serde(rename = "earliest starts")]
+    earliest_starts: Weights,
+}
+
+#[derive(Serialize)]
+struct ReportCategory<'a> {
+    category: &'a str,
+    concepts: usize,
+    weights: Weights,
+}
+
+#[derive(Serialize)]
+struct Stats<'a> {
+    concepts: usize,
+    categories: Vec<ReportCategory<'a>>,
+    #[serde(rename = "total weights")]
+    total_weights: Weights,
+    #[serde(rename = "max depth")]
+    max_depth: usize,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    concepts: Vec<ReportConcept<'a>>,
+    schedule: Vec<ScheduleRow<'a>>,
+    stats: Stats<'a>,
+    diagnostics: &'a Diagnostics,
+}
+
+/// The solved map, its schedule, statistics, and all of the
+/// diagnostics, as one json document for dashboards. Requires the map
+/// to be solved.
+pub fn report_json<W: io::Write>(
+    m: &ConceptMap,
+    diagnostics: &Diagnostics,
+    mut out: W,
+) -> anyhow::Result<()> {
+    let depths = m.depths();
+    let concepts = m
+        .concepts
+        .iter()
+        .map(|c| ReportConcept {
+            concept: &c.concept,
+            kind: c.kind.name().to_lowercase(),
+            category: &c.category,
+            instructor: c.instructor.as_deref(),
+            dependencies: &c.dependencies,
+            depth: depths[c.offset],
+            weights: Weights::new(m, [0, 1, 2].map(|i| c.modes[i].weight)),
+            // start times, so never percentages
+            earliest_starts: Weights {
+                lecture: hundredths(c.modes[0].range.earliest_start),
+                lab: hundredths(c.modes[1].range.earliest_start),
+                hw: hundredths(c.modes[2].range.earliest_start),
+            },
+        })
+        .collect();
+    let categories = m
+        .categories()
+        .into_iter()
+        .map(|cat| ReportCategory {
+            category: cat,
+            concepts: m.concepts.iter().filter(|c| &c.category == cat).count(),
+            weights: Weights::new(m, m.category_weights(cat)),
+        })
+        .collect();
+    let report = Report {
+        concepts,
+        schedule: rows(m),
+        stats: Stats {
+            concepts: m.concepts.len(),
+            categories,
+            total_weights: Weights::new(m, m.total_weights),
+            max_depth: depths.iter().copied().max().unwrap_or(0),
+        },
+        diagnostics,
+    };
+
+    serde_json::to_writer_pretty(&mut out, &report)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The critical path's slack adds up to floating-point noise.
+    const CRITICAL_PATH: &str = "concept,dependencies,lecture weight
+a,,0.1
+b,a,0.2
+c,b,0.3
+d,c,0.7
+";
+
+    #[test]
+    fn critical_path_has_no_negative_zeros() {
+        let m = crate::test_map(CRITICAL_PATH);
+        let mut csv = Vec::new();
+        schedule_csv(&m, &mut csv).unwrap();
+
+        for r in rows(&m) {
+            assert!(r.slack.is_sign_positive(), "slack of {}", r.concept);
+            assert!(r.latest_start.is_sign_positive(), "latest of {}", r.concept);
+        }
+        assert!(!String::from_utf8(csv).unwrap().contains("-0"));
+    }
+}
@@ -0,0 +1,105 @@
+// Searching for concepts, for maps too large to eyeball. Matches are
+// case-insensitive: substrings of the name, an alias, or the
+// description, or failing those, the query's characters appearing in
+// order in the name or an alias (so "vmem" finds "virtual memory").
+
+use crate::schedule::Schedule;
+use crate::{category_label, Concept, ConceptMap};
+
+// How a concept matched, best first.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Match<'a> {
+    Name,
+    Alias(&'a str),
+    Description,
+    Fuzzy,
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut hay = haystack.chars();
+
+    needle.chars().all(|n| hay.any(|h| h == n))
+}
+
+fn matches<'a>(c: &'a Concept, query: &str) -> Option<Match<'a>> {
+    if c.concept.to_lowercase().contains(query) {
+        Some(Match::Name)
+    } else if let Some(a) = c.aliases.iter().find(|a| a.to_lowercase().contains(query)) {
+        Some(Match::Alias(a))
+    } else if c.description.to_lowercase().contains(query) {
+        Some(Match::Description)
+    } else if c.names().any(|n| is_subsequence(query, &n.to_lowercase())) {
+        Some(Match::Fuzzy)
+    } else {
+        None
+    }
+}
+
+/// The concepts matching `query` with their categories and weeks.
+/// Requires the map to be solved.
+pub fn report(m: &ConceptMap, query: &str) -> String {
+    let query = query.trim().to_lowercase();
+    let schedule = Schedule::new(m);
+    let mut found: Vec<_> = m
+        .concepts
+        .iter()
+        .filter_map(|c| matches(c, &query).map(|how| (how, c)))
+        .collect();
+    // stable, so equally good matches stay in csv order
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut out = String::new();
+
+    if found.is_empty() {
+        out.push_str(&format!("No concepts match \"{}\".\n", query));
+    }
+    for (how, c) in found {
+        let how = match how {
+            Match::Name => String::new(),
+            Match::Alias(a) => format!(" (alias \"{}\")", a),
+            Match::Description => String::from(" (in its description)"),
+            Match::Fuzzy => String::from(" (fuzzy)"),
+        };
+        out.push_str(&format!(
+            "{} [{}], week {}{}\n",
+            c.concept,
+            category_label(&c.category),
+            schedule.week(c),
+            how
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_are_best_first() {
+        let m = crate::test_map(
+            "concept,dependencies,category,aliases,description
+paging,,memory,virtual memory,
+virtual machines,,os,,
+caches,,memory,,Memory near the CPU
+segmentation,,memory,,
+",
+        );
+
+        assert_eq!(
+            report(&m, " Memory "),
+            "paging [memory], week 1 (alias \"virtual memory\")\n\
+             caches [memory], week 1 (in its description)\n"
+        );
+        assert_eq!(
+            report(&m, "vm"),
+            "paging [memory], week 1 (fuzzy)\n\
+             virtual machines [os], week 1 (fuzzy)\n"
+        );
+        assert_eq!(
+            report(&m, "virtual"),
+            "virtual machines [os], week 1\npaging [memory], week 1 (alias \"virtual memory\")\n"
+        );
+        assert_eq!(report(&m, "tlb"), "No concepts match \"tlb\".\n");
+    }
+}
@@ -0,0 +1,100 @@
+// The schedule as a Mermaid gantt chart, for presenting the course to
+// the curriculum committee: a section per category, and in it a bar
+// per concept, from its lecture's start to its end. Mermaid charts
+// dates, so the weeks are counted from a Sunday (1970-01-04, as
+// seconds since the epoch in the chart), which labels them "week 01",
+// "week 02", and so on. Concepts without lecture weight, e.g., exams,
+// are milestones.
+
+use crate::schedule::Schedule;
+use crate::{category_label, Concept, ConceptMap};
+
+// The first Sunday after the epoch, the start of week 1.
+const WEEK_1: f64 = 3.0 * 24.0 * 60.0 * 60.0;
+const WEEK: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+// Colons end a task's name, and semicolons and hashes end its line.
+fn text(name: &str) -> String {
+    name.replace([':', ';', '#'], " ")
+}
+
+// The lecture's start, in weeks from the start of the course, in the
+// week the schedule teaches it in.
+fn start(c: &Concept, week: u64) -> f64 {
+    let earliest = c.modes[0].range.earliest_start;
+
+    if earliest.floor() as u64 + 1 == week {
+        earliest
+    } else {
+        (week - 1) as f64
+    }
+}
+
+/// Requires the map to be solved.
+pub fn mermaid(m: &ConceptMap) -> String {
+    let s = Schedule::new(m);
+    let mut out = String::new();
+    let seconds = |weeks: f64| (WEEK_1 + weeks * WEEK).round() as u64;
+
+    out.push_str("gantt\n    title Concept map schedule\n    dateFormat X\n    axisFormat week %U\n    tickInterval 1week\n");
+    for cat in m.categories() {
+        let concepts: Vec<&Concept> = m
+            .dependency_order
+            .iter()
+            .map(|n| m.dependency_to_concept(n))
+            .filter(|c| &c.category == cat)
+            .filter(|c| c.is_taught())
+            .collect();
+        if concepts.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("    section {}\n", text(category_label(cat))));
+        for c in concepts {
+            let from = start(c, s.week(c));
+            let weight = c.modes[0].weight;
+            let tags = if weight > 0.0 { "" } else { "milestone, " };
+            out.push_str(&format!(
+                "    {} :{}c{}, {}, {}\n",
+                text(&c.concept),
+                tags,
+                c.offset,
+                seconds(from),
+                seconds(from + weight)
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_run_from_the_lectures_start_to_end() {
+        let m = crate::test_map(
+            "concept,dependencies,category,lecture weight,type,status
+a: intro,,x; y,0.5,,
+b,a: intro,x; y,1,,
+old,,x; y,1,,retired
+midterm,b,exams,0,exam,
+",
+        );
+
+        assert_eq!(
+            mermaid(&m),
+            "gantt
+    title Concept map schedule
+    dateFormat X
+    axisFormat week %U
+    tickInterval 1week
+    section x  y
+    a  intro :c0, 259200, 561600
+    b :c1, 561600, 1166400
+    section exams
+    midterm :milestone, c3, 1166400, 1166400
+"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// A glossary handout from the concepts' descriptions, foundational
+// terms first (by depth in the dependency graph), each linking to the
+// terms it builds on. Concepts without descriptions, and those the
+// course doesn't teach, are left out.
+
+use crate::html::escape;
+use crate::{category_label, Concept, ConceptMap};
+
+pub fn md_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for ch in s.chars() {
+        if "\\`*_[]<>#|".contains(ch) {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn is_entry(c: &Concept) -> bool {
+    c.is_taught() && !c.description.is_empty()
+}
+
+fn entries(m: &ConceptMap) -> Vec<&Concept> {
+    let depths = m.depths();
+    let mut entries: Vec<_> = m.concepts.iter().filter(|c| is_entry(c)).collect();
+
+    // stable, so concepts at the same depth stay in csv order
+    entries.sort_by_key(|c| depths[c.offset]);
+    entries
+}
+
+pub fn markdown(m: &ConceptMap) -> String {
+    let mut out = String::from("# Glossary\n");
+
+    for c in entries(m) {
+        out.push_str(&format!(
+            "\n## <a id=\"{}\"></a>{}\n\n*{}*\n\n{}\n",
+            c.slug,
+            md_escape(&c.concept),
+            md_escape(category_label(&c.category)),
+            md_escape(&c.description)
+        ));
+        let deps: Vec<String> = c
+            .dependencies
+            .iter()
+            .map(|d| {
+                let d = m.dependency_to_concept(d);
+                if !is_entry(d) {
+                    md_escape(&d.concept)
+                } else {
+                    format!("[{}](#{})", md_escape(&d.concept), d.slug)
+                }
+            })
+            .collect();
+        if !deps.is_empty() {
+            out.push_str(&format!("\nBuilds on: {}\n", deps.join(", ")));
+        }
+    }
+
+    out
+}
+
+pub fn html(m: &ConceptMap) -> String {
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Glossary</title>\n<style>body { font-family: sans-serif; margin: 2em; max-width: 50em; } dt { font-weight: bold; margin-top: 1em; } .category, .deps { color: #555; font-size: 0.85em; } :target { background: #ffa; }</style>\n</head>\n<body>\n<h1>Glossary</h1>\n<dl>\n");
+    for c in entries(m) {
+        let deps: Vec<String> = c
+            .dependencies
+            .iter()
+            .map(|d| {
+                let d = m.dependency_to_concept(d);
+                if !is_entry(d) {
+                    escape(&d.concept)
+                } else {
+                    format!("<a href=\"#{}\">{}</a>", d.slug, escape(&d.concept))
+                }
+            })
+            .collect();
+
+        out.push_str(&format!(
+            "<dt id=\"{}\">{} <span class=\"category\">{}</span></dt>\n<dd>{}\n",
+            c.slug,
+            escape(&c.concept),
+            escape(category_label(&c.category)),
+            escape(&c.description)
+        ));
+        if !deps.is_empty() {
+            out.push_str(&format!(
+                "<div class=\"deps\">Builds on: {}</div>\n",
+                deps.join(", ")
+            ));
+        }
+        out.push_str("</dd>\n");
+    }
+    out.push_str("</dl>\n</body>\n</html>\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERMS: &str = "concept,dependencies,category,description,lecture weight,status
+C,,extern:C,The C language,0,
+stack,C;heap,memory,Where *locals* live,1,
+heap,,memory,,1,
+pointers,C;stack,memory,Addresses,1,
+old,,memory,Segments,1,retired
+";
+
+    #[test]
+    fn entries_link_to_the_entries_they_build_on() {
+        assert_eq!(
+            markdown(&crate::test_map(TERMS)),
+            "# Glossary
+
+## <a id=\"stack\"></a>stack
+
+*memory*
+
+Where \\*locals\\* live
+
+Builds on: C, heap
+
+## <a id=\"pointers\"></a>pointers
+
+*memory*
+
+Addresses
+
+Builds on: C, [stack](#stack)
+"
+        );
+    }
+
+    #[test]
+    fn html_entries_link_too() {
+        let html = html(&crate::test_map(TERMS));
+
+        assert!(html.contains("<dt id=\"pointers\">pointers <span class=\"category\">memory</span></dt>\n<dd>Addresses\n<div class=\"deps\">Builds on: C, <a href=\"#stack\">stack</a></div>\n"), "{}", html);
+        assert!(!html.contains("Segments"), "{}", html);
+        assert_eq!(md_escape("a_b [c]"), "a\\_b \\[c\\]");
+    }
+}
@@ -0,0 +1,98 @@
+// Dead branches of the curriculum: concepts that none of its goals
+// (e.g., the final project's skills, given with --goal) build on, even
+// softly, or through corequisites, so they could be cut without the
+// goals noticing.
+
+use crate::{normalize, ConceptMap, Status};
+use std::collections::HashSet;
+
+/// Warnings for the goals that aren't concepts, and for the concepts
+/// none of the goals transitively depend on. Milestones and retired
+/// concepts aren't goals' dependencies to begin with.
+pub fn check(m: &ConceptMap, goals: &[String]) -> String {
+    let mut warnings = String::new();
+    let mut pending = Vec::new();
+
+    for g in goals {
+        let name = normalize::name(g.trim());
+        match m.lookup.get(&name) {
+            Some(&offset) => pending.push(offset),
+            None => warnings.push_str(&format!(
+                "- Goal \"{}\" does not correspond to a concept. Ignoring the goal.\n",
+                name
+            )),
+        }
+    }
+    if pending.is_empty() {
+        return warnings;
+    }
+
+    let mut reached: HashSet<usize> = pending.iter().copied().collect();
+    while let Some(o) = pending.pop() {
+        let c = &m.concepts[o];
+        for d in c
+            .dependencies
+            .iter()
+            .chain(&c.soft_dependencies)
+            .chain(&c.corequisites)
+        {
+            let d = m.dependency_to_concept(d).offset;
+            if reached.insert(d) {
+                pending.push(d);
+            }
+        }
+    }
+
+    let goals: Vec<&str> = reached_goals(m, goals);
+    for c in m.concepts.iter().filter(|c| {
+        !reached.contains(&c.offset) && !c.kind.is_milestone() && c.status != Status::Retired
+    }) {
+        warnings.push_str(&format!(
+            "- \"{}\" on {} isn't needed for any of the goals ({}).\n",
+            c.concept,
+            c.location,
+            goals.join(", ")
+        ));
+    }
+    warnings
+}
+
+// The goals that are concepts, by their names in the map.
+fn reached_goals<'a>(m: &'a ConceptMap, goals: &[String]) -> Vec<&'a str> {
+    goals
+        .iter()
+        .filter_map(|g| m.lookup.get(&normalize::name(g.trim())))
+        .map(|&o| m.concepts[o].concept.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concepts_no_goal_needs_are_dead_branches() {
+        let m = crate::test_map(
+            "concept,dependencies,corequisites,status,type
+a,,,,
+b,a;c?,d,,
+c,,,,
+d,,,,
+unused,a,,,
+old,,,retired,
+exam,unused,,,exam
+",
+        );
+        let goals = [String::from(" b "), String::from("nope")];
+
+        assert_eq!(
+            check(&m, &goals),
+            "- Goal \"nope\" does not correspond to a concept. Ignoring the goal.\n\
+             - \"unused\" on line 6 isn't needed for any of the goals (b).\n"
+        );
+        assert_eq!(
+            check(&m, &goals[1..]),
+            "- Goal \"nope\" does not correspond to a concept. Ignoring the goal.\n"
+        );
+    }
+}
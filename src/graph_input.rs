@@ -0,0 +1,150 @@
+// The concept table from a prerequisite graph drawn in another tool
+// (see `dot_input` and `mermaid_input`): nodes are concepts, named by
+// their labels (or ids), and edges are dependencies. The nodes our own
+// graphs add, like the summary, aren't concepts.
+
+use crate::input::join_list;
+use csv::StringRecord;
+use std::collections::HashMap;
+
+pub struct Graph {
+    nodes: Vec<String>, // ids, in the order they first appear
+    labels: HashMap<String, String>,
+    categories: HashMap<String, String>,
+    dependencies: HashMap<String, Vec<String>>,
+    // By default, edges point from concepts to their dependencies, as
+    // in the generated graphs.
+    from_prerequisites: bool,
+}
+
+impl Graph {
+    pub fn new(from_prerequisites: bool) -> Self {
+        Graph {
+            nodes: Vec::new(),
+            labels: HashMap::new(),
+            categories: HashMap::new(),
+            dependencies: HashMap::new(),
+            from_prerequisites,
+        }
+    }
+
+    pub fn node(&mut self, id: &str) {
+        if !self.dependencies.contains_key(id) {
+            self.nodes.push(id.to_string());
+            self.dependencies.insert(id.to_string(), Vec::new());
+        }
+    }
+
+    pub fn label(&mut self, id: &str, label: &str) {
+        self.labels.insert(id.to_string(), label.to_string());
+    }
+
+    // The innermost group a node is in is its category, so the first
+    // one assigned sticks.
+    pub fn category(&mut self, id: &str, category: &str) {
+        self.categories
+            .entry(id.to_string())
+            .or_insert_with(|| category.to_string());
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str) {
+        let (concept, dependency) = if self.from_prerequisites {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        self.node(concept);
+        self.node(dependency);
+        self.dependencies
+            .get_mut(concept)
+            .unwrap() // just added
+            .push(dependency.to_string());
+    }
+
+    // For labels like the ones the generated graphs have, with the
+    // earliest starts on the lines after the name.
+    fn name(&self, id: &str) -> String {
+        let label = self.labels.get(id).map_or(id, |l| l.as_str());
+        let first = label
+            .split('\n')
+            .flat_map(|l| l.split("\\n"))
+            .next()
+            .unwrap_or(label);
+
+        first.trim_end_matches("\\l").trim().to_string()
+    }
+
+    // The summary, footer, and category legend nodes of the generated
+    // graphs, which aren't concepts. The legend's are `c<offset>_<name>`
+    // (the concepts' are `n<offset>_<name>`), and none have edges.
+    fn is_generated(&self, id: &str) -> bool {
+        let legend = id.strip_prefix('c').is_some_and(|rest| {
+            let name = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+            name.len() < rest.len() && (name.is_empty() || name.starts_with('_'))
+        });
+
+        (id == "summary" || id == "footer" || legend)
+            && self.dependencies[id].is_empty()
+            && !self.dependencies.values().flatten().any(|d| d == id)
+    }
+
+    pub fn table(&self, dep_separator: char) -> (StringRecord, Vec<StringRecord>) {
+        let rows = self
+            .nodes
+            .iter()
+            .filter(|id| !self.is_generated(id))
+            .map(|id| {
+                let deps: Vec<String> =
+                    self.dependencies[id].iter().map(|d| self.name(d)).collect();
+                StringRecord::from(vec![
+                    self.name(id),
+                    self.categories.get(id).cloned().unwrap_or_default(),
+                    join_list(&deps, dep_separator),
+                ])
+            })
+            .collect();
+
+        (
+            StringRecord::from(vec!["concept", "category", "dependencies"]),
+            rows,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concepts_are_named_by_their_labels_first_line() {
+        let mut g = Graph::new(true);
+        g.edge("a", "b");
+        g.label("a", "Pointers\\nEarliest lecture: 0.00\\l");
+        g.label("b", "Heap\nallocation");
+        g.category("b", "memory");
+        g.category("b", "outer");
+        g.node("summary");
+        let (headers, rows) = g.table(';');
+        let rows: Vec<Vec<&str>> = rows.iter().map(|r| r.iter().collect()).collect();
+
+        assert_eq!(headers, vec!["concept", "category", "dependencies"]);
+        // edges from prerequisites point to the concepts needing them
+        assert_eq!(
+            rows,
+            [vec!["Heap", "memory", "Pointers"], vec!["Pointers", "", ""]]
+        );
+    }
+
+    #[test]
+    fn generated_nodes_are_only_skipped_without_edges() {
+        let mut g = Graph::new(false);
+        g.node("footer");
+        g.node("c3_memory");
+        g.node("c3po");
+        g.edge("summary", "c1");
+        let (_, rows) = g.table(';');
+        let names: Vec<&str> = rows.iter().map(|r| &r[0]).collect();
+
+        assert_eq!(names, ["c3po", "summary", "c1"]);
+    }
+}
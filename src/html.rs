@@ -0,0 +1,186 @@
+// A self-contained html page for the concept map. Categories are
+// collapsible groups that start out collapsed, so the page opens on
+// the overview (per-category totals and cross-category dependencies),
+// and expanding a category shows its concepts in place. For screen
+// readers, the groups and tables are labeled, and each concept's
+// category and dependencies are given in text, never just by color.
+
+use crate::{category_label, plural, ConceptMap};
+use std::collections::BTreeMap;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+details.category { border-left: 0.6em solid; margin: 0.5em 0; padding: 0.3em 0.8em; }
+details.category > summary { cursor: pointer; font-size: 1.2em; }
+.totals, .crossdeps { color: #555; font-size: 0.85em; margin-left: 1em; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+td, th { border-bottom: 1px solid #ddd; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+caption { color: #555; font-size: 0.85em; text-align: left; }
+:target { background: #ffa; }
+";
+
+// Expanding everything is handy for printing, and following a link to
+// a concept in a collapsed category has to open it.
+const SCRIPT: &str = "
+function setAll(open) {
+  document.querySelectorAll('details.category').forEach(function (d) { d.open = open; });
+}
+function openTarget() {
+  var t = document.getElementById(decodeURIComponent(location.hash.slice(1)));
+  for (var e = t; e; e = e.parentElement) {
+    if (e.tagName === 'DETAILS') { e.open = true; }
+  }
+  if (t) { t.scrollIntoView(); }
+}
+window.addEventListener('hashchange', openTarget);
+window.addEventListener('load', openTarget);
+";
+
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+// The graph uses X11 color names that css doesn't know.
+pub fn css_color(graphviz: &str) -> &str {
+    match graphviz {
+        "cadetblue1" => "#98f5ff",
+        "chocolate1" => "#ff7f24",
+        "darkgoldenrod1" => "#ffb90f",
+        "darkorchid1" => "#bf3eff",
+        "deeppink" => "#ff1493",
+        "dodgerblue2" => "#1c86ee",
+        "firebrick1" => "#ff3030",
+        "gray38" => "#616161",
+        "green3" => "#00cd00",
+        "navy" => "#000080",
+        "orchid" => "#da70d6",
+        "teal" => "#008080",
+        "violetred" => "#d02090",
+        "yellow1" => "#ffff00",
+        "tomato1" => "#ff6347",
+        other => other,
+    }
+}
+
+pub fn render(m: &ConceptMap) -> String {
+    let colors = m.category_colors();
+    let mut out = String::new();
+
+    out.push_str(&format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Concept map</title>\n<style>{}</style>\n<script>{}</script>\n</head>\n<body>\n<main>\n",
+        STYLE, SCRIPT
+    ));
+    out.push_str(&format!(
+        "<h1>Concept map</h1>\n<p>Lecture {}, lab {}, HW {}.</p>\n",
+        m.weight_label(0, m.total_weights[0]),
+        m.weight_label(1, m.total_weights[1]),
+        m.weight_label(2, m.total_weights[2])
+    ));
+    out.push_str("<p><button onclick=\"setAll(true)\">Expand all</button> <button onclick=\"setAll(false)\">Collapse all</button></p>\n");
+
+    for cat in m.categories() {
+        let ws = m.category_weights(cat);
+        let concepts: Vec<_> = m.concepts.iter().filter(|c| &c.category == cat).collect();
+        let mut crossdeps: BTreeMap<&String, usize> = BTreeMap::new();
+        for c in &concepts {
+            for d in &c.dependencies {
+                let dep_c = m.dependency_to_concept(d);
+
+                if &dep_c.category != cat {
+                    *crossdeps.entry(&dep_c.category).or_insert(0) += 1;
+                }
+            }
+        }
+        let name = category_label(cat);
+
+        out.push_str(&format!("<details class=\"category\" style=\"border-color: {}\" aria-label=\"Category {}, {}\">\n",
+            css_color(colors.get(cat).unwrap()), // unwrap: all categories have colors
+            escape(name),
+            plural(concepts.len(), "concept", "concepts")
+        ));
+        out.push_str(&format!(
+            "<summary>{} <span class=\"totals\">{}; lecture {}, lab {}, HW {}</span>\n",
+            escape(name),
+            plural(concepts.len(), "concept", "concepts"),
+            m.weight_label(0, ws[0]),
+            m.weight_label(1, ws[1]),
+            m.weight_label(2, ws[2])
+        ));
+        if !crossdeps.is_empty() {
+            let deps: Vec<String> = crossdeps
+                .iter()
+                .map(|(dc, n)| format!("{} ({})", escape(category_label(dc)), n))
+                .collect();
+            out.push_str(&format!(
+                "<div class=\"crossdeps\">Depends on: {}</div>\n",
+                deps.join(", ")
+            ));
+        }
+        out.push_str("</summary>\n");
+
+        out.push_str(&format!("<table>\n<caption>Concepts in {}</caption>\n<tr><th scope=\"col\">Concept</th><th scope=\"col\">Earliest lecture</th><th scope=\"col\">Earliest lab</th><th scope=\"col\">Earliest HW</th><th scope=\"col\">Depends on</th></tr>\n",
+            escape(name)
+        ));
+        for c in &concepts {
+            let deps: Vec<String> = c
+                .dependencies
+                .iter()
+                .map(|d| {
+                    let dep_c = m.dependency_to_concept(d);
+                    format!("<a href=\"#{}\">{}</a>", dep_c.slug, escape(&dep_c.concept))
+                })
+                .collect();
+            out.push_str(&format!("<tr id=\"{}\"><th scope=\"row\">{}</th><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{}</td></tr>\n",
+                c.slug,
+                escape(&c.concept),
+                c.modes[0].range.earliest_start,
+                c.modes[1].range.earliest_start,
+                c.modes[2].range.earliest_start,
+                deps.join(", ")
+            ));
+        }
+        out.push_str("</table>\n</details>\n");
+    }
+    out.push_str("</main>\n</body>\n</html>\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_are_groups_with_their_cross_dependencies() {
+        let m = crate::test_map(
+            "concept,dependencies,category,lecture weight
+a <b>,,x,1
+c,a <b>,y & z,0.5
+d,a <b>;c,y & z,0.5
+",
+        );
+        let html = render(&m);
+
+        assert!(html.contains("<details class=\"category\" style=\"border-color: #ff7f24\" aria-label=\"Category y &amp; z, 2 concepts\">\n"), "{}", html);
+        assert!(html.contains("<summary>y &amp; z <span class=\"totals\">2 concepts; lecture 1.00 weeks, lab 0.00 weeks, HW 0.00 weeks</span>\n<div class=\"crossdeps\">Depends on: x (2)</div>\n</summary>\n"), "{}", html);
+        assert!(
+            html.contains("<td><a href=\"#a-b\">a &lt;b&gt;</a>, <a href=\"#c\">c</a></td></tr>\n"),
+            "{}",
+            html
+        );
+        assert_eq!(escape("'\"&"), "&#39;&quot;&amp;");
+        assert_eq!(css_color("green3"), "#00cd00");
+        assert_eq!(css_color("#123456"), "#123456");
+    }
+}
@@ -0,0 +1,577 @@
+// Reading the concept table. Whatever the source, it's read into csv
+// records (headers and rows), so everything downstream, including
+// writing the table back out, only deals with csv.
+
+use crate::locale::{self, Locale};
+use crate::{diagnostics, dot_input, mermaid_input, xlsx_input};
+use clap::{Args, ValueEnum};
+use csv::StringRecord;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+#[derive(Args)]
+#[command(next_help_heading = "Input")]
+pub struct InputOpts {
+    /// Read the concept table from FILE, or from an http(s) URL (e.g.,
+    /// a Google Sheet published as csv, fetched with curl), rather than
+    /// from stdin
+    #[arg(long = "input", value_name = "FILE|URL")]
+    pub source: Option<PathBuf>,
+    /// Format of the concept table on stdin
+    #[arg(long, value_enum, default_value = "csv")]
+    pub from: InputFormat,
+    /// In xlsx input, the worksheet with the concept table; the first
+    /// one by default
+    #[arg(long, value_name = "NAME")]
+    pub sheet: Option<String>,
+    /// Read the csv FIELD (e.g., concept, dependencies, lecture weight)
+    /// from the column named COLUMN; can be given repeatedly
+    #[arg(long = "column", value_name = "FIELD=COLUMN", value_parser = parse_column)]
+    pub columns: Vec<(String, String)>,
+    /// In dot and Mermaid input, edges point from prerequisites to the
+    /// concepts that need them, rather than from concepts to their
+    /// dependencies as in the generated graphs
+    #[arg(long)]
+    pub edges_from_prerequisites: bool,
+    /// What separates the dependencies (and their notes) in a cell.
+    /// Names containing it can be put in double quotes.
+    #[arg(long, value_name = "CHAR", default_value = ";")]
+    pub dep_separator: char,
+    /// Language of the csv's headers, which also decides whether its
+    /// weights have decimal commas
+    #[arg(long, value_enum, default_value = "en")]
+    pub locale: Locale,
+    /// Character encoding of the input; byte order marks are always
+    /// honored
+    #[arg(long, value_enum, default_value = "auto")]
+    pub encoding: Encoding,
+    /// What separates the cells: a character, or "tab"; by default,
+    /// whichever of tabs, semicolons, and commas the header line has
+    /// the most of
+    #[arg(long, value_name = "CHAR", value_parser = parse_delimiter)]
+    pub delimiter: Option<u8>,
+}
+
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!(
+            "expected a single ASCII character or \"tab\", found \"{}\"",
+            s
+        )),
+    }
+}
+
+// Excel exports tab-separated text, and spreadsheets in locales with
+// decimal commas mostly separate cells with semicolons, so go by the
+// header line's (outside of quotes), preferring commas in a tie.
+fn detect_delimiter(src: &str) -> u8 {
+    let header = src.lines().next().unwrap_or("");
+    let mut quoted = false;
+    let mut counts = [(b',', 0), (b';', 0), (b'\t', 0)];
+
+    for ch in header.bytes() {
+        if ch == b'"' {
+            quoted = !quoted;
+        }
+        for (d, n) in counts.iter_mut() {
+            if ch == *d && !quoted {
+                *n += 1;
+            }
+        }
+    }
+    // max_by_key takes the last maximum, so commas go last
+    counts.iter().rev().max_by_key(|(_, n)| *n).unwrap().0 // unwrap: not empty
+}
+
+fn url(path: &Path) -> Option<&str> {
+    path.to_str()
+        .filter(|p| p.starts_with("https://") || p.starts_with("http://"))
+}
+
+// A Google Sheet's own link is to its editor, so ask for its csv export
+// instead, of the tab the link is to (its "gid"). That requires the
+// sheet to be shared with anyone with the link; published sheets'
+// links ("/d/e/...") are left as they are.
+fn sheet_csv_url(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://docs.google.com/spreadsheets/d/")?;
+    let (id, rest) = rest.split_once('/')?;
+    if id == "e" || !rest.starts_with("edit") {
+        return None;
+    }
+    let gid = rest
+        .split(['#', '?', '&'])
+        .find_map(|p| p.strip_prefix("gid="));
+
+    Some(format!(
+        "https://docs.google.com/spreadsheets/d/{}/export?format=csv{}",
+        id,
+        gid.map(|g| format!("&gid={}", g)).unwrap_or_default()
+    ))
+}
+
+// With curl, which follows Google's redirects to the sheet's content.
+fn fetch(url: &str) -> anyhow::Result<Vec<u8>> {
+    let url = match sheet_csv_url(url) {
+        Some(export) => {
+            diagnostics::note(&format!(
+                "Fetching the Google Sheet's csv export, {}.",
+                export
+            ));
+            export
+        }
+        None => url.to_string(),
+    };
+    let out = process::Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .arg(&url)
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => anyhow::anyhow!(
+                "Fetching {} needs curl, which isn't installed (or isn't on the PATH). Install it, or download the file and give its path instead.",
+                url
+            ),
+            _ => anyhow::anyhow!("Couldn't run curl to fetch {}: {}", url, e),
+        })?;
+
+    if !out.status.success() {
+        anyhow::bail!(
+            "Couldn't fetch {}: {}",
+            url,
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(out.stdout)
+}
+
+/// The contents of the file, or of the http(s) URL.
+pub fn load(path: &Path) -> anyhow::Result<Vec<u8>> {
+    match url(path) {
+        Some(url) => fetch(url),
+        None => Ok(fs::read(path)?),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Encoding {
+    /// UTF-8, unless the input isn't valid UTF-8, and then Windows-1252
+    Auto,
+    #[value(name = "utf-8")]
+    Utf8,
+    /// What Excel on Windows exports csv files in
+    #[value(name = "windows-1252")]
+    Windows1252,
+}
+
+// The input as text. Excel prefixes UTF-8 csv files with a byte order
+// mark, which would otherwise become part of the first header, and
+// exports plain csv files in Windows-1252, which would otherwise mangle
+// accented names.
+fn decode(bytes: &[u8], encoding: Encoding) -> anyhow::Result<String> {
+    let encoding = match encoding {
+        Encoding::Utf8 => encoding_rs::UTF_8,
+        Encoding::Windows1252 => encoding_rs::WINDOWS_1252,
+        Encoding::Auto if std::str::from_utf8(bytes).is_ok() => encoding_rs::UTF_8,
+        Encoding::Auto => {
+            diagnostics::note("The input isn't valid UTF-8, so reading it as Windows-1252.");
+            encoding_rs::WINDOWS_1252
+        }
+    };
+    // honoring, and removing, any byte order mark
+    let (text, _, malformed) = encoding.decode(bytes);
+
+    if malformed {
+        anyhow::bail!("The input isn't valid {}; see --encoding.", encoding.name());
+    }
+    Ok(text.into_owned())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    /// The concept csv
+    Csv,
+    /// A Notion database exported as csv
+    Notion,
+    /// A graphviz graph of the concepts and their dependencies
+    Dot,
+    /// A Mermaid flowchart of the concepts and their dependencies
+    Mermaid,
+    /// A worksheet of an Excel workbook (see --sheet)
+    Xlsx,
+}
+
+fn parse_column(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((field, column)) if !field.trim().is_empty() && !column.trim().is_empty() => {
+            Ok((field.trim().to_string(), column.trim().to_string()))
+        }
+        _ => Err(format!("expected FIELD=COLUMN, found \"{}\"", s)),
+    }
+}
+
+/// The items of a list cell, split on `sep` other than within double
+/// quotes, where a doubled quote is a quote.
+pub fn split_list(cell: &str, sep: char) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut quoted = false;
+    let mut chars = cell.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                item.push('"');
+            }
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => items.push(std::mem::take(&mut item)),
+            c => item.push(c),
+        }
+    }
+    items.push(item);
+    items
+        .into_iter()
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+/// The inverse of `split_list`.
+pub fn join_list<S: AsRef<str>>(items: &[S], sep: char) -> String {
+    let quoted: Vec<String> = items
+        .iter()
+        .map(|i| {
+            let i = i.as_ref();
+            if i.contains(sep) || i.contains('"') {
+                format!("\"{}\"", i.replace('"', "\"\""))
+            } else {
+                i.to_string()
+            }
+        })
+        .collect();
+
+    quoted.join(&sep.to_string())
+}
+
+// The fields holding lists, which Notion exports comma-separated.
+const LIST_FIELDS: [&str; 5] = [
+    "dependencies",
+    "corequisites",
+    "aliases",
+    "tracks",
+    "outcomes",
+];
+
+// Notion exports relations as "Title (https://www.notion.so/...)",
+// and relations and multi-selects as comma-separated lists. Titles can
+// contain commas themselves, so when the links are there, they delimit
+// the titles.
+fn notion_list(cell: &str) -> Vec<&str> {
+    let mut titles = Vec::new();
+    let mut rest = cell.trim();
+
+    if rest.contains("(https://") {
+        while let Some(open) = rest.find(" (https://") {
+            titles.push(rest[..open].trim());
+            rest = match rest[open..].find(')') {
+                Some(close) => rest[open + close + 1..].trim_start_matches([',', ' ']),
+                None => "",
+            };
+        }
+        if !rest.trim().is_empty() {
+            titles.push(rest.trim());
+        }
+    } else {
+        titles.extend(rest.split(',').map(str::trim));
+    }
+    titles.retain(|t| !t.is_empty());
+    titles
+}
+
+// The fields without which a row can't be read.
+const REQUIRED_FIELDS: [&str; 2] = ["concept", "dependencies"];
+
+// The number of single-character insertions, deletions, and
+// substitutions between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// The field an unknown header was likely meant to be.
+fn suggestion(header: &str) -> Option<&'static str> {
+    let header = header.trim().to_lowercase();
+
+    locale::FIELDS
+        .iter()
+        .map(|f| (edit_distance(&header, f), *f))
+        .filter(|(d, f)| *d <= 3 && *d < f.len() / 2)
+        .min()
+        .map(|(_, f)| f)
+}
+
+/// The names `name` was most likely meant to be, e.g., when it's
+/// misspelled: those at the smallest edit distance from it (ignoring
+/// case), up to three, if that's small for its length.
+pub fn closest<'a, I: IntoIterator<Item = &'a String>>(name: &str, names: I) -> Vec<&'a String> {
+    let name = name.trim().to_lowercase();
+    let max = (name.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &String)> = names
+        .into_iter()
+        .map(|n| (edit_distance(&name, &n.to_lowercase()), n))
+        .filter(|(d, _)| *d <= max)
+        .collect();
+
+    scored.sort();
+    scored.dedup();
+    let best = scored.first().map(|(d, _)| *d);
+    scored
+        .into_iter()
+        .filter(|(d, _)| Some(*d) == best)
+        .take(3)
+        .map(|(_, n)| n)
+        .collect()
+}
+
+/// Warnings for the columns that aren't fields, whose values would
+/// otherwise be silently dropped, with the fields they were likely
+/// meant to be. Missing required fields are an error. When there are
+/// unknown columns, or no weights, the fields that were found are
+/// noted, too. Graphs are read into the fields, so there's nothing to
+/// check.
+pub fn check_columns(opts: &InputOpts, headers: &StringRecord) -> anyhow::Result<String> {
+    if matches!(opts.from, InputFormat::Dot | InputFormat::Mermaid) {
+        return Ok(String::new());
+    }
+    let missing: Vec<&str> = REQUIRED_FIELDS
+        .iter()
+        .copied()
+        .filter(|f| !headers.iter().any(|h| h == *f))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "The csv is missing the required {} column{}.",
+            missing.join(" and "),
+            if missing.len() == 1 { "" } else { "s" }
+        );
+    }
+    let mut warnings = String::new();
+    for h in headers.iter().filter(|h| !locale::FIELDS.contains(h)) {
+        warnings.push_str(&match suggestion(h) {
+            Some(f) => format!(
+                "- Unknown column \"{}\"; did you mean \"{}\"? Ignoring the column.\n",
+                h, f
+            ),
+            None => format!("- Unknown column \"{}\". Ignoring the column.\n", h),
+        });
+    }
+    let weights = headers.iter().any(|h| h.ends_with(" weight"));
+    if !warnings.is_empty() || !weights {
+        let found: Vec<&str> = headers
+            .iter()
+            .filter(|h| locale::FIELDS.contains(h) && !REQUIRED_FIELDS.contains(h))
+            .collect();
+        diagnostics::note(&format!(
+            "Found the optional columns: {}.",
+            if found.is_empty() {
+                String::from("none")
+            } else {
+                found.join(", ")
+            }
+        ));
+    }
+    Ok(warnings)
+}
+
+// The csv reader positions each record right after the previous one,
+// before its line terminator and any blank lines, so that its line is
+// off by one, or more, from the one it starts on. Count the lines up
+// to its first character instead.
+fn fix_lines(src: &str, records: &mut [StringRecord]) {
+    let (mut line, mut counted) = (1, 0);
+
+    for r in records {
+        let Some(mut pos) = r.position().cloned() else {
+            continue;
+        };
+        let rest = &src[pos.byte() as usize..];
+        let start = src.len() - rest.trim_start_matches(['\r', '\n']).len();
+        line += src[counted..start].matches('\n').count() as u64;
+        counted = start;
+        pos.set_line(line);
+        r.set_position(Some(pos));
+    }
+}
+
+/// The headers and rows of the concept table, with the columns renamed
+/// to the csv's fields.
+pub fn read<R: io::Read>(
+    opts: &InputOpts,
+    mut input: R,
+) -> anyhow::Result<(StringRecord, Vec<StringRecord>)> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    let (headers, records) = if opts.from == InputFormat::Xlsx {
+        // the workbook is binary, and its text is always UTF-8
+        xlsx_input::read(bytes, opts)?
+    } else {
+        let src = decode(&bytes, opts.encoding)?;
+        match opts.from {
+            InputFormat::Dot => return dot_input::read(&src, opts),
+            InputFormat::Mermaid => return mermaid_input::read(&src, opts),
+            InputFormat::Csv | InputFormat::Notion | InputFormat::Xlsx => (),
+        }
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(opts.delimiter.unwrap_or_else(|| detect_delimiter(&src)))
+            .from_reader(src.as_bytes());
+        let headers = rdr.headers()?.clone();
+        let mut records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+        fix_lines(&src, &mut records);
+        (headers, records)
+    };
+    let headers: StringRecord = headers
+        .iter()
+        .map(|h| {
+            opts.columns
+                .iter()
+                .find(|(_, column)| column == h.trim())
+                .map(|(field, _)| field.as_str())
+                .or_else(|| opts.locale.field(h))
+                .unwrap_or(h)
+        })
+        .collect();
+    let mut rows = Vec::new();
+    for row in records {
+        let mut fixed: StringRecord = headers
+            .iter()
+            .zip(&row)
+            .map(|(h, cell)| {
+                let notion = opts.from == InputFormat::Notion;
+                Ok(if notion && h == "dependencies" {
+                    join_list(&notion_list(cell), opts.dep_separator)
+                } else if notion && LIST_FIELDS.contains(&h) {
+                    join_list(&notion_list(cell), ';')
+                } else if opts.locale.decimal_comma() && locale::DECIMAL_FIELDS.contains(&h) {
+                    locale::decimal_point(cell).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "The {} \"{}\" on line {} mixes decimal commas and points; write it as, e.g., 1000,5.",
+                            h,
+                            cell,
+                            row.position().map_or(0, |p| p.line())
+                        )
+                    })?
+                } else {
+                    cell.to_string()
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        // keep the position, for errors deserializing the row
+        fixed.set_position(row.position().cloned());
+        rows.push(fixed);
+    }
+
+    Ok((headers, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser as _;
+
+    #[test]
+    fn lists_split_outside_quotes() {
+        assert_eq!(split_list(" a ; b;;c ", ';'), ["a", "b", "c"]);
+        assert_eq!(
+            split_list(r#""a; b"; "say ""hi""""#, ';'),
+            ["a; b", r#"say "hi""#]
+        );
+        assert_eq!(split_list("a|b;c", '|'), ["a", "b;c"]);
+        assert!(split_list("  ", ';').is_empty());
+    }
+
+    #[test]
+    fn joined_lists_split_back() {
+        let items = ["a; b", r#"say "hi""#, "c"];
+        let cell = join_list(&items, ';');
+
+        assert_eq!(cell, r#""a; b";"say ""hi""";c"#);
+        assert_eq!(split_list(&cell, ';'), items);
+    }
+
+    #[test]
+    fn delimiters_are_the_headers_outside_quotes() {
+        assert_eq!(detect_delimiter("concept,dependencies\na;b;c,d\n"), b',');
+        assert_eq!(
+            detect_delimiter("concept;dependencies;\"lecture, weight\"\n"),
+            b';'
+        );
+        assert_eq!(detect_delimiter("concept\tdependencies\n"), b'\t');
+        // ties, and headers without any, are commas
+        assert_eq!(detect_delimiter("a,b;c\n"), b',');
+        assert_eq!(detect_delimiter("concept\n"), b',');
+        assert_eq!(detect_delimiter(""), b',');
+    }
+
+    #[test]
+    fn sheet_links_are_their_csv_exports() {
+        assert_eq!(
+            sheet_csv_url("https://docs.google.com/spreadsheets/d/abc/edit#gid=42").as_deref(),
+            Some("https://docs.google.com/spreadsheets/d/abc/export?format=csv&gid=42")
+        );
+        assert_eq!(
+            sheet_csv_url("https://docs.google.com/spreadsheets/d/abc/edit?usp=sharing").as_deref(),
+            Some("https://docs.google.com/spreadsheets/d/abc/export?format=csv")
+        );
+        assert_eq!(
+            sheet_csv_url("https://docs.google.com/spreadsheets/d/e/abc/pub?output=csv"),
+            None
+        );
+        assert_eq!(sheet_csv_url("https://example.com/map.csv"), None);
+        assert_eq!(url(Path::new("map.csv")), None);
+    }
+
+    #[test]
+    fn notion_relations_are_delimited_by_their_links() {
+        assert_eq!(
+            notion_list(
+                "Sets, Maps (https://www.notion.so/a1), Pointers (https://www.notion.so/b2)"
+            ),
+            ["Sets, Maps", "Pointers"]
+        );
+        assert_eq!(notion_list(" Sets, Maps ,, "), ["Sets", "Maps"]);
+        assert!(notion_list("").is_empty());
+    }
+
+    #[test]
+    fn notion_exports_are_read_as_lists() {
+        let cli = crate::Cli::parse_from([
+            "concept_map",
+            "--from",
+            "notion",
+            "--column",
+            "concept=Name",
+            "--column",
+            "dependencies=Prerequisites",
+        ]);
+        let src = "Name,Prerequisites,tracks\n\
+                   Pointers,\"Sets, Maps (https://www.notion.so/a1), C (https://www.notion.so/b2)\",\"Systems, Theory\"\n";
+        let (headers, rows) = read(&cli.input, src.as_bytes()).unwrap();
+
+        assert_eq!(headers, vec!["concept", "dependencies", "tracks"]);
+        assert_eq!(rows[0], vec!["Pointers", "Sets, Maps;C", "Systems;Theory"]);
+    }
+}
@@ -0,0 +1,116 @@
+// Plans for team-taught courses: for each instructor (from the csv's
+// instructor column), the weeks they teach in, with the lecture weight
+// and concepts of each, and their total load. Weeks where an
+// instructor's lecture weight is more than they can teach in a week
+// are flagged.
+
+use crate::schedule::Schedule;
+use crate::{Concept, ConceptMap};
+use clap::Args;
+use std::collections::BTreeMap;
+
+#[derive(Args)]
+pub struct InstructorsArgs {
+    /// Flag the weeks where an instructor's lecture weight is over this
+    /// many weeks
+    #[arg(long, value_name = "WEEKS", default_value = "1")]
+    pub max_weekly_load: f64,
+}
+
+/// Requires the map to be solved.
+pub fn report(m: &ConceptMap, args: &InstructorsArgs) -> String {
+    let s = Schedule::new(m);
+    // instructor -> week -> concepts, with the unassigned concepts last
+    let mut plans: BTreeMap<(bool, &str), BTreeMap<u64, Vec<&Concept>>> = BTreeMap::new();
+    let mut out = String::new();
+
+    // Milestones aren't taught by anyone.
+    for c in m
+        .concepts
+        .iter()
+        .filter(|c| c.is_taught() && !c.kind.is_milestone())
+    {
+        let who = match &c.instructor {
+            Some(i) => (false, i.as_str()),
+            None => (true, "(unassigned)"),
+        };
+        plans
+            .entry(who)
+            .or_default()
+            .entry(s.week(c))
+            .or_default()
+            .push(c);
+    }
+
+    if plans.keys().all(|(unassigned, _)| *unassigned) {
+        out.push_str("No instructors; add an instructor column.\n");
+    }
+    for ((_, who), weeks) in &plans {
+        let total =
+            |mode: usize| -> f64 { weeks.values().flatten().map(|c| c.modes[mode].weight).sum() };
+        out.push_str(&format!(
+            "{}: lecture {}, lab {}, HW {}\n",
+            who,
+            m.weight_label(0, total(0)),
+            m.weight_label(1, total(1)),
+            m.weight_label(2, total(2))
+        ));
+        for (week, concepts) in weeks {
+            let load: f64 = concepts.iter().map(|c| c.modes[0].weight).sum();
+            let names: Vec<&str> = concepts.iter().map(|c| c.concept.as_str()).collect();
+            let over = if load > args.max_weekly_load {
+                format!(
+                    " (overloaded: over {})",
+                    m.units.weeks(args.max_weekly_load)
+                )
+            } else {
+                String::new()
+            };
+            out.push_str(&format!(
+                "  week {:>3} {:>11}  {}{}\n",
+                week,
+                m.weight_label(0, load),
+                names.join(", "),
+                over
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_flag_the_overloaded_weeks() {
+        let m = crate::test_map(
+            "concept,dependencies,instructor,lecture weight,lab weight,type
+a,,Ada,0.75,1,
+b,,Ada,0.5,,
+c,a,Grace,1,,
+d,c,,0.5,,
+exam,d,Grace,0,,exam
+",
+        );
+        let report = report(
+            &m,
+            &InstructorsArgs {
+                max_weekly_load: 1.0,
+            },
+        );
+
+        assert_eq!(
+            report.lines().collect::<Vec<_>>(),
+            [
+                "Ada: lecture 1.25 weeks, lab 1.00 weeks, HW 0.00 weeks",
+                "  week   1  1.25 weeks  a, b (overloaded: over 1.00 weeks)",
+                "Grace: lecture 1.00 weeks, lab 0.00 weeks, HW 0.00 weeks",
+                "  week   1  1.00 weeks  c",
+                "(unassigned): lecture 0.50 weeks, lab 0.00 weeks, HW 0.00 weeks",
+                "  week   2  0.50 weeks  d",
+            ]
+        );
+    }
+}
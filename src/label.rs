@@ -0,0 +1,141 @@
+// Node label templates, e.g., "{name}\n{category} · wk {week}", with
+// the concept's fields in braces ("{{" and "}}" for braces themselves),
+// and "\n" for line breaks.
+
+use crate::schedule::Schedule;
+use crate::{category_label, Concept};
+
+/// The nodes' labels, without --label-template.
+pub const DEFAULT: &str =
+    "{name}\nEarliest lecture: {earliest_lecture}\nEarliest lab: {earliest_lab}\nEarliest HW: {earliest_hw}";
+
+pub const FIELDS: [&str; 16] = [
+    "name",
+    "type",
+    "category",
+    "instructor",
+    "description",
+    "week",
+    "depth",
+    "lecture_weight",
+    "lab_weight",
+    "hw_weight",
+    "earliest_lecture",
+    "earliest_lab",
+    "earliest_hw",
+    "latest_start",
+    "slack",
+    "dependencies",
+];
+
+enum Piece<'a> {
+    Text(&'a str),
+    Field(&'a str),
+}
+
+fn pieces(template: &str) -> Result<Vec<Piece<'_>>, String> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix("{{") {
+            pieces.push(Piece::Text("{"));
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("}}") {
+            pieces.push(Piece::Text("}"));
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix('{') {
+            let end = r
+                .find('}')
+                .ok_or_else(|| format!("unclosed \"{{\" in \"{}\"", template))?;
+            let field = &r[..end];
+            if !FIELDS.contains(&field) {
+                return Err(format!(
+                    "unknown field \"{}\"; expected one of {}",
+                    field,
+                    FIELDS.join(", ")
+                ));
+            }
+            pieces.push(Piece::Field(field));
+            rest = &r[end + 1..];
+        } else {
+            let end = rest.find(['{', '}']).unwrap_or(rest.len()).max(1);
+            pieces.push(Piece::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    Ok(pieces)
+}
+
+/// Check a template from the command line, turning its "\n"s into
+/// line breaks.
+pub fn parse_template(s: &str) -> Result<String, String> {
+    let template = s.replace("\\n", "\n");
+
+    pieces(&template)?;
+    Ok(template)
+}
+
+/// The concept's label, from a template checked by `parse_template`.
+pub fn render(template: &str, c: &Concept, s: &Schedule, depth: usize) -> String {
+    let mut label = String::new();
+
+    // unwrap: checked when parsed
+    for piece in pieces(template).unwrap() {
+        match piece {
+            Piece::Text(t) => label.push_str(t),
+            Piece::Field(f) => label.push_str(&match f {
+                "name" => c.concept.clone(),
+                "type" => c.kind.name().to_lowercase(),
+                "category" => category_label(&c.category).to_string(),
+                "instructor" => c.instructor.clone().unwrap_or_default(),
+                "description" => c.description.clone(),
+                "week" => s.week(c).to_string(),
+                "depth" => depth.to_string(),
+                "lecture_weight" => format!("{:.2}", c.modes[0].weight),
+                "lab_weight" => format!("{:.2}", c.modes[1].weight),
+                "hw_weight" => format!("{:.2}", c.modes[2].weight),
+                "earliest_lecture" => format!("{:.2}", c.modes[0].range.earliest_start),
+                "earliest_lab" => format!("{:.2}", c.modes[1].range.earliest_start),
+                "earliest_hw" => format!("{:.2}", c.modes[2].range.earliest_start),
+                "latest_start" => format!("{:.2}", c.latest_start()),
+                "slack" => format!("{:.2}", c.slack()),
+                "dependencies" => c.dependencies.join(", "),
+                _ => unreachable!(), // not in FIELDS
+            }),
+        }
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_fill_in_the_fields() {
+        let m = crate::test_map("concept,dependencies,category,lecture weight\na,,x,1\nb,a,,0.5\n");
+        let s = Schedule::new(&m);
+        let template = parse_template(
+            r"{name} ({category}) {{wk {week}}}\n{earliest_lecture}: {dependencies}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            render(&template, &m.concepts[1], &s, 1),
+            format!("b ({}) {{wk 2}}\n1.00: a", category_label(""))
+        );
+    }
+
+    #[test]
+    fn bad_templates_are_errors() {
+        assert_eq!(
+            parse_template("{name").unwrap_err(),
+            "unclosed \"{\" in \"{name\""
+        );
+        assert!(parse_template("{nmae}")
+            .unwrap_err()
+            .starts_with("unknown field \"nmae\"; expected one of name, type,"));
+        assert!(parse_template("}").is_ok());
+    }
+}
@@ -0,0 +1,176 @@
+// Packing each week's concepts into its lectures, and the outline of
+// each lecture as a skeleton for its slides. The concepts are packed in
+// dependency order by their lecture weights: one that needs more than
+// what's left of a lecture continues into the next, and each week's
+// first concept starts a new lecture.
+
+use crate::glossary::md_escape;
+use crate::schedule::Schedule;
+use crate::{category_label, ConceptMap};
+
+const EPSILON: f64 = 1e-9;
+
+pub struct Part {
+    pub offset: usize,
+    // if the concept was started in an earlier lecture
+    pub continued: bool,
+}
+
+pub struct Lecture {
+    pub week: u64,
+    pub parts: Vec<Part>,
+}
+
+/// The semester's lectures, and what's taught in them. Requires the map
+/// to be solved. Milestones aren't taught, and are left out.
+pub fn pack(m: &ConceptMap) -> Vec<Lecture> {
+    let s = Schedule::new(m);
+    let mut lectures: Vec<Lecture> = Vec::new();
+
+    for week in 1..=s.nweeks() {
+        let mut room = 0.0; // what's left of the last lecture
+        let concepts = m
+            .dependency_order
+            .iter()
+            .map(|n| m.dependency_to_concept(n))
+            .filter(|c| c.is_taught() && !c.kind.is_milestone() && s.week(c) == week);
+
+        for c in concepts {
+            let mut left = c.modes[0].weight * m.units.lectures_per_week;
+            let mut continued = false;
+
+            loop {
+                // Concepts without lecture weight are mentioned in the
+                // week's last lecture.
+                let started = lectures.last().is_some_and(|l| l.week == week);
+                if room < EPSILON && (left >= EPSILON || !started) {
+                    lectures.push(Lecture {
+                        week,
+                        parts: Vec::new(),
+                    });
+                    room = 1.0;
+                }
+                // unwrap: just pushed, if there wasn't one
+                lectures.last_mut().unwrap().parts.push(Part {
+                    offset: c.offset,
+                    continued,
+                });
+                let taught = left.min(room);
+                room -= taught;
+                left -= taught;
+                continued = true;
+                if left < EPSILON {
+                    break;
+                }
+            }
+        }
+    }
+    lectures
+}
+
+/// A Markdown outline of each lecture: its concepts, their descriptions,
+/// and the prerequisites from earlier lectures to recap.
+pub fn outline(m: &ConceptMap) -> String {
+    let lectures = pack(m);
+    // the (0-based) lecture each concept starts in, by offset
+    let mut first = vec![None; m.concepts.len()];
+    for (i, l) in lectures.iter().enumerate() {
+        for p in l.parts.iter().filter(|p| !p.continued) {
+            first[p.offset] = Some(i);
+        }
+    }
+    let mut out = String::from("# Lecture outlines\n");
+
+    for (i, l) in lectures.iter().enumerate() {
+        out.push_str(&format!("\n## Lecture {} (week {})\n", i + 1, l.week));
+        for p in &l.parts {
+            let c = &m.concepts[p.offset];
+            out.push_str(&format!(
+                "\n### {}{}\n\n*{}*\n",
+                md_escape(&c.concept),
+                if p.continued { " (continued)" } else { "" },
+                md_escape(category_label(&c.category))
+            ));
+            if p.continued {
+                continue;
+            }
+            if !c.description.is_empty() {
+                out.push_str(&format!("\n{}\n", md_escape(&c.description)));
+            }
+            let recap: Vec<String> = c
+                .dependencies
+                .iter()
+                .map(|d| m.dependency_to_concept(d))
+                .filter_map(|d| {
+                    first[d.offset]
+                        .filter(|&j| j < i)
+                        .map(|j| format!("{} (lecture {})", md_escape(&d.concept), j + 1))
+                })
+                .collect();
+            if !recap.is_empty() {
+                out.push_str(&format!("\nRecap: {}\n", recap.join(", ")));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKED: &str = "concept,dependencies,description,lecture weight,lab weight
+a,,The basics,1,
+b,a,,0.75,
+c,a,,0.25,
+lab,a,,0,1
+";
+
+    #[test]
+    fn concepts_continue_into_the_next_lecture() {
+        let m = crate::test_map(PACKED);
+        let lectures: Vec<(u64, Vec<String>)> = pack(&m)
+            .iter()
+            .map(|l| {
+                let parts = l
+                    .parts
+                    .iter()
+                    .map(|p| {
+                        format!(
+                            "{}{}",
+                            m.concepts[p.offset].concept,
+                            if p.continued { "+" } else { "" }
+                        )
+                    })
+                    .collect();
+                (l.week, parts)
+            })
+            .collect();
+
+        let parts = |l: &[&str]| l.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            lectures,
+            [
+                (1, parts(&["a"])),
+                (1, parts(&["a+"])),
+                (2, parts(&["b"])),
+                (2, parts(&["b+", "c", "lab"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn outlines_recap_earlier_lectures() {
+        let outline = outline(&crate::test_map(PACKED));
+
+        assert!(outline.starts_with("# Lecture outlines\n\n## Lecture 1 (week 1)\n\n### a\n\n*(uncategorized)*\n\nThe basics\n\n## Lecture 2 (week 1)\n\n### a (continued)\n"), "{}", outline);
+        assert!(
+            outline.contains(
+                "\n## Lecture 3 (week 2)\n\n### b\n\n*(uncategorized)*\n\nRecap: a (lecture 1)\n"
+            ),
+            "{}",
+            outline
+        );
+        assert_eq!(outline.matches("Recap:").count(), 3, "{}", outline);
+    }
+}
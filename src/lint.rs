@@ -0,0 +1,49 @@
+// Lint output, for pre-commit hooks and CI: every diagnostic on one
+// line, as FILE:LINE: LEVEL: MESSAGE, and nothing else.
+
+use crate::diagnostics::{line, Diagnostics};
+
+pub fn report(file: &str, diags: &Diagnostics) -> String {
+    let mut out = String::new();
+
+    for s in diags.sections() {
+        let level = s.level.name();
+        for m in &s.messages {
+            match line(m) {
+                Some(n) => out.push_str(&format!("{}:{}: {}: {}\n", file, n, level, m)),
+                None => out.push_str(&format!("{}: {}: {}\n", file, level, m)),
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Level;
+
+    #[test]
+    fn every_message_is_a_line() {
+        let mut diags = Diagnostics::default();
+        diags.add(
+            Level::Warning,
+            "orphan",
+            "Orphaned concept warnings",
+            "- \"a\" on line 7 has no dependencies.\n- \"b\" on line 9 has no dependencies.\n",
+        );
+        diags.add(
+            Level::Error,
+            "columns",
+            "Column errors",
+            "- The csv is missing the concept column.\n",
+        );
+
+        assert_eq!(
+            report("map.csv", &diags),
+            "map.csv:7: warning: \"a\" on line 7 has no dependencies.\n\
+             map.csv:9: warning: \"b\" on line 9 has no dependencies.\n\
+             map.csv: error: The csv is missing the concept column.\n"
+        );
+    }
+}
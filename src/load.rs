@@ -0,0 +1,278 @@
+// The weekly load of the schedule: how much weight starts in each
+// week, broken down by category, as an ASCII bar chart or an SVG image,
+// or by modality, as a table flagging the weeks over capacity.
+// The image has a title and description for screen readers, and the
+// categories are told apart by hatching as well as by color.
+
+use crate::html::{css_color, escape};
+use crate::schedule::Schedule;
+use crate::{category_label, Concept, ConceptMap, MODE_NAMES};
+use clap::{Args, ValueEnum};
+use std::path::PathBuf;
+
+const BAR_WIDTH: f64 = 60.0;
+// Symbols for the categories in the ASCII chart, in category order.
+const SYMBOLS: &[u8] = b"#*+=%@&ox~ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+// Hatching for the categories in the SVG chart, in category order, as
+// paths in 8x8 tiles.
+const HATCHES: &[&str] = &[
+    "",
+    "M0,8 L8,0",
+    "M0,4 H8",
+    "M4,0 V8",
+    "M0,0 L8,8",
+    "M0,4 H8 M4,0 V8",
+    "M0,0 L8,8 M0,8 L8,0",
+    "M3,4 a1,1 0 1,0 2,0 a1,1 0 1,0 -2,0",
+];
+
+#[derive(Args)]
+pub struct LoadArgs {
+    /// Which modality's weights to count
+    #[arg(long, value_enum, default_value = "all")]
+    pub mode: ModeFilter,
+    /// Also write the chart as an SVG image
+    #[arg(long, value_name = "FILE")]
+    pub svg: Option<PathBuf>,
+    /// Print each week's lecture, lab, and HW weight instead of the
+    /// chart, flagging those over --capacity
+    #[arg(long)]
+    pub by_mode: bool,
+    /// The weight of each modality that fits in a week, in weeks
+    #[arg(long, value_name = "WEEKS", default_value = "1", requires = "by_mode")]
+    pub capacity: f64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ModeFilter {
+    Lecture,
+    Lab,
+    Hw,
+    All,
+}
+
+impl ModeFilter {
+    pub fn weight(&self, c: &Concept) -> f64 {
+        match self {
+            Self::Lecture => c.modes[0].weight,
+            Self::Lab => c.modes[1].weight,
+            Self::Hw => c.modes[2].weight,
+            Self::All => c.modes.iter().map(|m| m.weight).sum(),
+        }
+    }
+}
+
+// External concepts are taught before the course, in week 0.
+fn taught<'a>(m: &'a ConceptMap, s: &'a Schedule) -> impl Iterator<Item = &'a Concept> {
+    m.concepts
+        .iter()
+        .filter(move |c| c.is_taught() && s.week(c) > 0)
+}
+
+// loads[week - 1][category index], for the categories in
+// `m.categories()` order.
+pub fn weekly_loads(m: &ConceptMap, s: &Schedule, mode: ModeFilter) -> Vec<Vec<f64>> {
+    let cats = m.categories();
+    let mut loads = vec![vec![0.0; cats.len()]; s.nweeks() as usize];
+
+    for c in taught(m, s) {
+        let cat = cats.iter().position(|cat| *cat == &c.category).unwrap(); // all categories present
+        loads[s.week(c) as usize - 1][cat] += mode.weight(c);
+    }
+    loads
+}
+
+pub fn ascii_chart(m: &ConceptMap, s: &Schedule, mode: ModeFilter) -> String {
+    let cats = m.categories();
+    let loads = weekly_loads(m, s, mode);
+    let most = loads
+        .iter()
+        .map(|w| w.iter().sum::<f64>())
+        .fold(0.0, f64::max);
+    let scale = if most > 0.0 { BAR_WIDTH / most } else { 0.0 };
+    let symbol = |i: usize| SYMBOLS[i % SYMBOLS.len()] as char;
+    let mut out = String::new();
+
+    for (week, load) in loads.iter().enumerate() {
+        let mut bar = String::new();
+        let mut total = 0.0;
+
+        // Round the running total, so that the rounding of each
+        // category's segment doesn't accumulate.
+        for (i, w) in load.iter().enumerate() {
+            let from = (total * scale).round() as usize;
+            total += w;
+            let to = (total * scale).round() as usize;
+            bar.extend(std::iter::repeat_n(symbol(i), to - from));
+        }
+        out.push_str(&format!("week {:>3} | {} {:.2}\n", week + 1, bar, total));
+    }
+    let legend: Vec<String> = cats
+        .iter()
+        .enumerate()
+        .map(|(i, cat)| format!("{} {}", symbol(i), category_label(cat)))
+        .collect();
+    out.push_str(&format!("\n{}\n", legend.join(", ")));
+    if let Some(units) = m.units.legend() {
+        out.push_str(&format!("{}\n", units));
+    }
+
+    out
+}
+
+/// Each week's weight by modality, with the modalities over `capacity`
+/// marked, and listed after.
+pub fn mode_table(m: &ConceptMap, s: &Schedule, capacity: f64) -> String {
+    let mut loads = vec![[0.0; 3]; s.nweeks() as usize];
+    let mut over = Vec::new();
+    let mut out = String::new();
+
+    for c in taught(m, s) {
+        for (l, mode) in loads[s.week(c) as usize - 1].iter_mut().zip(&c.modes) {
+            *l += mode.weight;
+        }
+    }
+
+    out.push_str(&format!(
+        "Weekly load, in weeks, with * over {}:\n",
+        m.units.weeks(capacity)
+    ));
+    out.push_str(&format!(
+        "week {:>9} {:>9} {:>9}\n",
+        MODE_NAMES[0], MODE_NAMES[1], MODE_NAMES[2]
+    ));
+    for (week, load) in loads.iter().enumerate() {
+        let mut row = format!("{:>4}", week + 1);
+        let mut flagged = Vec::new();
+        for (mode, w) in load.iter().enumerate() {
+            let flag = if *w > capacity + 1e-9 {
+                flagged.push(format!("{} {}", MODE_NAMES[mode], m.units.weeks(*w)));
+                '*'
+            } else {
+                ' '
+            };
+            row.push_str(&format!(" {:>8.2}{}", w, flag));
+        }
+        if !flagged.is_empty() {
+            over.push(format!("week {} ({})", week + 1, flagged.join(", ")));
+        }
+        out.push_str(&format!("{}\n", row.trim_end()));
+    }
+
+    if over.is_empty() {
+        out.push_str("\nNo week is over capacity.\n");
+    } else {
+        out.push_str(&format!("\nOver capacity: {}\n", over.join("; ")));
+    }
+    if let Some(units) = m.units.legend() {
+        out.push_str(&format!("{}\n", units));
+    }
+    out
+}
+
+pub fn svg_chart(m: &ConceptMap, s: &Schedule, mode: ModeFilter) -> String {
+    const PLOT_H: f64 = 300.0;
+    const BAR_W: f64 = 28.0;
+    const TOP: f64 = 20.0;
+    const LEFT: f64 = 50.0;
+    let cats = m.categories();
+    let colors = m.category_colors();
+    let loads = weekly_loads(m, s, mode);
+    let most = loads
+        .iter()
+        .map(|w| w.iter().sum::<f64>())
+        .fold(0.0, f64::max);
+    let scale = if most > 0.0 { PLOT_H / most } else { 0.0 };
+    let width = LEFT + BAR_W * loads.len() as f64 + 200.0;
+    let height = (TOP + PLOT_H + 40.0).max(TOP + 16.0 * cats.len() as f64 + 20.0);
+    let mut svg = String::new();
+
+    svg.push_str(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"sans-serif\" font-size=\"10\" role=\"img\" aria-labelledby=\"load-title load-desc\">\n",
+        width, height
+    ));
+    let names: Vec<String> = cats.iter().map(|c| escape(category_label(c))).collect();
+    svg.push_str(&format!("<title id=\"load-title\">Weekly load by category</title>\n<desc id=\"load-desc\">Stacked bars of the weight starting in each of the {} weeks, by category ({}). The heaviest week has {:.2}.</desc>\n",
+        loads.len(),
+        names.join(", "),
+        most
+    ));
+    svg.push_str("<defs>\n");
+    for (i, cat) in cats.iter().enumerate() {
+        svg.push_str(&format!("<pattern id=\"load-{}\" width=\"8\" height=\"8\" patternUnits=\"userSpaceOnUse\"><rect width=\"8\" height=\"8\" fill=\"{}\"/><path d=\"{}\" stroke=\"black\" stroke-width=\"1\" fill=\"black\"/></pattern>\n",
+            i,
+            css_color(colors.get(cat).unwrap()), // unwrap: all categories have colors
+            HATCHES[i % HATCHES.len()]
+        ));
+    }
+    svg.push_str("</defs>\n");
+    svg.push_str(&format!("<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.2}</text>\n<text x=\"{}\" y=\"{}\" text-anchor=\"end\">0</text>\n",
+        LEFT - 4.0,
+        TOP + 4.0,
+        most,
+        LEFT - 4.0,
+        TOP + PLOT_H
+    ));
+    for (week, load) in loads.iter().enumerate() {
+        let x = LEFT + week as f64 * BAR_W;
+        let mut y = TOP + PLOT_H;
+
+        for (i, w) in load.iter().enumerate().filter(|(_, w)| **w > 0.0) {
+            let h = w * scale;
+            y -= h;
+            svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"url(#load-{})\"><title>week {}: {} {:.2}</title></rect>\n",
+                x,
+                y,
+                BAR_W - 3.0,
+                h,
+                i,
+                week + 1,
+                escape(category_label(cats[i])),
+                w
+            ));
+        }
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+            x + BAR_W / 2.0,
+            TOP + PLOT_H + 14.0,
+            week + 1
+        ));
+    }
+    let legend_x = LEFT + BAR_W * loads.len() as f64 + 20.0;
+    for (i, cat) in cats.iter().enumerate() {
+        let y = TOP + 16.0 * i as f64;
+        svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"url(#load-{})\" stroke=\"black\" stroke-width=\"0.5\"/><text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+            legend_x,
+            y,
+            i,
+            legend_x + 14.0,
+            y + 9.0,
+            escape(category_label(cat))
+        ));
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTERNAL: &str = "concept,dependencies,category,lecture weight,status
+C,,extern:C,1,
+pointers,C,memory,1,
+old,,memory,0.5,retired
+";
+
+    #[test]
+    fn external_and_retired_concepts_arent_loads() {
+        let m = crate::test_map(EXTERNAL);
+        let s = Schedule::new(&m);
+        let loads = weekly_loads(&m, &s, ModeFilter::Lecture);
+
+        assert_eq!(loads.len(), 1);
+        assert_eq!(loads[0].iter().sum::<f64>(), 1.0);
+        assert!(ascii_chart(&m, &s, ModeFilter::All).starts_with("week   1 |"));
+        assert!(svg_chart(&m, &s, ModeFilter::All).ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,196 @@
+// Reading csv files exported from non-English spreadsheets: their
+// headers are translated, and their weights use decimal commas (and
+// so, usually, `;` rather than `,` between the cells).
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Locale {
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+// The csv fields holding decimal numbers.
+pub const DECIMAL_FIELDS: [&str; 6] = [
+    "lecture weight",
+    "lab weight",
+    "hw weight",
+    "lecture coverage",
+    "lab coverage",
+    "hw coverage",
+];
+
+// The csv's fields, and their translations, in the same order.
+pub const FIELDS: [&str; 24] = [
+    "concept",
+    "dependencies",
+    "corequisites",
+    "category",
+    "unit",
+    "topic",
+    "week",
+    "earliest",
+    "latest",
+    "lecture weight",
+    "lab weight",
+    "hw weight",
+    "lecture coverage",
+    "lab coverage",
+    "hw coverage",
+    "dependency notes",
+    "aliases",
+    "description",
+    "url",
+    "instructor",
+    "tracks",
+    "outcomes",
+    "status",
+    "type",
+];
+const DE: [&str; 24] = [
+    "konzept",
+    "abhängigkeiten",
+    "korequisiten",
+    "kategorie",
+    "einheit",
+    "thema",
+    "woche",
+    "frühestens",
+    "spätestens",
+    "gewicht vorlesung",
+    "gewicht labor",
+    "gewicht hausaufgaben",
+    "abdeckung vorlesung",
+    "abdeckung labor",
+    "abdeckung hausaufgaben",
+    "hinweise zu abhängigkeiten",
+    "aliase",
+    "beschreibung",
+    "url",
+    "dozent",
+    "schwerpunkte",
+    "lernziele",
+    "status",
+    "typ",
+];
+const FR: [&str; 24] = [
+    "concept",
+    "dépendances",
+    "corequis",
+    "catégorie",
+    "unité",
+    "sujet",
+    "semaine",
+    "au plus tôt",
+    "au plus tard",
+    "poids cours",
+    "poids tp",
+    "poids devoirs",
+    "couverture cours",
+    "couverture tp",
+    "couverture devoirs",
+    "notes de dépendances",
+    "alias",
+    "description",
+    "url",
+    "enseignant",
+    "parcours",
+    "acquis",
+    "statut",
+    "type",
+];
+const ES: [&str; 24] = [
+    "concepto",
+    "dependencias",
+    "correquisitos",
+    "categoría",
+    "unidad",
+    "tema",
+    "semana",
+    "más temprano",
+    "más tarde",
+    "peso clase",
+    "peso laboratorio",
+    "peso tareas",
+    "cobertura clase",
+    "cobertura laboratorio",
+    "cobertura tareas",
+    "notas de dependencias",
+    "alias",
+    "descripción",
+    "url",
+    "profesor",
+    "itinerarios",
+    "resultados",
+    "estado",
+    "tipo",
+];
+
+impl Locale {
+    pub fn decimal_comma(&self) -> bool {
+        *self != Locale::En
+    }
+
+    /// The csv field for a header in this locale, or in English, as
+    /// exported spreadsheets often keep some, ignoring case in either.
+    pub fn field(&self, header: &str) -> Option<&'static str> {
+        let names = match self {
+            Locale::En => &FIELDS,
+            Locale::De => &DE,
+            Locale::Fr => &FR,
+            Locale::Es => &ES,
+        };
+        let header = header.trim().to_lowercase();
+
+        names
+            .iter()
+            .chain(&FIELDS)
+            .position(|n| *n == header)
+            .map(|i| FIELDS[i % FIELDS.len()])
+    }
+}
+
+/// A number with a decimal comma, with a decimal point instead, and
+/// without the thousands separators, e.g., "1.000,5" (or "1 000,5", in
+/// French) as "1000.5". Numbers already with a decimal point, e.g.,
+/// "0.5", are left alone, but a mix that could be either, e.g.,
+/// "1,000.5", is rejected with `None`.
+pub fn decimal_point(cell: &str) -> Option<String> {
+    let cell: String = cell
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\u{a0}' | '\u{202f}'))
+        .collect();
+
+    match (cell.matches(',').count(), cell.rfind(','), cell.rfind('.')) {
+        (0, _, _) => Some(cell),
+        (1, Some(comma), Some(point)) if point > comma => None,
+        (1, _, _) => Some(cell.replace('.', "").replace(',', ".")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_match_ignoring_case_in_every_locale() {
+        assert_eq!(Locale::En.field("Concept"), Some("concept"));
+        assert_eq!(Locale::De.field("Konzept"), Some("concept"));
+        assert_eq!(Locale::De.field("Lecture Weight"), Some("lecture weight"));
+        assert_eq!(Locale::Fr.field("colonne"), None);
+    }
+
+    #[test]
+    fn decimal_commas_and_thousands_separators() {
+        assert_eq!(decimal_point("0,5").as_deref(), Some("0.5"));
+        assert_eq!(decimal_point("1.000,5").as_deref(), Some("1000.5"));
+        assert_eq!(decimal_point("1 000,5").as_deref(), Some("1000.5"));
+        assert_eq!(decimal_point("0.5").as_deref(), Some("0.5"));
+        assert_eq!(decimal_point("1,000.5"), None);
+        assert_eq!(decimal_point("1,2,3"), None);
+    }
+}
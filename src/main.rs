@@ -1,15 +1,79 @@
-use anyhow;
-use dot_writer::{Attributes, DotWriter, Shape, Style};
+mod audit;
+mod balance;
+mod capacity;
+mod chains;
+mod curriculum;
+mod degree;
+mod diagnostics;
+mod diff;
+mod dot_input;
+mod exit;
+mod export;
+mod find;
+mod gantt;
+mod glossary;
+mod goals;
+mod graph_input;
+mod html;
+mod input;
+mod instructors;
+mod label;
+mod lectures;
+mod lint;
+mod load;
+mod locale;
+mod mermaid_input;
+mod metrics;
+mod milestones;
+mod normalize;
+mod order;
+mod org;
+mod outcomes;
+mod overlap;
+mod plan;
+mod poster;
+mod program;
+mod query;
+mod schedule;
+mod site;
+mod slack;
+mod slides;
+mod soft;
+mod stats;
+mod suggest;
+mod syllabus;
+mod tikz;
+mod typst;
+mod units;
+mod xlsx;
+mod xlsx_input;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use diagnostics::{Diagnostics, FailOn, Level};
+use dot_writer::{Attributes, DotWriter, Scope, Shape, Style};
 use itertools::Itertools; // for join on hashset
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
-use std::io;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
-#[derive(Debug, Deserialize)]
+// The coverage columns are parsed, but not yet used.
+#[allow(dead_code)]
+#[derive(Debug, Default, Deserialize)]
 struct ConceptRecord {
     concept: String,
     dependencies: String,
+    corequisites: Option<String>,
     category: Option<String>,
+    unit: Option<String>,
+    topic: Option<String>,
     week: Option<u64>,
     earliest: Option<u64>,
     latest: Option<u64>,
@@ -25,97 +89,293 @@ struct ConceptRecord {
     lab_coverage: Option<f64>,
     #[serde(rename = "hw coverage")]
     hw_coverage: Option<f64>,
+    #[serde(rename = "dependency notes")]
+    dependency_notes: Option<String>,
+    aliases: Option<String>,
+    description: Option<String>,
+    url: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    instructor: Option<String>,
+    tracks: Option<String>,
+    outcomes: Option<String>,
+    status: Option<String>,
 }
 
 type ConceptName = String;
 
+// The modalities, in the order of each concept's `modes`.
+const MODE_NAMES: [&str; 3] = ["Lecture", "Lab", "HW"];
+
 #[derive(Debug)]
 struct ConceptMap {
     nconcepts: usize,
     concepts: Vec<Concept>,
     lookup: HashMap<ConceptName, usize>, // mapping to offsets into `concepts`
     dependency_order: Vec<ConceptName>,
+    // Errors keep the map from being scheduled; warnings are about
+    // what was ignored or fixed in the csv.
     errors: String,
+    warnings: String,
     total_weights: [f64; 3],
+    semester_weeks: Option<f64>,
+    units: units::Units,
 }
 
 impl ConceptMap {
     fn new() -> Self {
         ConceptMap {
-            nconcepts: 0, // just tracking the line number
+            nconcepts: 0, // just counting the records
             concepts: Vec::new(),
             lookup: HashMap::new(),
             dependency_order: Vec::new(),
             errors: String::from(""),
+            warnings: String::from(""),
             total_weights: [0.0, 0.0, 0.0],
+            semester_weeks: None,
+            units: units::Units::default(),
         }
     }
 
-    fn errs(&self) -> Option<String> {
-        if self.errors == String::from("") {
-            None
-        } else {
-            Some(self.errors.clone())
-        }
-    }
-
-    fn graph(&self) -> Vec<u8> {
-        let mut output_bytes = Vec::new();
+    // Render the graph. If `only` names a category, just that
+    // category's concepts are drawn, along with grayed-out boundary
+    // nodes for the concepts on the other side of cross-category
+    // dependencies.
+    fn graph(&self, opts: &RenderOpts, only: Option<&str>) -> Vec<u8> {
+        let shown = |c: &Concept| only.is_none_or(|cat| c.category == cat);
+        let out = DotBuffer::new(opts.compact);
         {
-            let mut writer = DotWriter::from(&mut output_bytes);
+            let mut buffer = out.clone();
+            let mut writer = DotWriter::from(&mut buffer);
             let mut digraph = writer.digraph();
-            let mut colors = HashMap::new();
-            let colormap = [
-                "cadetblue1",
-                "chocolate1",
-                "darkgoldenrod1",
-                "darkorchid1",
-                "deeppink",
-                "dodgerblue2",
-                "firebrick1",
-                "gray38",
-                "green3",
-                "navy",
-                "orchid",
-                "teal",
-                "violetred",
-                "yellow1",
-                "tomato1",
-            ];
-            let mut color_idx = 0;
+            let colors = self.category_colors();
+            let grays = self.category_grays();
+            let depths = self.depths();
+            let blocked = if opts.size_by_blocking {
+                query::blocked(self)
+            } else {
+                Vec::new()
+            };
+            let most_blocked = blocked.iter().copied().max().unwrap_or(0).max(1);
+            let schedule = schedule::Schedule::new(self);
+            let node_label = |c: &Concept| {
+                let template = opts.label_template.as_deref().unwrap_or(label::DEFAULT);
+                let mut label = label::render(template, c, &schedule, depths[c.offset]);
+                if opts.show_depth {
+                    label.push_str(&format!("\nDepth: {}", depths[c.offset]));
+                }
+                if opts.size_by_blocking {
+                    label.push_str(&format!("\nBlocks: {}", blocked[c.offset]));
+                }
+                if opts.accessible {
+                    label.push_str(&format!("\nCategory: {}", category_label(&c.category)));
+                }
+                dot_escape(&label)
+            };
+
+            let concept_node = |g: &mut Scope, c: &Concept| {
+                let mut node = g.node_named(&c.node_id);
+                if opts.grayscale {
+                    node.set("color", "black", true)
+                        .set("fillcolor", grays[&c.category].0, true);
+                } else {
+                    // unwrap: added in previous loop
+                    node.set("color", colors.get(&c.category).unwrap(), true);
+                }
+                if let Some(shape) = c.kind.shape().or_else(|| opts.modality_shape(c)) {
+                    node.set("shape", shape, true);
+                }
+                node.set_label(&node_label(c));
+                if opts.size_by_blocking {
+                    // from the nodes' font size, to twice that
+                    let base = f64::from(opts.node_font_size.unwrap_or(14.0));
+                    let size = base * (1.0 + blocked[c.offset] as f64 / most_blocked as f64);
+                    node.set("fontsize", &format!("{:.1}", size), false);
+                }
+                if opts.accessible {
+                    node.set("tooltip", &dot_escape(&self.describe(c)), true);
+                }
+                if opts
+                    .highlight_weight_over
+                    .is_some_and(|max| c.modes[0].weight > max)
+                {
+                    node.set_pen_width(6.0).set("peripheries", "2", false);
+                }
+                // faded, as the concept isn't (or is no longer) ready,
+                // which takes precedence over the grayscale borders
+                let border = match c.status {
+                    Status::Ready => None,
+                    Status::Planned | Status::Drafted => {
+                        node.set("fontcolor", "gray40", false);
+                        Some("dashed")
+                    }
+                    Status::Retired => {
+                        node.set("fontcolor", "gray60", false);
+                        Some("dotted")
+                    }
+                };
+                if opts.grayscale {
+                    let border = border.unwrap_or(grays[&c.category].1);
+                    node.set("style", &format!("filled,{}", border), true);
+                } else if let Some(border) = border {
+                    node.set("style", border, true);
+                }
+            };
 
+            opts.set_layout(&mut digraph);
+            opts.set_fonts(&mut digraph);
+            opts.set_titles(&mut digraph);
+            opts.set_edge_style(&mut digraph);
             digraph.node_attributes().set("penwidth", "2.5", false);
-            for c in &self.concepts {
-                if !colors.contains_key(&c.category) {
-                    assert_ne!(color_idx, colormap.len()); // don't support more than this many categories
-                    colors.insert(&c.category, &colormap[color_idx]);
-                    color_idx += 1;
+            // Each unit's topics as nested clusters, in the order they
+            // first appear. Concepts outside of a unit or topic are
+            // drawn one level up.
+            let topic_clusters = |g: &mut Scope, concepts: &[&Concept]| {
+                for topic in concepts.iter().map(|c| &c.topic).unique() {
+                    let in_topic = concepts.iter().filter(|c| &c.topic == topic);
+                    if topic.is_empty() {
+                        in_topic.for_each(|c| concept_node(g, c));
+                        continue;
+                    }
+                    let mut cluster = g.cluster();
+                    cluster
+                        .set_label(&dot_escape(topic))
+                        .set_style(Style::Dashed)
+                        .set("color", "gray50", false);
+                    in_topic.for_each(|c| concept_node(&mut cluster, c));
+                }
+            };
+            if self.has_hierarchy() {
+                for unit in self.units() {
+                    let concepts: Vec<_> = self
+                        .concepts
+                        .iter()
+                        .filter(|c| shown(c) && &c.unit == unit)
+                        .collect();
+                    if concepts.is_empty() {
+                        continue;
+                    }
+                    if unit.is_empty() {
+                        out.comment("Concepts outside of any unit");
+                        topic_clusters(&mut digraph, &concepts);
+                        continue;
+                    }
+                    out.comment(&dot_comment(&format!("Unit {}", unit)));
+                    let mut cluster = digraph.cluster();
+                    cluster
+                        .set_label(&dot_escape(unit))
+                        .set_style(Style::Rounded)
+                        .set("penwidth", "2", false);
+                    topic_clusters(&mut cluster, &concepts);
+                }
+            } else {
+                for cat in self.categories() {
+                    let concepts: Vec<_> = self
+                        .concepts
+                        .iter()
+                        .filter(|c| shown(c) && &c.category == cat)
+                        .collect();
+                    if concepts.is_empty() {
+                        continue;
+                    }
+                    out.comment(&dot_comment(&format!(
+                        "Concepts in {}",
+                        category_label(cat)
+                    )));
+                    for c in concepts {
+                        concept_node(&mut digraph, c);
+                    }
                 }
             }
-            for c in &self.concepts {
-                digraph.node_named(c.graph_name.to_string()).set(
-                    "color",
-                    &colors.get(&c.category).unwrap().to_string(),
-                    true,
-                );
-                // unwrap: added in previous loop
+            if only.is_some() {
+                let mut boundary = HashSet::new();
+                for c in &self.concepts {
+                    for d in &c.dependencies {
+                        let dep_c = self.dependency_to_concept(d);
+
+                        if shown(c) && !shown(dep_c) {
+                            boundary.insert(&dep_c.concept);
+                        } else if !shown(c) && shown(dep_c) {
+                            boundary.insert(&c.concept);
+                        }
+                    }
+                }
+                out.comment("Boundary concepts, in other categories");
+                for c in self
+                    .concepts
+                    .iter()
+                    .filter(|c| boundary.contains(&c.concept))
+                {
+                    digraph
+                        .node_named(&c.node_id)
+                        .set_label(&node_label(c))
+                        .set_style(Style::Dashed)
+                        .set("color", "gray", false)
+                        .set("fontcolor", "gray", false);
+                }
             }
-            let summary_name = format!(
-                "\"Summary\nLecture {:.2} weeks\nLab {:.2} weeks\nHW {:.2} weeks\"",
-                self.total_weights[0], self.total_weights[1], self.total_weights[2]
+            let (title, totals) = match only {
+                Some(cat) => (format!("Summary: {}", cat), self.category_weights(cat)),
+                None => (String::from("Summary"), self.total_weights),
+            };
+            let mut summary = format!(
+                "{}\nLecture {}\nLab {}\nHW {}",
+                title,
+                self.weight_label(0, totals[0]),
+                self.weight_label(1, totals[1]),
+                self.weight_label(2, totals[2])
             );
+            if only.is_none() {
+                summary.push_str(if self.units.percent_of_course {
+                    "\n\nBy category (lecture/lab/HW %):"
+                } else {
+                    "\n\nBy category (lecture/lab/HW weeks):"
+                });
+                for cat in self.categories() {
+                    let ws = self.category_weights(cat);
+                    summary.push_str(&format!(
+                        "\n{}: {:.2} / {:.2} / {:.2}",
+                        category_label(cat),
+                        self.shown_weight(0, ws[0]),
+                        self.shown_weight(1, ws[1]),
+                        self.shown_weight(2, ws[2])
+                    ));
+                }
+            }
+            out.comment("Weight summary");
             digraph
-                .node_named(summary_name.to_string())
+                .node_named("summary")
+                .set_label(&dot_escape(&summary))
                 .set_shape(Shape::None)
-                .set_font_size(20.0);
+                .set_font_size(opts.summary_font_size);
 
-            for (cat, col) in &colors {
-                digraph
-                    .node_named(cat.to_string())
-                    .set_style(Style::Filled)
-                    .set_shape(Shape::Rectangle)
-                    .set("color", col, true);
+            if !matches!(opts.legend, Legend::Hide) {
+                out.comment("Category legend");
+            }
+            match opts.legend {
+                Legend::Nodes => self.legend(&mut digraph, opts, only),
+                Legend::Cluster => {
+                    let mut cluster = digraph.cluster();
+                    cluster.set_label("Categories").set_style(Style::Dashed);
+                    self.legend(&mut cluster, opts, only);
+                }
+                Legend::Hide => (),
             }
+            if opts.rank_by_depth {
+                out.comment("Concepts at each depth");
+                for depth in 0..=depths.iter().copied().max().unwrap_or(0) {
+                    let mut level = digraph.subgraph();
+                    level.set("rank", "same", false);
+                    for c in self
+                        .concepts
+                        .iter()
+                        .filter(|c| shown(c) && depths[c.offset] == depth)
+                    {
+                        level.node_named(&c.node_id);
+                    }
+                }
+            }
+            out.comment("Dependencies");
             self.dependency_order
                 .iter()
                 .map(|o| self.dependency_to_concept(o))
@@ -123,38 +383,337 @@ impl ConceptMap {
                     for d in &c.dependencies {
                         let dep_c = self.dependency_to_concept(d);
 
-                        digraph.edge(c.graph_name.to_string(), dep_c.graph_name.to_string());
+                        if !shown(c) && !shown(dep_c) {
+                            continue;
+                        }
+                        let (from, to) = opts.edge(&c.node_id, &dep_c.node_id);
+                        let edge = digraph.edge(from, to);
+                        if let Some(note) = c.dependency_notes.get(d) {
+                            let attr = match opts.edge_notes {
+                                EdgeNotes::Label => "label",
+                                EdgeNotes::Tooltip => "tooltip",
+                            };
+                            edge.attributes().set(attr, &dot_escape(note), true);
+                        }
                     }
                 });
+            let coreqs: Vec<(&Concept, &Concept)> = self
+                .concepts
+                .iter()
+                .flat_map(|c| {
+                    c.corequisites
+                        .iter()
+                        .map(move |d| (c, self.dependency_to_concept(d)))
+                })
+                .filter(|(c, d)| c.offset < d.offset && (shown(c) || shown(d)))
+                .collect();
+            if !coreqs.is_empty() {
+                out.comment("Corequisites");
+            }
+            for (c, d) in coreqs {
+                digraph
+                    .edge(&c.node_id, &d.node_id)
+                    .attributes()
+                    .set_style(Style::Dotted)
+                    .set("dir", "none", false)
+                    .set("constraint", "false", false);
+            }
+            let soft: Vec<(&Concept, &ConceptName)> = self
+                .concepts
+                .iter()
+                .flat_map(|c| c.soft_dependencies.iter().map(move |d| (c, d)))
+                .filter(|(c, d)| shown(c) || shown(self.dependency_to_concept(d)))
+                .collect();
+            if !soft.is_empty() {
+                out.comment("Soft dependencies");
+            }
+            for (c, d) in soft {
+                let (from, to) = opts.edge(&c.node_id, &self.dependency_to_concept(d).node_id);
+                let edge = digraph.edge(from, to);
+                let mut attrs = edge.attributes();
+                attrs
+                    .set_style(Style::Dashed)
+                    .set("constraint", "false", false);
+                if let Some(note) = c.dependency_notes.get(d) {
+                    let attr = match opts.edge_notes {
+                        EdgeNotes::Label => "label",
+                        EdgeNotes::Tooltip => "tooltip",
+                    };
+                    attrs.set(attr, &dot_escape(note), true);
+                }
+            }
         }
-        output_bytes
+        out.into_bytes()
     }
 
-    fn dependency_to_concept(&self, n: &ConceptName) -> &Concept {
-        let idx = *self.lookup.get(n).unwrap(); // we've already validated all entries
-        &self.concepts[idx]
+    // The concept in words, for screen readers.
+    fn describe(&self, c: &Concept) -> String {
+        let deps: Vec<&str> = c
+            .dependencies
+            .iter()
+            .map(|d| self.dependency_to_concept(d).concept.as_str())
+            .collect();
+
+        if deps.is_empty() {
+            format!("{}, in {}", c.concept, category_label(&c.category))
+        } else {
+            format!(
+                "{}, in {}, builds on {}",
+                c.concept,
+                category_label(&c.category),
+                deps.join(", ")
+            )
+        }
+    }
+
+    // A filled box in each (shown) category's color.
+    fn legend(&self, g: &mut Scope, opts: &RenderOpts, only: Option<&str>) {
+        let colors = self.category_colors();
+        let grays = self.category_grays();
+
+        for (i, cat) in self.categories().into_iter().enumerate() {
+            if only.is_some_and(|o| o != cat) {
+                continue;
+            }
+            let mut label = category_label(cat).to_string();
+            if opts.legend_counts {
+                let n = self.concepts.iter().filter(|c| &c.category == cat).count();
+                label.push_str(&format!(" ({})", n));
+            }
+            let mut node = g.node_named(category_id(i, cat));
+            node.set_label(&dot_escape(&label))
+                .set_shape(Shape::Rectangle);
+            opts.set_category_fill(&mut *node, &colors[cat], grays[cat]);
+        }
+    }
+
+    fn category_weights(&self, cat: &str) -> [f64; 3] {
+        self.weights_where(|c| c.category == cat)
+    }
+
+    // The total weights of the (unretired) concepts matching `filter`.
+    fn weights_where(&self, filter: impl Fn(&Concept) -> bool) -> [f64; 3] {
+        let mut ws = [0.0, 0.0, 0.0];
+
+        for c in self
+            .concepts
+            .iter()
+            .filter(|c| filter(c) && c.status != Status::Retired)
+        {
+            for (i, w) in ws.iter_mut().enumerate() {
+                *w += c.modes[i].weight;
+            }
+        }
+        ws
+    }
+
+    // Each category's fill shade and border style for grayscale
+    // printing, assigned in order of first appearance. Every pair of
+    // the first 16 categories differs in one or the other.
+    fn category_grays(&self) -> HashMap<&String, (&'static str, &'static str)> {
+        let shades = ["white", "gray85", "gray65", "gray45"];
+        let borders = ["solid", "dashed", "dotted", "bold"];
+
+        self.categories()
+            .into_iter()
+            .enumerate()
+            .map(|(i, cat)| (cat, (shades[(i + i / 4) % 4], borders[i % 4])))
+            .collect()
+    }
+
+    // Each category's color, assigned in order of first appearance.
+    fn category_colors(&self) -> HashMap<&String, String> {
+        let mut colors = HashMap::new();
+        let colormap = [
+            "cadetblue1",
+            "chocolate1",
+            "darkgoldenrod1",
+            "darkorchid1",
+            "deeppink",
+            "dodgerblue2",
+            "firebrick1",
+            "gray38",
+            "green3",
+            "navy",
+            "orchid",
+            "teal",
+            "violetred",
+            "yellow1",
+            "tomato1",
+        ];
+        let cats = self.categories();
+        let extra = cats.len().saturating_sub(colormap.len());
+
+        for (i, cat) in cats.into_iter().enumerate() {
+            let color = match colormap.get(i) {
+                Some(color) => color.to_string(),
+                None => hue_color(i - colormap.len(), extra),
+            };
+            colors.insert(cat, color);
+        }
+        colors
+    }
+
+    // The 10,000-foot view: one node per category, sized by its total
+    // weight, with edges aggregating the cross-category dependencies.
+    fn overview(&self, opts: &RenderOpts) -> Vec<u8> {
+        let colors = self.category_colors();
+        let grays = self.category_grays();
+        let cats = self.categories();
+        let mut edges: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        let cat_idx = |cat: &String| cats.iter().position(|c| *c == cat).unwrap(); // all categories present
+
+        for c in &self.concepts {
+            for d in &c.dependencies {
+                let dep_c = self.dependency_to_concept(d);
+
+                if c.category != dep_c.category {
+                    let key = (cat_idx(&c.category), cat_idx(&dep_c.category));
+                    *edges.entry(key).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let node_id = |cat: &String| category_id(cat_idx(cat), cat);
+        let out = DotBuffer::new(opts.compact);
+        {
+            let mut buffer = out.clone();
+            let mut writer = DotWriter::from(&mut buffer);
+            let mut digraph = writer.digraph();
+
+            opts.set_layout(&mut digraph);
+            opts.set_fonts(&mut digraph);
+            opts.set_titles(&mut digraph);
+            opts.set_edge_style(&mut digraph);
+            digraph.node_attributes().set("penwidth", "2.5", false);
+            out.comment("Categories");
+            for cat in &cats {
+                let ws = self.category_weights(cat);
+                let total: f64 = ws.iter().sum();
+                let name = dot_escape(category_label(cat));
+
+                let mut node = digraph.node_named(node_id(cat));
+                // unwrap: all categories have colors
+                opts.set_category_fill(&mut *node, colors.get(cat).unwrap(), grays[cat]);
+                node.set("width", &format!("{:.2}", 1.0 + total.sqrt()), false)
+                    .set_label(&format!(
+                        "{}\nLecture {}\nLab {}\nHW {}",
+                        name,
+                        self.weight_label(0, ws[0]),
+                        self.weight_label(1, ws[1]),
+                        self.weight_label(2, ws[2])
+                    ));
+            }
+            out.comment("Cross-category dependencies");
+            for ((cat, dep_cat), count) in &edges {
+                let (from, to) = opts.edge(cats[*cat], cats[*dep_cat]);
+                digraph
+                    .edge(node_id(from), node_id(to))
+                    .attributes()
+                    .set_label(&count.to_string());
+            }
+        }
+        out.into_bytes()
+    }
+
+    // The offsets of each concept's direct dependents, indexed by
+    // offset.
+    fn dependents(&self) -> Vec<Vec<usize>> {
+        let mut ds = vec![Vec::new(); self.concepts.len()];
+
+        for c in &self.concepts {
+            for d in &c.dependencies {
+                ds[self.dependency_to_concept(d).offset].push(c.offset);
+            }
+        }
+        ds
+    }
+
+    // Each concept's depth in the dependency graph, indexed by offset:
+    // one more than the deepest of its dependencies. Concepts in
+    // dependency cycles aren't in the dependency order, and stay at 0.
+    fn depths(&self) -> Vec<usize> {
+        let mut depths = vec![0; self.concepts.len()];
+
+        for n in &self.dependency_order {
+            let c = self.dependency_to_concept(n);
+            depths[c.offset] = c
+                .dependencies
+                .iter()
+                .map(|d| depths[self.dependency_to_concept(d).offset] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        depths
+    }
+
+    // Each concept's slug, numbered ("-2", "-3", ...) after the first
+    // concept whose name has the same words, and "concept" for names
+    // without any.
+    fn add_slugs(&mut self) {
+        let mut taken = HashSet::new();
+
+        for c in &mut self.concepts {
+            let mut words = ascii_words(&c.concept, '-');
+            if words.is_empty() {
+                words = String::from("concept");
+            }
+            let mut slug = words.clone();
+            let mut n = 1;
+            while !taken.insert(slug.clone()) {
+                n += 1;
+                slug = format!("{}-{}", words, n);
+            }
+            c.slug = slug;
+        }
+    }
+
+    fn categories(&self) -> Vec<&String> {
+        self.concepts.iter().map(|c| &c.category).unique().collect()
+    }
+
+    // The units, in order of first appearance, with "" for the concepts
+    // outside of any.
+    fn units(&self) -> Vec<&String> {
+        self.concepts.iter().map(|c| &c.unit).unique().collect()
+    }
+
+    // The topics within `unit`, as with units.
+    fn topics(&self, unit: &str) -> Vec<&String> {
+        self.concepts
+            .iter()
+            .filter(|c| c.unit == unit)
+            .map(|c| &c.topic)
+            .unique()
+            .collect()
+    }
+
+    fn has_hierarchy(&self) -> bool {
+        self.concepts
+            .iter()
+            .any(|c| !c.unit.is_empty() || !c.topic.is_empty())
     }
 
-    fn dependency_to_concept_mut(&mut self, n: &ConceptName) -> &mut Concept {
+    fn dependency_to_concept(&self, n: &ConceptName) -> &Concept {
         let idx = *self.lookup.get(n).unwrap(); // we've already validated all entries
-        &mut self.concepts[idx]
+        &self.concepts[idx]
     }
 
     fn solve_dependency_transitive_closure(
         &self,
-        mut dep_closure: &mut HashMap<ConceptName, HashSet<ConceptName>>,
+        dep_closure: &mut HashMap<ConceptName, HashSet<ConceptName>>,
         n: &ConceptName,
     ) {
         // Base case #1: Have we already solved this component's
         // dependency transitive closure? This is the memoization
         // logic.
-        if let Some(_) = dep_closure.get(n) {
+        if dep_closure.get(n).is_some() {
             return;
         }
         let ds = &self.dependency_to_concept(n).dependencies;
 
         // Base case #2: do we have a concept with no dependencies?
-        if ds.len() == 0 {
+        if ds.is_empty() {
             let deps: HashSet<ConceptName> = ds.iter().cloned().collect();
             // unwrap: we know there is no previous value due to the check above.
             dep_closure.insert(n.to_string(), deps);
@@ -166,7 +725,7 @@ impl ConceptMap {
         // transitive closures.
         let mut all: HashSet<ConceptName> = ds.iter().cloned().collect();
         for d in ds {
-            self.solve_dependency_transitive_closure(&mut dep_closure, &d);
+            self.solve_dependency_transitive_closure(dep_closure, d);
 
             let tc = dep_closure.get(d).unwrap(); // just inserted!
             all = all.union(tc).cloned().collect();
@@ -175,142 +734,490 @@ impl ConceptMap {
     }
 
     fn solve_total_weights(&mut self) {
-        for i in 0..3 {
-            self.total_weights[i] = self.concepts.iter().map(|c| c.modes[i].weight).sum();
-        }
+        self.total_weights = self.total_weights();
     }
 
-    fn solve(&mut self) -> String {
-        let mut all_deps: HashMap<ConceptName, HashSet<ConceptName>> = HashMap::new();
-        // separate the solving for the weights from the
-        // data-structure to avoid borrow + modify issues
-        let mut weights: HashMap<ConceptName, f64> = HashMap::new();
-	let mut starts: HashMap<ConceptName, [f64; 3]> = HashMap::new();
+    // Each modality's weights, without the retired concepts'.
+    fn total_weights(&self) -> [f64; 3] {
+        let mut totals = [0.0; 3];
 
-        self.solve_total_weights();
+        for (i, total) in totals.iter_mut().enumerate() {
+            *total = self.concepts.iter().map(|c| c.scheduled_weight(i)).sum();
+        }
+        totals
+    }
 
+    // Solve for each concept's earliest starts and latest end, without
+    // changing the map, so that solving twice finds the same schedule.
+    // Requires the dependencies to be acyclic.
+    fn solve(&self) -> SolveResult {
+        let mut all_deps: HashMap<ConceptName, HashSet<ConceptName>> = HashMap::new();
         for c in &self.concepts {
             self.solve_dependency_transitive_closure(&mut all_deps, &c.concept);
-            weights.insert(c.concept.clone(), c.modes[0].weight);
-	    starts.insert(c.concept.clone(), [0.0, 0.0, 0.0]);
         }
+        let mut earliest_starts = vec![[0.0; 3]; self.concepts.len()];
 
-	for c in &mut self.concepts {
+        // A lecture starts after all of its (transitive) dependencies'.
+        for c in &self.concepts {
             let all = all_deps.get(&c.concept).unwrap(); // just inserted!
-            c.modes[0].range.earliest_start = all
+            earliest_starts[c.offset][0] = all
                 .iter()
-                .map(|d| weights.get(d).unwrap()) // added in previous loop
-                .fold(0.0, |p, n| p + n);
+                .map(|d| self.dependency_to_concept(d).scheduled_weight(0))
+                .fold(0.0, |p, n| p + n); // not sum(), which starts from -0.0
         }
 
         for i in 1..3 {
-	    // Go through the depended on concepts first. For each
-	    // start by setting its earliest start time to its mode -
-	    // 1, then go through all of our dependencies and see if
-	    // there are any later start times among this mode's
-	    // times. This assumes that this mode's times are always
-	    // higher (for a dependency) than their mode - 1 start
-	    // time. This is a property of each concept's start time's
-	    // initialization.
-	    for n in &self.dependency_order {
+            // Go through the depended on concepts first. Each has to
+            // wait for its mode - 1 instruction, and then for all of
+            // its (transitive) dependencies' instruction in this mode.
+            for n in &self.dependency_order {
                 let c = self.dependency_to_concept(n);
                 let ds = all_deps.get(n).unwrap(); // assigned above!
-                // have to wait at least for the previous mode's instruction
-                let mut earliest = c.modes[i - 1].range.earliest_start + c.modes[i - 1].weight;
+                let mut earliest = earliest_starts[c.offset][i - 1] + c.modes[i - 1].weight;
 
                 for dn in ds {
                     let d = self.dependency_to_concept(dn);
-		    let start = d.modes[i].range.earliest_start + d.modes[i].weight;
-		    if start > earliest {
-			earliest = start;
-		    }
+                    earliest = earliest.max(earliest_starts[d.offset][i] + d.scheduled_weight(i));
                 }
+                earliest_starts[c.offset][i] = earliest;
+            }
+        }
 
+        SolveResult {
+            latest_ends: self.solve_latest_ends(&all_deps),
+            earliest_starts,
+            total_weights: self.total_weights(),
+        }
+    }
 
-		let ws = starts.get_mut(&c.concept).unwrap();
-		ws[i] = earliest;
+    // Record the solved schedule in the concepts, for the outputs.
+    fn apply(&mut self, result: &SolveResult) {
+        for c in &mut self.concepts {
+            for (i, mode) in c.modes.iter_mut().enumerate() {
+                mode.range.earliest_start = result.earliest_starts[c.offset][i];
             }
+            c.modes[0].range.latest_end = result.latest_ends[c.offset];
         }
+        self.total_weights = result.total_weights;
+    }
 
-	for i in 1..3 {
-	    for (cn, ws) in &starts {
-		self.dependency_to_concept_mut(cn).modes[i].range.earliest_start = ws[i];
-	    }
-	}
+    // The latest each concept's lecture can end, for everything that
+    // depends on it to still fit in the course. Mirroring the earliest
+    // start, all of its (transitive) dependents have to be taught
+    // after it. The csv's `latest` week (by which a concept must have
+    // started) further constrains it, and in turn, its dependencies.
+    fn solve_latest_ends(&self, all_deps: &HashMap<ConceptName, HashSet<ConceptName>>) -> Vec<f64> {
+        let course_end = self.course_weeks();
+        let mut downstream: HashMap<&ConceptName, f64> = HashMap::new();
 
-        for c in &mut self.concepts {
-            c.graph_name = format!(
-                "\"{}\nEarliest lecture: {:.2}\nEarliest lab: {:.2}\nEarliest HW: {:.2}\"",
-                c.concept,
-                c.modes[0].range.earliest_start,
-                c.modes[1].range.earliest_start,
-                c.modes[2].range.earliest_start
-            );
+        for (n, ds) in all_deps {
+            let w = self.dependency_to_concept(n).scheduled_weight(0);
+            for d in ds {
+                *downstream.entry(d).or_insert(0.0) += w;
+            }
+        }
+        let mut ends: Vec<f64> = self
+            .concepts
+            .iter()
+            .map(|c| course_end - downstream.get(&c.concept).copied().unwrap_or(0.0))
+            .collect();
+
+        // dependents come later in the dependency order
+        let dependents = self.dependents();
+        for n in self.dependency_order.iter().rev() {
+            let c = self.dependency_to_concept(n);
+            let mut end = ends[c.offset];
+
+            for &d in &dependents[c.offset] {
+                end = end.min(ends[d] - self.concepts[d].scheduled_weight(0));
+            }
+            if let Some(latest) = c.latest {
+                end = end.min(latest as f64 + c.modes[0].weight);
+            }
+            ends[c.offset] = end;
+        }
+        ends
+    }
+
+    // A weight in `mode` as shown in labels, summaries, and exports: in
+    // weeks, or as a percentage of the modality's total.
+    fn shown_weight(&self, mode: usize, weight: f64) -> f64 {
+        let total = self.total_weights[mode];
+
+        if self.units.percent_of_course && total > 0.0 {
+            weight / total * 100.0
+        } else {
+            weight
+        }
+    }
+
+    fn weight_label(&self, mode: usize, weight: f64) -> String {
+        if self.units.percent_of_course {
+            format!("{:.1}%", self.shown_weight(mode, weight))
+        } else {
+            self.units.weeks(weight)
+        }
+    }
+
+    // The length of the course, in weeks: the semester if we know it,
+    // otherwise, just long enough for all of the lectures.
+    fn course_weeks(&self) -> f64 {
+        self.semester_weeks.unwrap_or(self.total_weights[0])
+    }
+
+    fn render(&self, opts: &RenderOpts) -> String {
+        String::from_utf8_lossy(&self.graph(opts, None)).to_string()
+    }
+
+    // Write one graph per category into `dir`, named after the category.
+    fn render_split(&self, opts: &RenderOpts, dir: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(dir)?;
+        // Categories can differ only in punctuation or case, e.g., "OS/FS"
+        // and "OS FS", so the later ones are numbered rather than
+        // overwriting the earlier ones' files.
+        let mut taken = HashSet::new();
+        for cat in self.categories() {
+            let base: String = if cat.is_empty() {
+                String::from("uncategorized")
+            } else {
+                cat.chars()
+                    .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+                    .collect()
+            };
+            let mut name = base.clone();
+            for n in 2.. {
+                if taken.insert(name.to_lowercase()) {
+                    break;
+                }
+                name = format!("{}_{}", base, n);
+            }
+            let path = dir.join(format!("{}.dot", name));
+            fs::write(path, self.graph(opts, Some(cat)))?;
         }
+        Ok(())
+    }
+}
 
-        String::from_utf8_lossy(&self.graph()).to_string()
+// For a name that's neither a concept's nor an alias, the ones it was
+// likely meant to be, if any are close.
+fn did_you_mean(
+    name: &str,
+    lookup: &HashMap<ConceptName, usize>,
+    aliases: &HashMap<String, ConceptName>,
+) -> String {
+    let names: Vec<String> = input::closest(name, lookup.keys().chain(aliases.keys()))
+        .iter()
+        .map(|n| format!("\"{}\"", n))
+        .collect();
+
+    if names.is_empty() {
+        return String::new();
     }
+    format!(" Did you mean {}?", names.join(" or "))
 }
 
 struct ConceptMapBuilder {
     map: ConceptMap,
+    dep_separator: char,
+    // Only the concepts in this track, and those in every track, are
+    // added; the names of the others are kept for the errors about
+    // depending on them.
+    track: Option<String>,
+    other_tracks: HashSet<ConceptName>,
+    // the file the concepts being added are from, in a curriculum
+    file: Option<String>,
 }
 
 impl ConceptMapBuilder {
-    fn new() -> Self {
+    fn new(dep_separator: char, track: Option<String>) -> Self {
         ConceptMapBuilder {
             map: ConceptMap::new(),
+            dep_separator,
+            track,
+            other_tracks: HashSet::new(),
+            file: None,
         }
     }
 
-    fn add(&mut self, c: ConceptRecord) {
+    // `line` is the record's in the csv, if it was read from one.
+    fn add(&mut self, mut c: ConceptRecord, line: Option<u64>) {
         let map = &mut self.map;
-        map.nconcepts = map.nconcepts + 1;
+        map.nconcepts += 1;
+        let at = Location {
+            file: self.file.clone(),
+            line,
+            record: map.nconcepts,
+        };
+
+        let name = normalize::name(&c.concept);
+        if name != c.concept {
+            map.warnings.push_str(
+                format!(
+                "- Normalized the quotes, dashes, or accents in concept \"{}\" on {} to \"{}\".\n",
+                c.concept, at, name
+            )
+                .as_str(),
+            );
+            c.concept = name;
+        }
+        let sep = self.dep_separator;
+        let deps = normalize::list(&c.dependencies, sep);
+        if deps != c.dependencies {
+            map.warnings.push_str(format!(
+                "- Normalized the quotes, dashes, or accents in the dependencies of concept \"{}\" on {} to {}.\n",
+                c.concept,
+                at,
+                input::split_list(&deps, sep)
+                    .iter()
+                    .map(|d| format!("\"{}\"", d))
+                    .join(", ")
+            ).as_str());
+            c.dependencies = deps;
+        }
+        c.dependency_notes = c.dependency_notes.map(|n| normalize::list(&n, sep));
+        c.aliases = c.aliases.map(|a| normalize::name(&a));
+        // "NaN" and "inf" parse as weights, but can't be scheduled
+        for (mode, w) in [&mut c.lecture_weight, &mut c.lab_weight, &mut c.hw_weight]
+            .iter_mut()
+            .enumerate()
+        {
+            if w.is_some_and(|w| !w.is_finite()) {
+                map.warnings.push_str(
+                    format!(
+                    "- The {} weight of concept \"{}\" on {} isn't a number of weeks. Using 0.\n",
+                    MODE_NAMES[mode].to_lowercase(), c.concept, at
+                )
+                    .as_str(),
+                );
+                **w = Some(0.0);
+            }
+        }
+        // concepts without tracks are in all of them
+        let tracks = input::split_list(c.tracks.as_deref().unwrap_or(""), ';');
+        if let Some(track) = &self.track {
+            if !tracks.is_empty() && !tracks.iter().any(|t| t.eq_ignore_ascii_case(track)) {
+                self.other_tracks.insert(c.concept);
+                return;
+            }
+        }
         if let Some(&redundant) = map.lookup.get(&c.concept) {
-            map.errors.push_str(format!(
-                "- Found redundant copy of concept \"{}\" in record {} (redundant with record {}). Ignoring concept entry.\n",
-                c.concept, map.nconcepts, map.concepts[redundant].line
+            map.warnings.push_str(format!(
+                "- Found redundant copy of concept \"{}\" on {} (redundant with {}). Ignoring concept entry.\n",
+                c.concept, at, map.concepts[redundant].location
             ).as_str());
             return;
         }
 
-        let mut concept = Concept::new(&c, map.nconcepts + 1);
+        let mut concept = Concept::new(&c, at.clone());
+        // Note: we cannot check if the dependencies are valid
+        // concepts yet, as they could be in concepts added later.
+        // See the validation after all concepts are added.
+        // A "?" marks a soft dependency, e.g., "recursion?".
         let mut deps: Vec<ConceptName> = Vec::new();
-        for d in c.dependencies.split(";") {
-            // Note: we cannot check if the dependency is a valid
-            // concept yet, as it could be in concepts added later.
-            // See the validation after all concepts are added.
-            if d != "" {
-                deps.push(d.trim().to_string());
+        let mut soft: Vec<ConceptName> = Vec::new();
+        for d in input::split_list(&c.dependencies, self.dep_separator) {
+            let (d, is_soft) = match d.strip_suffix('?') {
+                Some(name) => (name.trim().to_string(), true),
+                None => (d, false),
+            };
+            if deps.contains(&d) || soft.contains(&d) {
+                map.warnings.push_str(format!(
+                    "- Dependency on \"{}\" listed more than once in concept \"{}\" on {}. Ignoring the duplicate.\n",
+                    d, concept.concept, at
+                ).as_str());
+            } else if is_soft {
+                soft.push(d);
+            } else {
+                deps.push(d);
+            }
+        }
+
+        let notes = c.dependency_notes.as_deref().unwrap_or("");
+        for note in input::split_list(notes, self.dep_separator) {
+            match note.split_once('=') {
+                Some((d, reason)) if deps.iter().chain(&soft).any(|dep| dep == d.trim()) => {
+                    concept
+                        .dependency_notes
+                        .insert(d.trim().to_string(), reason.trim().to_string());
+                }
+                Some((d, _)) => map.warnings.push_str(format!(
+                    "- Note on \"{}\" in concept \"{}\" on {} is not for one of its dependencies. Ignoring note.\n",
+                    d.trim(), concept.concept, at
+                ).as_str()),
+                None => map.warnings.push_str(format!(
+                    "- Dependency note \"{}\" in concept \"{}\" on {} is not of the form Dependency=reason. Ignoring note.\n",
+                    note.trim(), concept.concept, at
+                ).as_str()),
+            }
+        }
+
+        concept.kind = match c.kind.as_deref().map(|k| k.trim().to_lowercase()) {
+            None => Kind::Concept,
+            Some(k) if k.is_empty() || k == "concept" => Kind::Concept,
+            Some(k) if k == "exam" => Kind::Exam,
+            Some(k) if k == "project" => Kind::Project,
+            Some(k) if k == "external" => Kind::External,
+            Some(k) => {
+                map.warnings.push_str(format!(
+                    "- Unknown type \"{}\" of concept \"{}\" on {}; expected concept, exam, project, or external. Treating it as a concept.\n",
+                    k, concept.concept, at
+                ).as_str());
+                Kind::Concept
+            }
+        };
+        // or marked external by its category, e.g., "extern: c"
+        if let Some(cat) = concept.category.strip_prefix("extern:") {
+            concept.category = cat.trim().to_string();
+            if concept.kind == Kind::Concept {
+                concept.kind = Kind::External;
+            }
+        }
+        concept.status = match c.status.as_deref().map(|s| s.trim().to_lowercase()) {
+            None => Status::Ready,
+            Some(s) if s.is_empty() || s == "ready" => Status::Ready,
+            Some(s) if s == "planned" => Status::Planned,
+            Some(s) if s == "drafted" => Status::Drafted,
+            Some(s) if s == "retired" => Status::Retired,
+            Some(s) => {
+                map.warnings.push_str(format!(
+                    "- Unknown status \"{}\" of concept \"{}\" on {}; expected planned, drafted, ready, or retired. Treating it as ready.\n",
+                    s, concept.concept, at
+                ).as_str());
+                Status::Ready
+            }
+        };
+        // Milestones aren't taught, so they don't add to the weights.
+        if concept.kind.is_milestone() && concept.modes.iter().any(|m| m.weight != 0.0) {
+            map.warnings.push_str(format!(
+                "- Milestone \"{}\" on {} has weights, but milestones take no time to teach. Ignoring its weights.\n",
+                concept.concept, at
+            ).as_str());
+            for mode in &mut concept.modes {
+                mode.weight = 0.0;
+            }
+        }
+        // Nor are external concepts, in this course.
+        if concept.kind == Kind::External && concept.modes.iter().any(|m| m.weight != 0.0) {
+            map.warnings.push_str(format!(
+                "- External concept \"{}\" on {} has weights, but it's taught in another course. Ignoring its weights.\n",
+                concept.concept, at
+            ).as_str());
+            for mode in &mut concept.modes {
+                mode.weight = 0.0;
             }
         }
 
         concept.add_dependencies(deps);
+        concept.soft_dependencies = soft;
+        let coreqs = normalize::list(c.corequisites.as_deref().unwrap_or(""), self.dep_separator);
+        concept.corequisites = input::split_list(&coreqs, self.dep_separator)
+            .into_iter()
+            .filter(|d| d != &concept.concept)
+            .unique()
+            .collect();
         let offset = map.concepts.len(); // where are we adding ourselves into the vector?
         concept.add_offset(offset);
         map.lookup.insert(concept.concept.clone(), offset);
         let name = concept.concept.clone();
         map.concepts.push(concept);
         assert_eq!(map.concepts[offset].concept, name);
-
-        return;
     }
 
     fn validate(&mut self) {
-        let mut errs = String::from("");
+        let mut warnings = String::from("");
         let m = &mut self.map;
 
-        for mut c in &mut m.concepts {
-            let mut ds = Vec::new();
+        // Dependencies can name concepts by their aliases, unless
+        // they're ambiguous.
+        let mut aliases: HashMap<String, ConceptName> = HashMap::new();
+        for c in &m.concepts {
+            for a in &c.aliases {
+                if m.lookup.contains_key(a) {
+                    if a != &c.concept {
+                        warnings.push_str(format!("- Alias \"{}\" of concept \"{}\" on {} is the name of another concept. Ignoring the alias.\n", a, c.concept, c.location).as_str());
+                    }
+                } else if let Some(other) = aliases.get(a) {
+                    if other != &c.concept {
+                        warnings.push_str(format!("- Alias \"{}\" of concept \"{}\" on {} is already an alias of \"{}\". Ignoring the alias.\n", a, c.concept, c.location, other).as_str());
+                    }
+                } else {
+                    aliases.insert(a.clone(), c.concept.clone());
+                }
+            }
+        }
+
+        for c in &mut m.concepts {
+            let mut ds: Vec<ConceptName> = Vec::new();
 
             for d in &c.dependencies {
-                if m.lookup.get(d).is_none() {
-                    errs.push_str(format!("- Dependency on \"{}\" in concept \"{}\" in record {} does not correspond to a concept. Ignoring dependency.\n", d, c.concept, c.line).as_str());
+                if let Some(name) = aliases.get(d) {
+                    if let Some(note) = c.dependency_notes.remove(d) {
+                        c.dependency_notes.insert(name.clone(), note);
+                    }
+                    if !ds.contains(name) && !c.dependencies.contains(name) {
+                        ds.push(name.clone());
+                    }
+                } else if self.other_tracks.contains(d) {
+                    warnings.push_str(format!("- Dependency on \"{}\" in concept \"{}\" on {} is only in other tracks than \"{}\". Ignoring dependency.\n", d, c.concept, c.location, self.track.as_deref().unwrap_or_default()).as_str());
+                } else if !m.lookup.contains_key(d) {
+                    warnings.push_str(format!("- Dependency on \"{}\" in concept \"{}\" on {} does not correspond to a concept.{} Ignoring dependency.\n", d, c.concept, c.location, did_you_mean(d, &m.lookup, &aliases)).as_str());
                 } else {
                     ds.push(d.clone());
                 }
             }
             c.dependencies = ds;
+
+            let mut soft: Vec<ConceptName> = Vec::new();
+            for d in std::mem::take(&mut c.soft_dependencies) {
+                let hard = format!("{}?", d);
+                if let Some(name) = aliases.get(&d) {
+                    if let Some(note) = c.dependency_notes.remove(&d) {
+                        c.dependency_notes.insert(name.clone(), note);
+                    }
+                    if !soft.contains(name) && !c.dependencies.contains(name) {
+                        soft.push(name.clone());
+                    }
+                } else if !m.lookup.contains_key(&d) && m.lookup.contains_key(&hard) {
+                    // a concept whose name ends in "?", e.g., "Why
+                    // concurrency?"
+                    if let Some(note) = c.dependency_notes.remove(&d) {
+                        c.dependency_notes.insert(hard.clone(), note);
+                    }
+                    c.dependencies.push(hard);
+                } else if self.other_tracks.contains(&d) {
+                    warnings.push_str(format!("- Soft dependency on \"{}\" in concept \"{}\" on {} is only in other tracks than \"{}\". Ignoring dependency.\n", d, c.concept, c.location, self.track.as_deref().unwrap_or_default()).as_str());
+                } else if !m.lookup.contains_key(&d) {
+                    warnings.push_str(format!("- Soft dependency on \"{}\" in concept \"{}\" on {} does not correspond to a concept.{} Ignoring dependency.\n", d, c.concept, c.location, did_you_mean(&d, &m.lookup, &aliases)).as_str());
+                } else if !c.dependencies.contains(&d) {
+                    soft.push(d);
+                }
+            }
+            c.soft_dependencies = soft;
+
+            let mut coreqs: Vec<ConceptName> = Vec::new();
+            for d in std::mem::take(&mut c.corequisites) {
+                let d = aliases.get(&d).cloned().unwrap_or(d);
+                if self.other_tracks.contains(&d) {
+                    warnings.push_str(format!("- Corequisite \"{}\" of concept \"{}\" on {} is only in other tracks than \"{}\". Ignoring corequisite.\n", d, c.concept, c.location, self.track.as_deref().unwrap_or_default()).as_str());
+                } else if !m.lookup.contains_key(&d) {
+                    warnings.push_str(format!("- Corequisite \"{}\" of concept \"{}\" on {} does not correspond to a concept.{} Ignoring corequisite.\n", d, c.concept, c.location, did_you_mean(&d, &m.lookup, &aliases)).as_str());
+                } else if d != c.concept && !coreqs.contains(&d) {
+                    coreqs.push(d);
+                }
+            }
+            c.corequisites = coreqs;
+        }
+        // Corequisites go both ways, whichever concept lists them.
+        let mut pairs: Vec<(usize, ConceptName)> = Vec::new();
+        for c in &m.concepts {
+            for d in &c.corequisites {
+                pairs.push((m.lookup[d], c.concept.clone()));
+            }
+        }
+        for (o, name) in pairs {
+            if !m.concepts[o].corequisites.contains(&name) {
+                m.concepts[o].corequisites.push(name);
+            }
         }
 
         // simple cycle detection
@@ -343,31 +1250,44 @@ impl ConceptMapBuilder {
                 }
             }
 
-            if !shrunk || pending.len() == 0 {
+            if !shrunk || pending.is_empty() {
                 break;
             }
         }
-        if pending.len() > 0 {
-            errs.push_str(
+        if !pending.is_empty() {
+            m.errors.push_str(
                 format!(
-                    "- Circular conceptual dependencies including (or depended on by) {} concepts: {}.\n",
-                    pending.len(),
+                    "- Circular conceptual dependencies including (or depended on by) {}: {}.\n",
+                    plural(pending.len(), "concept", "concepts"),
                     pending.iter().join(", ")
                 )
                 .as_str(),
             );
         }
 
-        // record errors
-        m.errors.push_str(&mut errs);
+        // record warnings
+        m.warnings.push_str(&warnings);
     }
 
     fn build(mut self) -> ConceptMap {
         self.validate();
+        self.map.solve_total_weights();
+        self.map.add_slugs();
         self.map
     }
 }
 
+// What solving the map finds, by concept offset. The solver doesn't
+// change the map, so this can be checked, or compared across maps,
+// before `ConceptMap::apply()` records it for the outputs.
+#[derive(Debug)]
+struct SolveResult {
+    earliest_starts: Vec<[f64; 3]>, // by modality
+    latest_ends: Vec<f64>,          // of the lectures
+    total_weights: [f64; 3],
+}
+
+#[allow(dead_code)]
 #[derive(Debug)]
 struct TimeRange {
     start: f64,
@@ -375,6 +1295,7 @@ struct TimeRange {
     latest_end: f64,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 struct Modality {
     range: TimeRange,
@@ -390,42 +1311,167 @@ impl Modality {
                 earliest_start: 0.0,
                 latest_end: 0.0,
             }),
-            weight: weight,
+            weight,
             coverage: 0.0,
         }
     }
 }
 
+// What a row is: a concept to teach, a milestone (an exam or
+// project) that depends on concepts having been taught, or an external
+// concept, assumed from a prerequisite course.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Concept,
+    Exam,
+    Project,
+    External,
+}
+
+impl Kind {
+    fn is_milestone(&self) -> bool {
+        matches!(self, Kind::Exam | Kind::Project)
+    }
+
+    fn shape(&self) -> Option<&'static str> {
+        match self {
+            Kind::Concept => None,
+            Kind::Exam => Some("doubleoctagon"),
+            Kind::Project => Some("house"),
+            Kind::External => Some("folder"),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Concept => "Concept",
+            Kind::Exam => "Exam",
+            Kind::Project => "Project",
+            Kind::External => "External",
+        }
+    }
+}
+
+// Where a concept's material is in its lifecycle. Retired concepts
+// aren't counted in the totals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Planned,
+    Drafted,
+    Ready,
+    Retired,
+}
+
+/// Where a concept's record is, for diagnostics: its line in the csv,
+/// and in a curriculum, its course's file. Records without lines, read
+/// from graphs or given as --exam, are numbered instead.
+#[derive(Debug, Clone)]
+struct Location {
+    file: Option<String>,
+    line: Option<u64>,
+    record: usize,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}", line)?,
+            None => write!(f, "record {}", self.record)?,
+        }
+        match &self.file {
+            Some(file) => write!(f, " of {}", file),
+            None => Ok(()),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Concept {
     concept: ConceptName,
+    kind: Kind,
+    status: Status,
     category: String,
-    line: usize,
+    // the hierarchy above concepts, if the csv has one: a unit's topics
+    // subdivide it, and both are empty if not given
+    unit: String,
+    topic: String,
+    location: Location,
     offset: usize,
     dependencies: Vec<ConceptName>,
+    // helpful, but not required: they're drawn, and reported on, but
+    // don't hold up the concept's schedule
+    soft_dependencies: Vec<ConceptName>,
+    // taught in the same week, both ways
+    corequisites: Vec<ConceptName>,
+    dependency_notes: HashMap<ConceptName, String>, // why each dependency exists
+    aliases: Vec<String>,
+    outcomes: Vec<String>, // the learning outcomes' ids
+    description: String,
+    url: Option<String>, // where the concept's material is
+    instructor: Option<String>,
+    // scheduling constraints from the csv, in weeks
+    week: Option<u64>,
+    earliest: Option<u64>,
+    latest: Option<u64>,
     modes: [Modality; 3],
-    graph_name: String,
+    node_id: String,
+    // for links to the concept in html: its name's words, e.g.,
+    // "virtual-memory", so they don't change as concepts are added
+    slug: String,
 }
 
 impl Concept {
-    pub fn new(r: &ConceptRecord, line: usize) -> Self {
+    pub fn new(r: &ConceptRecord, location: Location) -> Self {
         Concept {
             concept: r.concept.clone().trim().to_string(),
+            kind: Kind::Concept,
+            status: Status::Ready,
             category: r
                 .category
                 .clone()
                 .unwrap_or(String::from(""))
                 .trim()
                 .to_string(),
-            line,
+            unit: r.unit.clone().unwrap_or_default().trim().to_string(),
+            topic: r.topic.clone().unwrap_or_default().trim().to_string(),
+            location,
             offset: 0,
             dependencies: Vec::new(),
+            soft_dependencies: Vec::new(),
+            corequisites: Vec::new(),
+            dependency_notes: HashMap::new(),
+            aliases: r
+                .aliases
+                .iter()
+                .flat_map(|a| a.split(';'))
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            outcomes: input::split_list(r.outcomes.as_deref().unwrap_or(""), ';')
+                .into_iter()
+                .unique()
+                .collect(),
+            description: r.description.clone().unwrap_or_default().trim().to_string(),
+            url: r
+                .url
+                .as_ref()
+                .map(|u| u.trim().to_string())
+                .filter(|u| !u.is_empty()),
+            instructor: r
+                .instructor
+                .as_ref()
+                .map(|i| i.trim().to_string())
+                .filter(|i| !i.is_empty()),
+            week: r.week,
+            earliest: r.earliest,
+            latest: r.latest,
             modes: [
                 Modality::new(r.lecture_weight.unwrap_or(0.0), None),
                 Modality::new(r.lab_weight.unwrap_or(0.0), None),
                 Modality::new(r.hw_weight.unwrap_or(0.0), None),
             ],
-            graph_name: String::from(""),
+            node_id: String::new(),
+            slug: String::new(),
         }
     }
 
@@ -435,26 +1481,1364 @@ impl Concept {
 
     fn add_offset(&mut self, offset: usize) {
         self.offset = offset;
+        self.node_id = dot_id(&format!("n{}", offset), &self.concept);
+    }
+
+    // Its name and aliases.
+    fn names(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.concept).chain(&self.aliases)
+    }
+
+    fn latest_start(&self) -> f64 {
+        hundredths(self.modes[0].range.latest_end - self.modes[0].weight)
+    }
+
+    // How far the lecture can slip without pushing any other concept
+    // past the end of the course, or past its `latest` week.
+    fn slack(&self) -> f64 {
+        let latest_start = self.modes[0].range.latest_end - self.modes[0].weight;
+        hundredths(latest_start - self.modes[0].range.earliest_start)
+    }
+
+    // The weeks it takes in `mode`, which retired concepts no longer do.
+    fn scheduled_weight(&self, mode: usize) -> f64 {
+        if self.status == Status::Retired {
+            0.0
+        } else {
+            self.modes[mode].weight
+        }
     }
+
+    // If the course teaches it (or holds it, for exams and projects),
+    // rather than expecting it from earlier courses or having retired
+    // it. The schedule's outputs only show these.
+    fn is_taught(&self) -> bool {
+        self.status != Status::Retired
+            && (self.kind.is_milestone() || self.modes.iter().any(|m| m.weight > 0.0))
+    }
+}
+
+/// Organization, visualization, and time allocation for class concept
+/// maps. Reads the concept csv on stdin (or from --input) and, by
+/// default, prints the concept map as a graphviz digraph.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    input: input::InputOpts,
+    #[command(flatten)]
+    render: RenderOpts,
+    #[command(flatten)]
+    units: units::Units,
+    /// Output format (dot, html, schedule-csv, schedule-json, org,
+    /// tikz, typst, xlsx, canvas, lms-csv, glossary-md, glossary-html,
+    /// lecture-outline, slides-reveal, slides-beamer, syllabus, report, or
+    /// site),
+    /// written to FILE rather than stdout if given (the site, into the
+    /// directory FILE); can be given repeatedly, to write several
+    /// outputs in one run
+    #[arg(
+        long,
+        value_name = "FORMAT[=FILE]",
+        value_parser = parse_emit,
+        help_heading = "Output"
+    )]
+    emit: Vec<(Emit, Option<PathBuf>)>,
+    /// Write an output to FILE, in the format its extension is for:
+    /// .dot or .gv (dot), .html, .csv (schedule-csv), .json (report),
+    /// .md (syllabus), .org, .tex (tikz), .typ (typst), or .xlsx; can be
+    /// given repeatedly, along with --emit
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_parser = parse_out,
+        help_heading = "Output"
+    )]
+    out: Vec<(Emit, PathBuf)>,
+    /// Instead of printing one graph, write a graph per category into
+    /// this directory, with grayed-out boundary nodes for concepts in
+    /// other categories
+    #[arg(long, value_name = "DIR", help_heading = "Output")]
+    split_by_category: Option<PathBuf>,
+    /// Print an overview graph with one node per category, and edges
+    /// labeled by the number of cross-category dependencies
+    #[arg(long, help_heading = "Output", conflicts_with = "split_by_category")]
+    overview: bool,
+    /// Warn when any modality's weights add up to more than this many
+    /// weeks, suggesting concepts to cut
+    #[arg(long, global = true, value_name = "WEEKS")]
+    semester_weeks: Option<f64>,
+    /// Warn about concepts whose lab and HW weight is less than RATIO
+    /// times their lecture weight, e.g., lectured on but never practiced
+    #[arg(long, global = true, value_name = "RATIO")]
+    min_practice_ratio: Option<f64>,
+    /// Warn about concepts whose lecture weight is less than RATIO
+    /// times their lab and HW weight, e.g., practiced but never lectured
+    #[arg(long, global = true, value_name = "RATIO")]
+    min_lecture_ratio: Option<f64>,
+    /// Warn about concepts with more than N direct dependencies
+    #[arg(long, global = true, value_name = "N")]
+    max_dependencies: Option<usize>,
+    /// Warn about concepts with more than N direct dependents
+    #[arg(long, global = true, value_name = "N")]
+    max_dependents: Option<usize>,
+    /// Don't warn about concepts without dependencies or dependents,
+    /// e.g., for intentionally standalone topics
+    #[arg(long, global = true)]
+    allow_orphans: bool,
+    /// Warn about concepts that CONCEPT (e.g., one of the final
+    /// project's skills) doesn't transitively depend on, nor any other
+    /// goal; can be given repeatedly
+    #[arg(long = "goal", global = true, value_name = "CONCEPT")]
+    goals: Vec<String>,
+    /// Add an exam in WEEK covering CONCEPTS (separated as
+    /// dependencies are); can be given repeatedly
+    #[arg(
+        long = "exam",
+        global = true,
+        value_name = "[name=NAME:]week=WEEK:concepts=CONCEPTS",
+        value_parser = milestones::parse_exam
+    )]
+    exams: Vec<milestones::ExamSpec>,
+    /// Only include the concepts in this track (from the csv's tracks
+    /// column), along with those in every track
+    #[arg(long, global = true, value_name = "TRACK")]
+    track: Option<String>,
+    /// Fail on any errors or warnings about the csv file, and on
+    /// schedules that can't fit; see the exit codes in src/exit.rs
+    #[arg(long, global = true)]
+    strict: bool,
+    /// Fail on errors in the csv file (e.g., dependency cycles), on
+    /// them and any warnings about it (e.g., ignored dependencies, or
+    /// redundant records) as with --strict, or never; by default never,
+    /// except for lint, which fails on warnings
+    #[arg(long, global = true, value_enum, value_name = "LEVEL")]
+    fail_on: Option<FailOn>,
+    /// How to print the errors and warnings about the csv file (to
+    /// stderr, where csv leaves out the other notes)
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    diagnostics: diagnostics::Format,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Emit {
+    /// Graphviz digraph
+    Dot,
+    /// Self-contained html page with collapsible categories
+    Html,
+    /// The weekly schedule as csv
+    ScheduleCsv,
+    /// The weekly schedule as json
+    ScheduleJson,
+    /// The weekly schedule as an Emacs Org file
+    Org,
+    /// TikZ picture of the graph, for LaTeX documents
+    Tikz,
+    /// Typst diagram of the graph, and table of the schedule
+    Typst,
+    /// Excel workbook of the concepts, schedule, weekly load, and warnings
+    Xlsx,
+    /// Mermaid gantt chart of the schedule, a section per category and
+    /// a bar per concept's lectures
+    Gantt,
+    /// Canvas LMS modules, one per week, as json
+    Canvas,
+    /// Flat week, topic, description, and link csv for LMS imports
+    LmsCsv,
+    /// Markdown glossary of the concepts' descriptions
+    GlossaryMd,
+    /// Html glossary of the concepts' descriptions
+    GlossaryHtml,
+    /// Markdown outline of each lecture, with the concepts packed into
+    /// them, for slides
+    LectureOutline,
+    /// Reveal.js slide deck skeleton, a section per week and a slide
+    /// per concept
+    SlidesReveal,
+    /// Beamer slide deck skeleton, a section per week and a frame per
+    /// concept
+    SlidesBeamer,
+    /// Markdown syllabus, a section per week with its concepts and the
+    /// load so far
+    Syllabus,
+    /// One json document of the solved map, schedule, statistics, and
+    /// diagnostics
+    #[value(alias = "json")]
+    Report,
+    /// Static site of the graph, a page per concept, and a search, into
+    /// the directory given as site=DIR
+    Site,
+}
+
+fn parse_emit(s: &str) -> Result<(Emit, Option<PathBuf>), String> {
+    let (format, file) = match s.split_once('=') {
+        Some((format, file)) => (format, Some(PathBuf::from(file))),
+        None => (s, None),
+    };
+    let format = Emit::from_str(format.trim(), true).map_err(|_| {
+        let formats: Vec<_> = Emit::value_variants()
+            .iter()
+            .filter_map(|e| e.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        format!(
+            "unknown format \"{}\"; expected one of {}",
+            format,
+            formats.join(", ")
+        )
+    })?;
+    if format == Emit::Site && file.is_none() {
+        return Err(String::from(
+            "the site is written into a directory, given as site=DIR",
+        ));
+    }
+
+    Ok((format, file))
 }
 
-fn main() -> anyhow::Result<()> {
-    let mut rdr = csv::Reader::from_reader(io::stdin());
-    let mut mb = ConceptMapBuilder::new();
+fn parse_out(s: &str) -> Result<(Emit, PathBuf), String> {
+    let path = PathBuf::from(s);
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let format = match extension.as_str() {
+        "dot" | "gv" => Emit::Dot,
+        "html" | "htm" => Emit::Html,
+        "csv" => Emit::ScheduleCsv,
+        "json" => Emit::Report,
+        "md" => Emit::Syllabus,
+        "org" => Emit::Org,
+        "tex" => Emit::Tikz,
+        "typ" => Emit::Typst,
+        "xlsx" => Emit::Xlsx,
+        _ => {
+            return Err(format!(
+                "can't tell the format from the extension; give it as --emit FORMAT={}",
+                s
+            ))
+        }
+    };
 
-    for entry in rdr.deserialize() {
-        let concept: ConceptRecord = entry?;
+    Ok((format, path))
+}
 
-        mb.add(concept);
+// Write the solved map in `format`.
+fn emit<W: Write>(
+    m: &ConceptMap,
+    cli: &Cli,
+    diags: &Diagnostics,
+    format: Emit,
+    mut out: W,
+) -> anyhow::Result<()> {
+    match format {
+        Emit::Dot => match &cli.split_by_category {
+            Some(dir) => m.render_split(&cli.render, dir)?,
+            None if cli.overview => out.write_all(&m.overview(&cli.render))?,
+            None => writeln!(out, "{}", m.render(&cli.render))?,
+        },
+        Emit::Html => write!(out, "{}", html::render(m))?,
+        Emit::ScheduleCsv => export::schedule_csv(m, out)?,
+        Emit::ScheduleJson => export::schedule_json(m, out)?,
+        Emit::Org => write!(out, "{}", org::render(m))?,
+        Emit::Tikz => write!(out, "{}", tikz::render(m))?,
+        Emit::Typst => write!(out, "{}", typst::render(m))?,
+        Emit::Xlsx => out.write_all(&xlsx::render(m)?)?,
+        Emit::Gantt => write!(out, "{}", gantt::mermaid(m))?,
+        Emit::Canvas => export::canvas_modules(m, out)?,
+        Emit::LmsCsv => export::lms_csv(m, out)?,
+        Emit::GlossaryMd => write!(out, "{}", glossary::markdown(m))?,
+        Emit::GlossaryHtml => write!(out, "{}", glossary::html(m))?,
+        Emit::LectureOutline => write!(out, "{}", lectures::outline(m))?,
+        Emit::SlidesReveal => write!(out, "{}", slides::reveal(m))?,
+        Emit::SlidesBeamer => write!(out, "{}", slides::beamer(m))?,
+        Emit::Syllabus => write!(out, "{}", syllabus::markdown(m))?,
+        Emit::Report => export::report_json(m, diags, out)?,
+        Emit::Site => unreachable!(), // written into its directory by run()
+    }
+    Ok(())
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Rendering")]
+struct RenderOpts {
+    /// Graphviz layout engine to request in the generated graph
+    #[arg(long, value_enum)]
+    engine: Option<Engine>,
+    /// Minimum separation between ranks, in inches (dot only)
+    #[arg(long)]
+    ranksep: Option<f64>,
+    /// Minimum separation between nodes in a rank, in inches
+    #[arg(long)]
+    nodesep: Option<f64>,
+    /// How edges are drawn
+    #[arg(long, value_enum)]
+    splines: Option<Splines>,
+    /// Shape each concept by the modality with the largest weight
+    #[arg(long)]
+    shape_by_modality: bool,
+    /// Shapes for lecture-, lab-, and HW-heavy concepts, with
+    /// --shape-by-modality
+    #[arg(
+        long,
+        value_name = "LECTURE,LAB,HW",
+        default_value = "ellipse,box,diamond",
+        value_parser = parse_modality_shapes
+    )]
+    modality_shapes: [String; 3],
+    /// Draw a thick warning border around concepts with a lecture weight
+    /// over this many weeks
+    #[arg(long, value_name = "WEEKS")]
+    highlight_weight_over: Option<f64>,
+    /// Add each concept's depth, the longest chain of prerequisites
+    /// leading to it, to its label
+    #[arg(long)]
+    show_depth: bool,
+    /// Size each concept by how many concepts transitively depend on
+    /// it, whose schedules its slipping would hold up, and add their
+    /// number to its label
+    #[arg(long)]
+    size_by_blocking: bool,
+    /// Draw the concepts at each depth (the longest chain of
+    /// prerequisites leading to them) in the same row, so the levels
+    /// of prerequisites read top to bottom; dot can't also keep them in
+    /// unit and topic clusters
+    #[arg(long)]
+    rank_by_depth: bool,
+    /// The concepts' labels, with fields in braces, e.g.,
+    /// "{name}\n{category} · wk {week} · {lecture_weight}w"; the fields
+    /// are name, type, category, instructor, description, week, depth,
+    /// lecture_weight, lab_weight, hw_weight, earliest_lecture,
+    /// earliest_lab, earliest_hw, latest_start, slack, and dependencies
+    #[arg(long, value_name = "TEMPLATE", value_parser = label::parse_template)]
+    label_template: Option<String>,
+    /// Which way the arrows point
+    #[arg(long, value_enum, default_value = "builds-on")]
+    edge_direction: EdgeDirection,
+    /// Where the "dependency notes" for each edge are shown
+    #[arg(long, value_enum, default_value = "label")]
+    edge_notes: EdgeNotes,
+    /// How to draw the category legend, the boxes in each category's
+    /// color
+    #[arg(long, value_enum, default_value = "nodes")]
+    legend: Legend,
+    /// Add each category's number of concepts to its legend box
+    #[arg(long)]
+    legend_counts: bool,
+    /// Title at the top of the graph, e.g., "CSCI 3411 — Fall 2025"
+    #[arg(long)]
+    title: Option<String>,
+    /// Smaller line under the title
+    #[arg(long)]
+    subtitle: Option<String>,
+    /// Note at the bottom of the graph, e.g., the revision it's from
+    #[arg(long)]
+    footer: Option<String>,
+    /// Font family for all of the graph's text, e.g., "Fira Sans"
+    #[arg(long, value_name = "FAMILY")]
+    font: Option<String>,
+    /// Font size of the concepts' labels, in points
+    #[arg(long, value_name = "POINTS")]
+    node_font_size: Option<f32>,
+    /// Font size of the edges' labels (dependency notes, and counts in
+    /// overviews), in points
+    #[arg(long, value_name = "POINTS")]
+    edge_font_size: Option<f32>,
+    /// Font size of the footer, and of the graph's label when it has no
+    /// title, in points
+    #[arg(long, value_name = "POINTS")]
+    graph_font_size: Option<f32>,
+    /// Font size of the summary node, in points
+    #[arg(long, value_name = "POINTS", default_value_t = 20.0)]
+    summary_font_size: f32,
+    /// Instead of the categories' colors, use fill shades and border
+    /// styles, for black-and-white printing
+    #[arg(long)]
+    grayscale: bool,
+    /// Give each concept a tooltip (in graphviz's SVG) with its name,
+    /// category, and dependencies, and label it with its category, so
+    /// that categories aren't told apart only by color
+    #[arg(long)]
+    accessible: bool,
+    /// Write the DOT on one line, without the section comments, rather
+    /// than indented and grouped into commented sections
+    #[arg(long)]
+    compact: bool,
+}
+
+impl RenderOpts {
+    // The shape for the concept's dominant modality (the first, on
+    // ties), if shaping by modality. Concepts without any weight keep
+    // the default shape.
+    fn modality_shape(&self, c: &Concept) -> Option<&str> {
+        if !self.shape_by_modality {
+            return None;
+        }
+        let mut dominant = None;
+        let mut most = 0.0;
+        for (i, m) in c.modes.iter().enumerate() {
+            if m.weight > most {
+                most = m.weight;
+                dominant = Some(i);
+            }
+        }
+        dominant.map(|i| self.modality_shapes[i].as_str())
+    }
+
+    // Graph-level layout attributes, only emitted when requested so
+    // that graphviz's defaults otherwise apply.
+    fn set_layout<A: Attributes>(&self, g: &mut A) {
+        if let Some(e) = self.engine {
+            g.set("layout", e.as_str(), false);
+        }
+        if let Some(r) = self.ranksep {
+            g.set("ranksep", &r.to_string(), false);
+        }
+        if let Some(n) = self.nodesep {
+            g.set("nodesep", &n.to_string(), false);
+        }
+        if let Some(s) = self.splines {
+            g.set("splines", s.as_str(), false);
+        }
+    }
+
+    // The title and subtitle, as the graph's label, and the footer, as
+    // a plain node on the bottom rank.
+    fn set_titles(&self, g: &mut Scope) {
+        let mut label = String::new();
+        if let Some(t) = &self.title {
+            label.push_str(&format!(
+                "<FONT POINT-SIZE=\"28\">{}</FONT>",
+                html::escape(t)
+            ));
+        }
+        if let Some(s) = &self.subtitle {
+            if !label.is_empty() {
+                label.push_str("<BR/>");
+            }
+            label.push_str(&format!(
+                "<FONT POINT-SIZE=\"18\">{}</FONT>",
+                html::escape(s)
+            ));
+        }
+        if !label.is_empty() {
+            g.set("label", &format!("<{}>", label), false)
+                .set("labelloc", "t", false);
+        }
+        if let Some(f) = &self.footer {
+            let mut bottom = g.subgraph();
+            bottom.set("rank", "sink", false);
+            bottom
+                .node_named("footer")
+                .set_shape(Shape::None)
+                .set_label(&dot_escape(f));
+        }
+    }
+
+    // Font attributes, like the layout ones only emitted when requested.
+    fn set_fonts(&self, g: &mut Scope) {
+        if let Some(f) = &self.font {
+            g.set("fontname", &dot_escape(f), true);
+            g.node_attributes().set("fontname", &dot_escape(f), true);
+            g.edge_attributes().set("fontname", &dot_escape(f), true);
+        }
+        if let Some(s) = self.graph_font_size {
+            g.set_font_size(s);
+        }
+        if let Some(s) = self.node_font_size {
+            g.node_attributes().set_font_size(s);
+        }
+        if let Some(s) = self.edge_font_size {
+            g.edge_attributes().set_font_size(s);
+        }
+    }
+
+    // A category's node, filled with its color (or shade, in grayscale,
+    // with its border style).
+    fn set_category_fill<A: Attributes>(&self, node: &mut A, color: &str, gray: (&str, &str)) {
+        if self.grayscale {
+            node.set("style", &format!("filled,{}", gray.1), true)
+                .set("color", "black", true)
+                .set("fillcolor", gray.0, true);
+        } else {
+            node.set_style(Style::Filled).set("color", color, true);
+        }
+    }
+
+    // The ends of the edge between a concept (or category) and its
+    // prerequisite, in the direction the arrow points.
+    fn edge<'a, T: ?Sized>(&self, dependent: &'a T, prerequisite: &'a T) -> (&'a T, &'a T) {
+        match self.edge_direction {
+            EdgeDirection::BuildsOn => (dependent, prerequisite),
+            EdgeDirection::RequiredBy => (prerequisite, dependent),
+        }
+    }
+
+    // Open arrowheads set flipped graphs apart from the default ones.
+    fn set_edge_style(&self, g: &mut Scope) {
+        if self.edge_direction == EdgeDirection::RequiredBy {
+            g.edge_attributes().set("arrowhead", "vee", false);
+        }
+    }
+}
+
+fn parse_modality_shapes(s: &str) -> Result<[String; 3], String> {
+    let shapes: Vec<String> = s.split(',').map(|sh| sh.trim().to_string()).collect();
+
+    <[String; 3]>::try_from(shapes)
+        .map_err(|_| format!("expected three comma-separated shapes, found \"{}\"", s))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EdgeDirection {
+    /// From each concept to the concepts it builds on
+    BuildsOn,
+    /// From each prerequisite to the concepts that require it
+    RequiredBy,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Legend {
+    /// Loose nodes among the concepts
+    Nodes,
+    /// Grouped in a "Categories" box
+    Cluster,
+    /// No legend
+    Hide,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EdgeNotes {
+    Label,
+    Tooltip,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Engine {
+    Dot,
+    Neato,
+    Fdp,
+    Sfdp,
+}
+
+impl Engine {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Dot => "dot",
+            Self::Neato => "neato",
+            Self::Fdp => "fdp",
+            Self::Sfdp => "sfdp",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Splines {
+    None,
+    Line,
+    Polyline,
+    Curved,
+    Ortho,
+    Spline,
+}
+
+impl Splines {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::None => "none",
+            Self::Line => "line",
+            Self::Polyline => "polyline",
+            Self::Curved => "curved",
+            Self::Ortho => "ortho",
+            Self::Spline => "spline",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Suggest categories for concepts with a blank category, printing
+    /// the csv with a "proposed category" column
+    SuggestCategories,
+    /// Tile an EPS rendering of the map across letter/A4 pages, with
+    /// crop marks and overlap, printing the PostScript
+    Poster(poster::PosterArgs),
+    /// Print statistics about the concept map
+    Stats(stats::StatsArgs),
+    /// Map the concepts to the learning outcomes they cover, from the
+    /// "outcomes" column
+    Outcomes(outcomes::OutcomesArgs),
+    /// Chart the weight scheduled in each week, by category
+    Load(load::LoadArgs),
+    /// List concepts by how far their lectures can slip, least first
+    Slack,
+    /// Plan the weeks of a map whose windows and weekly capacity can't
+    /// all be met, listing the constraints it had to relax
+    Plan(plan::PlanArgs),
+    /// List the heaviest chains of dependencies, by lecture weight,
+    /// which the course can't be taught in less than
+    Chains(chains::ChainsArgs),
+    /// List the soft dependencies ("recursion?"), and whether the
+    /// schedule teaches them before the concepts they'd help with
+    SoftDeps,
+    /// Show the dependencies that add up to a concept's earliest lecture start
+    Explain {
+        /// The concept's name
+        concept: String,
+    },
+    /// List the concepts that a concept depends on
+    Deps(query::DepsArgs),
+    /// List the concepts that transitively depend on a concept
+    Rdeps(query::RdepsArgs),
+    /// List the concepts that the most others transitively depend on
+    Blocking(query::BlockingArgs),
+    /// Check that exams come after everything they cover
+    Exams,
+    /// Show the arc of project milestones, and where it outpaces lectures
+    Projects,
+    /// Summarize the changes between two versions of the csv, e.g.,
+    /// `diff --git HEAD~1 course.csv`
+    Diff(diff::DiffArgs),
+    /// Print a program-level graph of the courses' csv files, with an
+    /// edge for each course building on concepts another one teaches
+    Program(program::ProgramArgs),
+    /// Print the graph of several courses' csv files as one map, a
+    /// cluster per course, with dependencies across courses qualified
+    /// by their names, e.g., "OS::scheduling"
+    Curriculum(curriculum::CurriculumArgs),
+    /// List the concepts two courses' csv files both cover, with their
+    /// weights in each
+    Overlap(overlap::OverlapArgs),
+    /// Run every check on FILE (or stdin), printing one line per
+    /// problem and no outputs, and exit nonzero if there are any
+    Lint {
+        /// The csv file or URL; --input, or stdin, if not given
+        file: Option<PathBuf>,
+    },
+    /// Append the map's metrics, with the time and commit, to a json
+    /// file of snapshots
+    Snapshot(metrics::SnapshotArgs),
+    /// Show how the course has grown across the snapshots
+    Trend(metrics::TrendArgs),
+    /// Plan each instructor's weeks, and flag the overloaded ones
+    Instructors(instructors::InstructorsArgs),
+    /// Report which concepts a student is prepared for, from the
+    /// courses they've completed, and the prerequisites they're missing
+    Audit(audit::AuditArgs),
+    /// Print a teaching order that respects the dependencies, and its
+    /// score
+    Order(order::OrderArgs),
+    /// Search concept names, aliases, and descriptions
+    Find {
+        /// What to search for, ignoring case
+        query: String,
+    },
+}
+
+// The count with its noun, e.g., "1 concept" or "2 concepts".
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+// How to refer to a category in output; concepts without one are
+// grouped together.
+fn category_label(cat: &str) -> &str {
+    if cat.is_empty() {
+        "(uncategorized)"
+    } else {
+        cat
+    }
+}
+
+// Where graphs are written: shared with the DotWriter, so that section
+// comments (which it can't write) can go between its statements. Compact
+// output leaves them out, and joins the lines. (The DotWriter's own
+// compact output also strips the whitespace in quoted strings.)
+#[derive(Clone)]
+struct DotBuffer {
+    bytes: Rc<RefCell<Vec<u8>>>,
+    compact: bool,
+}
+
+impl DotBuffer {
+    fn new(compact: bool) -> Self {
+        DotBuffer {
+            bytes: Rc::default(),
+            compact,
+        }
+    }
+
+    // A comment line at the digraph's indentation. The statement before
+    // it must be complete (dropped).
+    fn comment(&self, text: &str) {
+        if !self.compact {
+            let mut bytes = self.bytes.borrow_mut();
+            bytes.extend_from_slice(format!("\n  // {}\n", text).as_bytes());
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let bytes = self.bytes.take();
+        if !self.compact {
+            return bytes;
+        }
+
+        let mut out = Vec::with_capacity(bytes.len());
+        let (mut quoted, mut escaped, mut line_start) = (false, false, false);
+        for b in bytes {
+            if quoted {
+                quoted = escaped || b != b'"';
+                escaped = !escaped && b == b'\\';
+            } else if b == b'\n' {
+                line_start = true;
+                continue;
+            } else if line_start && b == b' ' {
+                continue;
+            } else {
+                quoted = b == b'"';
+            }
+            line_start = false;
+            out.push(b);
+        }
+        out.push(b'\n');
+        out
+    }
+}
+
+impl Write for DotBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// The text's ASCII letters and digits (without their accents),
+// lowercased, with `separator` for each run of anything else, e.g.,
+// "virtual_memory" for "Virtual memory".
+fn ascii_words(text: &str, separator: char) -> String {
+    let mut words = String::new();
+
+    for ch in text.nfkd().filter(|ch| !is_combining_mark(*ch)) {
+        if ch.is_ascii_alphanumeric() {
+            words.push(ch.to_ascii_lowercase());
+        } else if !words.is_empty() && !words.ends_with(separator) {
+            words.push(separator);
+        }
+    }
+    words.trim_end_matches(separator).to_string()
+}
+
+// A DOT identifier for `text`: the prefix, an underscore, and then the
+// text's words, e.g., "n42_virtual_memory". The prefix keeps them
+// unique, and the labels show the text itself.
+fn dot_id(prefix: &str, text: &str) -> String {
+    let words = ascii_words(text, '_');
+
+    if words.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{}_{}", prefix, words)
+    }
+}
+
+// The category's node, given its offset in `categories()`.
+fn category_id(offset: usize, cat: &str) -> String {
+    dot_id(&format!("c{}", offset), cat)
+}
+
+// The `i`th of `n` evenly spaced hues, as an "#rrggbb" color that
+// Graphviz, css, and the other outputs all understand, for the
+// categories beyond the named colors. They're offset to fall between
+// the named colors' most common hues.
+fn hue_color(i: usize, n: usize) -> String {
+    let hue = (i as f64 / n as f64 + 0.1) % 1.0 * 6.0;
+    let (saturation, value) = (0.55, 0.9);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let byte = |c: f64| ((c + value - chroma) * 255.0).round() as u8;
+
+    format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b))
+}
+
+// Weeks to the hundredth, as in the rest of the output, rather than
+// with floating-point noise, which can leave -0.0 on the critical path.
+fn hundredths(weeks: f64) -> f64 {
+    (weeks * 100.0).round() / 100.0 + 0.0 // -0.0 + 0.0 is 0.0
+}
+
+// Text for quoted DOT strings. Line breaks are newlines, rather than
+// `\n`s, so every backslash is the text's own.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Text for DOT comments, where a line ending in a backslash continues
+// onto the next one.
+fn dot_comment(s: &str) -> String {
+    s.replace('\n', " ").trim_end_matches('\\').to_string()
+}
+
+// Solving needs the dependencies to be acyclic.
+fn solve(m: &mut ConceptMap) -> anyhow::Result<()> {
+    if m.dependency_order.len() != m.concepts.len() {
+        return Err(exit::fail(
+            exit::INFEASIBLE,
+            anyhow::anyhow!("The concepts can't be scheduled with circular dependencies."),
+        ));
+    }
+    let result = m.solve();
+    m.apply(&result);
+    Ok(())
+}
+
+// The csv's map, with the default options, solved, for the tests.
+#[cfg(test)]
+fn test_map(csv: &str) -> ConceptMap {
+    let cli = Cli::parse_from(["concept_map"]);
+    let (mut m, _, _) = read_map(&cli, csv.as_bytes()).unwrap();
+    solve(&mut m).unwrap();
+    m
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(exit::code(&e))
+        }
+    }
+}
+
+// The concept map, and the csv's headers and rows, from `input`.
+fn read_map<R: io::Read>(
+    cli: &Cli,
+    input: R,
+) -> anyhow::Result<(ConceptMap, csv::StringRecord, Vec<csv::StringRecord>)> {
+    read_map_from(cli, None, input)
+}
+
+// One of several concept maps from `input`, with `file` in the
+// locations of its diagnostics, to tell them apart.
+fn read_named_map<R: io::Read>(cli: &Cli, file: &str, input: R) -> anyhow::Result<ConceptMap> {
+    Ok(read_map_from(cli, Some(file.to_string()), input)?.0)
+}
+
+fn read_map_from<R: io::Read>(
+    cli: &Cli,
+    file: Option<String>,
+    input: R,
+) -> anyhow::Result<(ConceptMap, csv::StringRecord, Vec<csv::StringRecord>)> {
+    let (headers, rows) = input::read(&cli.input, input).map_err(exit::parse)?;
+    let columns = input::check_columns(&cli.input, &headers).map_err(exit::parse)?;
+    let mut mb = ConceptMapBuilder::new(cli.input.dep_separator, cli.track.clone());
+    mb.map.warnings.push_str(&columns);
+    mb.file = file;
+
+    for row in &rows {
+        let concept: ConceptRecord = row.deserialize(Some(&headers))?;
+
+        mb.add(
+            cli.units.in_weeks(concept),
+            row.position().map(|p| p.line()),
+        );
+    }
+    for e in &cli.exams {
+        mb.add(e.record(), None);
+    }
+
+    let mut m = mb.build();
+    m.semester_weeks = cli.semester_weeks;
+    m.units = cli.units;
+    Ok((m, headers, rows))
+}
+
+// The concept map from --input, or stdin.
+fn read_input_map(
+    cli: &Cli,
+) -> anyhow::Result<(ConceptMap, csv::StringRecord, Vec<csv::StringRecord>)> {
+    match &cli.input.source {
+        Some(path) => read_map(cli, input::load(path)?.as_slice()),
+        None => read_map(cli, io::stdin()),
+    }
+}
+
+fn input_name(cli: &Cli) -> String {
+    match &cli.input.source {
+        Some(path) => path.display().to_string(),
+        None => String::from("<stdin>"),
+    }
+}
+
+// The courses' concept maps as one, with their concepts namespaced by
+// their courses.
+fn read_courses(cli: &Cli, args: &curriculum::CurriculumArgs) -> anyhow::Result<ConceptMap> {
+    let mut mb = ConceptMapBuilder::new(cli.input.dep_separator, cli.track.clone());
+
+    for (course, file) in &args.courses {
+        let (headers, rows) =
+            input::read(&cli.input, input::load(file)?.as_slice()).map_err(exit::parse)?;
+        let columns = input::check_columns(&cli.input, &headers)
+            .map_err(|e| exit::parse(e.context(file.display().to_string())))?;
+        mb.map.warnings.push_str(&columns);
+        mb.file = Some(file.display().to_string());
+        for row in &rows {
+            let concept: ConceptRecord = row
+                .deserialize(Some(&headers))
+                .map_err(|e| anyhow::Error::new(e).context(file.display().to_string()))?;
+            let concept = curriculum::qualify_record(course, concept, cli.input.dep_separator);
+
+            mb.add(
+                cli.units.in_weeks(concept),
+                row.position().map(|p| p.line()),
+            );
+        }
     }
 
     let mut m = mb.build();
+    m.semester_weeks = cli.semester_weeks;
+    m.units = cli.units;
+    Ok(m)
+}
+
+// All of the checks on the map, with the number of validation
+// problems (errors and warnings about the csv), and of infeasibility
+// problems (milestones before what they depend on, and, with `slack`,
+// concepts that can't be taught in time).
+fn check(cli: &Cli, m: &mut ConceptMap, slack: bool) -> (Diagnostics, usize, usize) {
+    let mut diags = Diagnostics::default();
+    let mut problems = 0;
+    let mut infeasible = 0;
+
+    diags.add(Level::Error, "csv", "Errors in csv file", &m.errors);
+    problems += m.errors.lines().count();
+    diags.add(
+        Level::Warning,
+        "csv",
+        "Warnings about csv file",
+        &m.warnings,
+    );
+    problems += m.warnings.lines().count();
+    if let Some(weeks) = cli.semester_weeks {
+        let ws = capacity::check(m, weeks);
+        diags.add(
+            Level::Warning,
+            "capacity",
+            "Semester capacity warnings",
+            &ws,
+        );
+        problems += ws.lines().count();
+    }
+    let ws = balance::check(m, cli.min_practice_ratio, cli.min_lecture_ratio);
+    diags.add(Level::Warning, "balance", "Modality balance warnings", &ws);
+    problems += ws.lines().count();
+    let ws = degree::check(m, cli.max_dependencies, cli.max_dependents);
+    diags.add(Level::Warning, "degree", "Dependency count warnings", &ws);
+    problems += ws.lines().count();
+    if !cli.allow_orphans {
+        let ws = degree::check_orphans(m);
+        diags.add(Level::Warning, "orphan", "Orphaned concept warnings", &ws);
+        problems += ws.lines().count();
+    }
+    if !cli.goals.is_empty() {
+        let ws = goals::check(m, &cli.goals);
+        diags.add(Level::Warning, "goal", "Goal reachability warnings", &ws);
+        problems += ws.lines().count();
+    }
+
+    // Scheduling needs the dependencies to be acyclic.
+    let acyclic = m.dependency_order.len() == m.concepts.len();
+    let coreqs = m.concepts.iter().any(|c| !c.corequisites.is_empty());
+    let windows = m.concepts.iter().any(|c| c.latest.is_some());
+    if acyclic && (slack || coreqs || windows || m.concepts.iter().any(|c| c.kind.is_milestone())) {
+        let result = m.solve();
+        m.apply(&result);
+        let s = schedule::Schedule::new(m);
+        let ws = s.check_milestones(m);
+        diags.add(Level::Warning, "milestone", "Milestone warnings", &ws);
+        infeasible += ws.lines().count();
+        let ws = s.check_corequisites(m);
+        diags.add(Level::Warning, "corequisite", "Corequisite warnings", &ws);
+        infeasible += ws.lines().count();
+        let ws = schedule::check_windows(m);
+        diags.add(Level::Warning, "window", "Scheduling window warnings", &ws);
+        infeasible += ws.lines().count();
+        if slack {
+            let ws = slack::check(m);
+            diags.add(Level::Warning, "slack", "Schedule warnings", &ws);
+            infeasible += ws.lines().count();
+        }
+    }
+
+    (diags, problems, infeasible)
+}
+
+// Check the maps, and print their diagnostics, failing the run on the
+// ones --fail-on (or --strict) asks to.
+fn check_maps(cli: &Cli, maps: &mut [&mut ConceptMap]) -> anyhow::Result<Diagnostics> {
+    let fail_on = fail_on(cli);
+    let flag = if cli.strict {
+        "--strict"
+    } else {
+        "--fail-on warnings"
+    };
+    let (mut diags, mut problems, mut infeasible) = (Diagnostics::default(), 0, 0);
+    for m in maps.iter_mut() {
+        let (d, p, i) = check(cli, m, fail_on == FailOn::Warnings);
+        diags.extend(d);
+        problems += p;
+        infeasible += i;
+    }
+    diags.print_as(cli.diagnostics)?;
+    let errors = diags.count(Level::Error);
+    if fail_on == FailOn::Errors && errors > 0 {
+        return Err(exit::fail(
+            exit::VALIDATION,
+            anyhow::anyhow!(
+                "{}, with --fail-on errors.",
+                plural(errors, "error", "errors")
+            ),
+        ));
+    }
+    if fail_on == FailOn::Warnings && problems > 0 {
+        return Err(exit::fail(
+            exit::VALIDATION,
+            anyhow::anyhow!(
+                "{}, with {}.",
+                plural(problems, "error or warning", "errors or warnings"),
+                flag
+            ),
+        ));
+    }
+    if fail_on == FailOn::Warnings && infeasible > 0 {
+        return Err(exit::fail(
+            exit::INFEASIBLE,
+            anyhow::anyhow!(
+                "{} can't be scheduled in time, with {}.",
+                plural(infeasible, "concept or milestone", "concepts or milestones"),
+                flag
+            ),
+        ));
+    }
+    Ok(diags)
+}
 
-    if let Some(es) = m.errs() {
-        eprint!("Errors in csv file:\n{}", es);
+// --strict is --fail-on warnings.
+fn fail_on(cli: &Cli) -> FailOn {
+    if cli.strict {
+        FailOn::Warnings
+    } else {
+        cli.fail_on.unwrap_or(FailOn::Never)
     }
+}
+
+fn run() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    diagnostics::show_notes(matches!(cli.diagnostics, diagnostics::Format::Text));
+    match &cli.command {
+        Some(Command::Poster(args)) => return poster::write(args, io::stdout()),
+        Some(Command::Diff(args)) => {
+            let (old, new) = diff::versions(args)?;
+            let (old_name, new_name) = diff::version_names(args);
+            let mut old = read_named_map(&cli, &old_name, old.as_slice())?;
+            let mut new = read_named_map(&cli, &new_name, new.as_slice())?;
+            check_maps(&cli, &mut [&mut old, &mut new])?;
+            print!("{}", diff::report(&mut old, &mut new));
+            return Ok(());
+        }
+        Some(Command::Overlap(args)) => {
+            let load = |file: &Path| {
+                read_named_map(
+                    &cli,
+                    &file.display().to_string(),
+                    input::load(file)?.as_slice(),
+                )
+            };
+            let (mut a, mut b) = (load(&args.a)?, load(&args.b)?);
+            check_maps(&cli, &mut [&mut a, &mut b])?;
+            print!("{}", overlap::report(&a, &b, args));
+            return Ok(());
+        }
+        Some(Command::Program(args)) => {
+            let mut courses = Vec::new();
+            for file in &args.files {
+                let m = read_named_map(
+                    &cli,
+                    &file.display().to_string(),
+                    input::load(file)?.as_slice(),
+                )?;
+                courses.push((program::course_name(file), m));
+            }
+            let mut maps: Vec<&mut ConceptMap> = courses.iter_mut().map(|(_, m)| m).collect();
+            check_maps(&cli, &mut maps)?;
+            io::stdout().write_all(&program::graph(&courses, &cli.render))?;
+            return Ok(());
+        }
+        Some(Command::Curriculum(args)) => {
+            let mut m = read_courses(&cli, args)?;
+            check_maps(&cli, &mut [&mut m])?;
+            solve(&mut m)?;
+            writeln!(io::stdout(), "{}", m.render(&cli.render))?;
+            return Ok(());
+        }
+        Some(Command::Trend(args)) => {
+            print!("{}", metrics::trend(args)?);
+            return Ok(());
+        }
+        Some(Command::Lint { file }) => {
+            let (mut m, name) = match file {
+                Some(path) => (
+                    read_map(&cli, input::load(path)?.as_slice())?.0,
+                    path.display().to_string(),
+                ),
+                None => (read_input_map(&cli)?.0, input_name(&cli)),
+            };
+            let (diags, problems, infeasible) = check(&cli, &mut m, true);
+            print!("{}", lint::report(&name, &diags));
+            let errors = diags.count(Level::Error);
+            return match (
+                cli.fail_on.unwrap_or(FailOn::Warnings),
+                problems,
+                infeasible,
+            ) {
+                (FailOn::Never, _, _) => Ok(()),
+                (FailOn::Errors, _, _) if errors == 0 => Ok(()),
+                (FailOn::Errors, _, _) => Err(exit::fail(
+                    exit::VALIDATION,
+                    anyhow::anyhow!("The csv file has errors ({}).", errors),
+                )),
+                (_, 0, 0) => Ok(()),
+                (_, 0, n) => Err(exit::fail(
+                    exit::INFEASIBLE,
+                    anyhow::anyhow!(
+                        "The concepts can't all be scheduled in time ({}).",
+                        plural(n, "problem", "problems")
+                    ),
+                )),
+                (_, n, m) => Err(exit::fail(
+                    exit::VALIDATION,
+                    anyhow::anyhow!("The csv file has problems ({}).", n + m),
+                )),
+            };
+        }
+        _ => (),
+    }
+
+    let (mut m, headers, rows) = read_input_map(&cli)?;
+    let diags = check_maps(&cli, &mut [&mut m])?;
 
-    println!("{}", m.solve());
+    match cli.command {
+        None => {
+            solve(&mut m)?;
+            let mut outputs = cli.emit.clone();
+            outputs.extend(
+                cli.out
+                    .iter()
+                    .map(|(format, path)| (*format, Some(path.clone()))),
+            );
+            if outputs.is_empty() {
+                outputs.push((Emit::Dot, None));
+            }
+            for (format, file) in outputs {
+                match file {
+                    Some(dir) if format == Emit::Site => site::write(&m, &cli.render, &dir)?,
+                    Some(path) => emit(&m, &cli, &diags, format, fs::File::create(path)?)?,
+                    None => emit(&m, &cli, &diags, format, io::stdout().lock())?,
+                }
+            }
+        }
+        Some(Command::SuggestCategories) => {
+            suggest::write_suggestions(&m, &headers, &rows, io::stdout())?
+        }
+        Some(Command::Stats(args)) => {
+            print!("{}", stats::report(&m));
+            if args.roots_and_leaves {
+                print!("{}", stats::roots_and_leaves(&m));
+            }
+            if let Some(path) = &args.histogram_svg {
+                fs::write(path, stats::histogram_svg(&m))?;
+            }
+            if let Some(path) = &args.degrees_csv {
+                degree::distributions_csv(&m, fs::File::create(path)?)?;
+            }
+        }
+        Some(Command::Load(args)) => {
+            solve(&mut m)?;
+            let s = schedule::Schedule::new(&m);
+            if args.by_mode {
+                print!("{}", load::mode_table(&m, &s, args.capacity));
+            } else {
+                print!("{}", load::ascii_chart(&m, &s, args.mode));
+            }
+            if let Some(path) = &args.svg {
+                fs::write(path, load::svg_chart(&m, &s, args.mode))?;
+            }
+        }
+        Some(Command::Outcomes(args)) => {
+            print!("{}", outcomes::report(&m));
+            if let Some(path) = &args.matrix_csv {
+                outcomes::matrix_csv(&m, fs::File::create(path)?)?;
+            }
+        }
+        Some(Command::Slack) => {
+            solve(&mut m)?;
+            print!("{}", slack::report(&m));
+        }
+        Some(Command::Plan(args)) => {
+            solve(&mut m)?;
+            print!("{}", plan::report(&m, &args));
+        }
+        Some(Command::Chains(args)) => print!("{}", chains::report(&m, args.top)),
+        Some(Command::SoftDeps) => {
+            solve(&mut m)?;
+            print!("{}", soft::report(&m));
+        }
+        Some(Command::Explain { concept }) => {
+            solve(&mut m)?;
+            print!("{}", query::explain(&m, &concept)?);
+        }
+        Some(Command::Deps(args)) => print!("{}", query::deps(&m, &args)?),
+        Some(Command::Rdeps(args)) => print!("{}", query::rdeps(&m, &args)?),
+        Some(Command::Blocking(args)) => print!("{}", query::blocking(&m, &args)),
+        Some(Command::Find { query }) => {
+            solve(&mut m)?;
+            print!("{}", find::report(&m, &query));
+        }
+        Some(Command::Exams) => {
+            solve(&mut m)?;
+            print!("{}", milestones::exams(&m));
+        }
+        Some(Command::Projects) => {
+            solve(&mut m)?;
+            print!("{}", milestones::projects(&m));
+        }
+        Some(Command::Instructors(args)) => {
+            solve(&mut m)?;
+            print!("{}", instructors::report(&m, &args));
+        }
+        Some(Command::Audit(args)) => {
+            solve(&mut m)?;
+            print!("{}", audit::report(&m, &args)?);
+        }
+        Some(Command::Order(args)) => {
+            solve(&mut m)?;
+            print!("{}", order::report(&m, &args));
+        }
+        Some(Command::Snapshot(args)) => metrics::snapshot(&m, &args)?,
+        // handled before reading stdin
+        Some(Command::Poster(_))
+        | Some(Command::Diff(_))
+        | Some(Command::Overlap(_))
+        | Some(Command::Program(_))
+        | Some(Command::Curriculum(_))
+        | Some(Command::Lint { .. })
+        | Some(Command::Trend(_)) => unreachable!(),
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN: &str = "concept,dependencies,lecture weight,lab weight
+a,,1,0.5
+b,a,0.5,1
+c,,0.25,
+";
+
+    fn unsolved(csv: &str) -> ConceptMap {
+        let cli = Cli::parse_from(["concept_map"]);
+        read_map(&cli, csv.as_bytes()).unwrap().0
+    }
+
+    #[test]
+    fn solve_leaves_the_map_unchanged() {
+        let m = unsolved(CHAIN);
+        let (first, second) = (m.solve(), m.solve());
+
+        assert_eq!(first.earliest_starts, second.earliest_starts);
+        assert_eq!(first.latest_ends, second.latest_ends);
+        assert!(m
+            .concepts
+            .iter()
+            .all(|c| c.modes.iter().all(|mode| mode.range.earliest_start == 0.0)));
+    }
+
+    #[test]
+    fn solve_schedules_dependencies_first() {
+        let m = unsolved(CHAIN);
+        let result = m.solve();
+
+        // b's lecture waits for a's, and its lab for a's lab and its lecture
+        assert_eq!(result.earliest_starts[1], [1.0, 1.5, 2.5]);
+        assert_eq!(result.earliest_starts[2][0], 0.0);
+        assert!(result.earliest_starts[0][0].is_sign_positive());
+        assert_eq!(result.total_weights, [1.75, 1.5, 0.0]);
+        // everything after a has to fit in the course
+        assert_eq!(result.latest_ends[0], 1.75 - 0.5);
+    }
+
+    #[test]
+    fn retired_dependents_dont_take_time() {
+        let m = test_map(
+            "concept,dependencies,lecture weight,status
+Pointers,,1,
+Arrays,Pointers,1,
+Old Topic,Pointers,0.25,retired
+",
+        );
+
+        assert_eq!(m.total_weights[0], 2.0);
+        assert!(m.concepts.iter().all(|c| c.slack() >= 0.0));
+        assert_eq!(m.concepts[0].slack(), 0.0);
+    }
+
+    #[test]
+    fn dot_strings_escape_backslashes() {
+        assert_eq!(dot_escape("C:\\"), "C:\\\\");
+        assert_eq!(dot_escape("say \"hi\"\n"), "say \\\"hi\\\"\n");
+
+        let m = test_map("concept,category,dependencies\nC:\\,x\\,\n");
+        let dot = m.render(&Cli::parse_from(["concept_map"]).render);
+        assert!(dot.contains("label=\"C:\\\\\n"));
+        assert!(dot.contains("label=\"x\\\\\","));
+        assert!(dot
+            .lines()
+            .filter(|l| l.trim_start().starts_with("//"))
+            .all(|l| !l.ends_with('\\')));
+    }
+
+    #[test]
+    fn apply_records_the_result() {
+        let mut m = unsolved(CHAIN);
+        let result = m.solve();
+        m.apply(&result);
+
+        for c in &m.concepts {
+            for (i, mode) in c.modes.iter().enumerate() {
+                assert_eq!(
+                    mode.range.earliest_start,
+                    result.earliest_starts[c.offset][i]
+                );
+            }
+            assert_eq!(c.modes[0].range.latest_end, result.latest_ends[c.offset]);
+        }
+        assert_eq!(m.total_weights, result.total_weights);
+    }
+}
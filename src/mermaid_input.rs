@@ -0,0 +1,274 @@
+// Reading a Mermaid flowchart as the concept table, for the
+// prerequisite sketches on the wiki. Nodes in a subgraph get its title
+// as their category. Styling, classes, clicks, and link text are
+// ignored.
+
+use crate::graph_input::Graph;
+use crate::input::InputOpts;
+use csv::StringRecord;
+
+const IGNORED: [&str; 6] = [
+    "classDef",
+    "class",
+    "style",
+    "linkStyle",
+    "click",
+    "direction",
+];
+
+struct Line<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn skip_space(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let end = self.rest.find(|c| !f(c)).unwrap_or(self.rest.len());
+        let (taken, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        taken
+    }
+
+    // A node's id, and its text if it has a shape, e.g. `A`,
+    // `A[Pointers]`, `B(("Virtual memory"))`, or `C>flag]`.
+    fn node(&mut self) -> Option<(&'a str, Option<String>)> {
+        self.skip_space();
+        let id = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if id.is_empty() {
+            return None;
+        }
+        let mut text = None;
+        if self.rest.starts_with(['[', '(', '{', '>']) {
+            self.take_while(|c| "[({>/\\".contains(c));
+            let t = if let Some(quoted) = self.rest.strip_prefix('"') {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                self.rest = &quoted[(end + 1).min(quoted.len())..];
+                &quoted[..end]
+            } else {
+                self.take_while(|c| !"])}".contains(c))
+            };
+            self.take_while(|c| "])}/\\".contains(c));
+            // only the first line of multi-line text
+            let first = t.split("<br").next().unwrap_or(t);
+            text = Some(first.trim().to_string());
+        }
+        // class shorthand
+        if let Some(rest) = self.rest.strip_prefix(":::") {
+            self.rest = rest;
+            self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        }
+        Some((id, text))
+    }
+
+    // Nodes joined with `&`.
+    fn nodes(&mut self) -> Vec<(&'a str, Option<String>)> {
+        let mut nodes: Vec<_> = self.node().into_iter().collect();
+
+        loop {
+            self.skip_space();
+            match self.rest.strip_prefix('&') {
+                Some(rest) if !nodes.is_empty() => {
+                    self.rest = rest;
+                    nodes.extend(self.node());
+                }
+                _ => return nodes,
+            }
+        }
+    }
+
+    // A link, e.g. `-->`, `---`, `==>`, `-.->`, `--o`, `-- text -->`,
+    // or `-->|text|`, returning whether there was one.
+    fn link(&mut self) -> bool {
+        self.skip_space();
+        let op = self.take_while(|c| "<-=.".contains(c));
+        if op.len() < 2 {
+            return false;
+        }
+        // `A -- text --> B`: the text runs to the rest of the link
+        if ["--", "==", "-."].contains(&op) && !self.rest.starts_with(['>', 'o', 'x']) {
+            let end = ["-->", "---", "==>", "===", ".->", ".-"]
+                .iter()
+                .filter_map(|l| self.rest.find(l))
+                .min()
+                .unwrap_or(self.rest.len());
+            self.rest = &self.rest[end..];
+            self.take_while(|c| "-=.".contains(c));
+        }
+        // arrowheads
+        if self.rest.starts_with(['>', 'o', 'x'])
+            && self.rest[1..].starts_with(|c: char| c.is_whitespace() || c == '|')
+            || self.rest.starts_with('>')
+        {
+            self.rest = &self.rest[1..];
+        }
+        self.skip_space();
+        if let Some(text) = self.rest.strip_prefix('|') {
+            self.rest = text.find('|').map_or("", |end| &text[end + 1..]);
+        }
+        true
+    }
+}
+
+// A subgraph's title: `subgraph id [Title]`, `subgraph "Title"`, or
+// `subgraph Title`.
+fn subgraph_title(decl: &str) -> String {
+    let decl = decl.trim();
+    let title = match decl.find('[') {
+        Some(open) => decl[open + 1..].trim_end_matches(']'),
+        None => decl,
+    };
+
+    title.trim().trim_matches('"').trim().to_string()
+}
+
+// The `;`-separated statements on a line, leaving the `;`s in quoted
+// or bracketed node text, e.g. `A["malloc; free"]`, alone.
+fn statements(line: &str) -> Vec<&str> {
+    let (mut statements, mut start) = (Vec::new(), 0);
+    let (mut quoted, mut depth) = (false, 0);
+
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' | '{' if !quoted => depth += 1,
+            ']' | ')' | '}' if !quoted && depth > 0 => depth -= 1,
+            ';' if !quoted && depth == 0 => {
+                statements.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    statements.push(&line[start..]);
+    statements
+}
+
+/// The concept table from a Mermaid flowchart.
+pub fn read(src: &str, opts: &InputOpts) -> anyhow::Result<(StringRecord, Vec<StringRecord>)> {
+    let mut g = Graph::new(opts.edges_from_prerequisites);
+    // the open subgraphs' titles, and the nodes in each
+    let mut subgraphs: Vec<(String, Vec<String>)> = Vec::new();
+    let mut statements = src
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("%%"))
+        .flat_map(statements)
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+
+    match statements.next() {
+        Some(header) if header.starts_with("flowchart") || header.starts_with("graph") => (),
+        _ => {
+            anyhow::bail!("Expected a Mermaid flowchart, starting with \"flowchart\" or \"graph\".")
+        }
+    }
+    for s in statements {
+        let keyword = s.split_whitespace().next().unwrap_or("");
+        if IGNORED.contains(&keyword) {
+            continue;
+        }
+        if keyword == "subgraph" {
+            subgraphs.push((subgraph_title(&s["subgraph".len()..]), Vec::new()));
+            continue;
+        }
+        if s == "end" {
+            if let Some((title, members)) = subgraphs.pop() {
+                for m in members {
+                    g.category(&m, &title);
+                }
+            }
+            continue;
+        }
+
+        let mut line = Line { rest: s };
+        let mut from = line.nodes();
+        if from.is_empty() {
+            anyhow::bail!("Can't read \"{}\" in the Mermaid flowchart.", s);
+        }
+        let mut all: Vec<&str> = from.iter().map(|(id, _)| *id).collect();
+        for (id, text) in &from {
+            g.node(id);
+            if let Some(t) = text {
+                g.label(id, t);
+            }
+        }
+        while line.link() {
+            let to = line.nodes();
+            for (id, text) in &to {
+                g.node(id);
+                if let Some(t) = text {
+                    g.label(id, t);
+                }
+                for (f, _) in &from {
+                    g.edge(f, id);
+                }
+            }
+            all.extend(to.iter().map(|(id, _)| *id));
+            from = to;
+        }
+        if let Some((_, members)) = subgraphs.last_mut() {
+            members.extend(all.iter().map(|id| id.to_string()));
+        }
+    }
+
+    Ok(g.table(opts.dep_separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser as _;
+
+    fn table(src: &str) -> Vec<Vec<String>> {
+        let cli = Cli::parse_from(["concept_map", "--from", "mermaid"]);
+        let (_, rows) = read(src, &cli.input).unwrap();
+        rows.iter()
+            .map(|r| r.iter().map(String::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn semicolons_in_node_text_dont_end_statements() {
+        assert_eq!(
+            statements("A[\"malloc; free\"] --> B; B --> C(a;b)"),
+            ["A[\"malloc; free\"] --> B", " B --> C(a;b)"]
+        );
+        assert_eq!(
+            table("graph TD; A[\"malloc; free\"] --> B[Pointers]"),
+            [["malloc; free", "", "Pointers"], ["Pointers", "", ""]]
+        );
+    }
+
+    #[test]
+    fn subgraphs_links_and_comments() {
+        let rows = table(
+            "flowchart LR
+  %% the C part; of the course
+  subgraph c [C]
+    B(Pointers) -- needs --> A & D
+  end
+  C{{Malloc}} -->|uses| B
+  class A important
+",
+        );
+
+        assert_eq!(
+            rows,
+            [
+                ["Pointers", "C", "A;D"],
+                ["A", "C", ""],
+                ["D", "C", ""],
+                ["Malloc", "", "Pointers"],
+            ]
+        );
+    }
+
+    #[test]
+    fn other_diagrams_are_errors() {
+        let cli = Cli::parse_from(["concept_map", "--from", "mermaid"]);
+        assert!(read("sequenceDiagram\n  A->>B: hi\n", &cli.input).is_err());
+    }
+}
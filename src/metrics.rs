@@ -0,0 +1,267 @@
+// Curriculum metrics over time. Each snapshot appends the map's size,
+// weights, depth, and cycles to a json file, along with when it was
+// taken and at which commit, and the trend report shows how the course
+// has grown from snapshot to snapshot.
+
+use crate::ConceptMap;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    /// The json file of snapshots to append to
+    #[arg(long, value_name = "FILE")]
+    pub db: PathBuf,
+    /// What to call the snapshot in the trend, e.g., the semester
+    #[arg(long)]
+    pub label: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TrendArgs {
+    /// The json file of snapshots
+    #[arg(long, value_name = "FILE")]
+    pub db: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    timestamp: u64, // seconds since the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    concepts: usize,
+    #[serde(rename = "total weights")]
+    total_weights: [f64; 3],
+    #[serde(rename = "max depth")]
+    max_depth: usize,
+    cycles: usize,
+}
+
+// The number of dependency cycles: the strongly connected components
+// with more than one concept, or with a concept depending on itself.
+fn cycles(m: &ConceptMap) -> usize {
+    struct Tarjan<'a> {
+        m: &'a ConceptMap,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        cycles: usize,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+
+            for d in &self.m.concepts[v].dependencies {
+                let w = self.m.dependency_to_concept(d).offset;
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(i) if self.on_stack[w] => self.low[v] = self.low[v].min(i),
+                    Some(_) => (),
+                }
+            }
+            if Some(self.low[v]) == self.index[v] {
+                let mut size = 0;
+                loop {
+                    // unwrap: v is on the stack
+                    let w = self.stack.pop().unwrap();
+                    self.on_stack[w] = false;
+                    size += 1;
+                    if w == v {
+                        break;
+                    }
+                }
+                let c = &self.m.concepts[v];
+                if size > 1 || c.dependencies.contains(&c.concept) {
+                    self.cycles += 1;
+                }
+            }
+        }
+    }
+
+    let n = m.concepts.len();
+    let mut t = Tarjan {
+        m,
+        index: vec![None; n],
+        low: vec![0; n],
+        stack: Vec::new(),
+        on_stack: vec![false; n],
+        next: 0,
+        cycles: 0,
+    };
+    for v in 0..n {
+        if t.index[v].is_none() {
+            t.visit(v);
+        }
+    }
+    t.cycles
+}
+
+fn head_commit() -> Option<String> {
+    let out = process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn load(db: &Path) -> anyhow::Result<Vec<Snapshot>> {
+    match fs::read(db) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Append the map's metrics to the snapshots in `args.db`.
+pub fn snapshot(m: &ConceptMap, args: &SnapshotArgs) -> anyhow::Result<()> {
+    let mut snapshots = load(&args.db)?;
+
+    snapshots.push(Snapshot {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        commit: head_commit(),
+        label: args.label.clone(),
+        concepts: m.concepts.len(),
+        total_weights: m.total_weights,
+        max_depth: m.depths().into_iter().max().unwrap_or(0),
+        cycles: cycles(m),
+    });
+    fs::write(&args.db, serde_json::to_string_pretty(&snapshots)? + "\n")?;
+    Ok(())
+}
+
+// The timestamp's UTC date, as YYYY-MM-DD (from Howard Hinnant's
+// days-to-civil algorithm).
+fn date(timestamp: u64) -> String {
+    let z = (timestamp / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The snapshots in `args.db`, oldest first, and the change from the
+/// first to the last.
+pub fn trend(args: &TrendArgs) -> anyhow::Result<String> {
+    let snapshots = load(&args.db)?;
+    let mut out = String::new();
+
+    if snapshots.is_empty() {
+        out.push_str(&format!("No snapshots in {}.\n", args.db.display()));
+        return Ok(out);
+    }
+    out.push_str(&format!(
+        "{:<10} {:<16} {:>9} {:>8} {:>8} {:>8} {:>6} {:>7}\n",
+        "date", "snapshot", "concepts", "lecture", "lab", "HW", "depth", "cycles"
+    ));
+    for s in &snapshots {
+        let name = s
+            .label
+            .as_deref()
+            .or(s.commit.as_deref())
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{:<10} {:<16} {:>9} {:>8.2} {:>8.2} {:>8.2} {:>6} {:>7}\n",
+            date(s.timestamp),
+            name,
+            s.concepts,
+            s.total_weights[0],
+            s.total_weights[1],
+            s.total_weights[2],
+            s.max_depth,
+            s.cycles
+        ));
+    }
+    if let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) {
+        out.push_str(&format!("\nSince {}: {:+} concepts, lecture {:+.2}, lab {:+.2}, HW {:+.2} weeks, depth {:+}, cycles {:+}.\n",
+            date(first.timestamp),
+            last.concepts as i64 - first.concepts as i64,
+            last.total_weights[0] - first.total_weights[0],
+            last.total_weights[1] - first.total_weights[1],
+            last.total_weights[2] - first.total_weights[2],
+            last.max_depth as i64 - first.max_depth as i64,
+            last.cycles as i64 - first.cycles as i64
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_map, Cli};
+    use clap::Parser as _;
+
+    #[test]
+    fn dates_are_utc_days() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(1_791_935_999), "2026-10-13");
+    }
+
+    #[test]
+    fn cycles_are_counted_once_each() {
+        let csv = "concept,dependencies\na,b\nb,a\nself,self\nc,a\n";
+        let (m, _, _) = read_map(&Cli::parse_from(["concept_map"]), csv.as_bytes()).unwrap();
+
+        assert_eq!(cycles(&m), 2);
+    }
+
+    #[test]
+    fn trends_compare_the_first_and_last_snapshots() {
+        let db =
+            std::env::temp_dir().join(format!("concept_map_trend_{}.json", std::process::id()));
+        let snapshot = |timestamp, label: &str, concepts, lecture| Snapshot {
+            timestamp,
+            commit: Some(String::from("abc1234")),
+            label: Some(label.to_string()).filter(|l| !l.is_empty()),
+            concepts,
+            total_weights: [lecture, 0.0, 1.0],
+            max_depth: 3,
+            cycles: 0,
+        };
+        let snapshots = [snapshot(0, "fall", 10, 8.0), snapshot(86400, "", 12, 9.5)];
+        fs::write(&db, serde_json::to_string(&snapshots).unwrap()).unwrap();
+        let report = trend(&TrendArgs { db: db.clone() }).unwrap();
+        fs::remove_file(&db).unwrap();
+
+        assert_eq!(
+            report.lines().collect::<Vec<_>>(),
+            [
+                "date       snapshot          concepts  lecture      lab       HW  depth  cycles",
+                "1970-01-01 fall                    10     8.00     0.00     1.00      3       0",
+                "1970-01-02 abc1234                 12     9.50     0.00     1.00      3       0",
+                "",
+                "Since 1970-01-01: +2 concepts, lecture +1.50, lab +0.00, HW +0.00 weeks, depth +0, cycles +0.",
+            ]
+        );
+        let missing = trend(&TrendArgs { db: db.clone() }).unwrap();
+        assert_eq!(missing, format!("No snapshots in {}.\n", db.display()));
+    }
+}
@@ -0,0 +1,315 @@
+// Milestones. For exams, from the csv's exam rows and the command
+// line: is everything an exam covers taught before it, and which chain
+// of prerequisites is the last to be taught (and so, what to move to
+// give the exam room). For projects, which can build on earlier
+// project milestones: the arc of milestones, with what each adds.
+
+use crate::schedule::Schedule;
+use crate::{plural, Concept, ConceptMap, ConceptRecord, Kind};
+use std::collections::HashSet;
+
+// An exam given with `--exam`.
+#[derive(Clone)]
+pub struct ExamSpec {
+    name: Option<String>,
+    week: u64,
+    concepts: String,
+}
+
+/// `[name=NAME:]week=WEEK:concepts=A;B;...`, where the concepts go to
+/// the end, so their names can have colons.
+pub fn parse_exam(s: &str) -> Result<ExamSpec, String> {
+    let (mut name, mut week, mut concepts) = (None, None, None);
+    let mut rest = s.trim();
+
+    while !rest.is_empty() {
+        if let Some(cs) = rest.strip_prefix("concepts=") {
+            concepts = Some(cs.to_string());
+            break;
+        }
+        let (field, tail) = rest.split_once(':').unwrap_or((rest, ""));
+        match field.split_once('=') {
+            Some(("name", n)) => name = Some(n.trim().to_string()),
+            Some(("week", w)) => {
+                week = Some(
+                    w.trim()
+                        .parse()
+                        .map_err(|_| format!("expected a week number, found \"{}\"", w))?,
+                )
+            }
+            _ => return Err(format!("unexpected \"{}\" in the exam", field)),
+        }
+        rest = tail;
+    }
+    match (week, concepts) {
+        (Some(week), Some(concepts)) => Ok(ExamSpec {
+            name,
+            week,
+            concepts,
+        }),
+        _ => Err(String::from(
+            "expected [name=NAME:]week=WEEK:concepts=CONCEPTS",
+        )),
+    }
+}
+
+impl ExamSpec {
+    pub fn record(&self) -> ConceptRecord {
+        ConceptRecord {
+            concept: self
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Exam in week {}", self.week)),
+            dependencies: self.concepts.clone(),
+            week: Some(self.week),
+            kind: Some(String::from("exam")),
+            ..ConceptRecord::default()
+        }
+    }
+}
+
+// Everything `exam` transitively depends on that the course teaches,
+// by offset.
+fn covered(m: &ConceptMap, exam: &Concept) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    let mut covered = Vec::new();
+    let mut pending = vec![exam];
+
+    while let Some(c) = pending.pop() {
+        for d in &c.dependencies {
+            let d = m.dependency_to_concept(d);
+            if seen.insert(d.offset) {
+                if d.is_taught() {
+                    covered.push(d.offset);
+                }
+                pending.push(d);
+            }
+        }
+    }
+    covered
+}
+
+// From the exam, repeatedly the dependency taught last (by week, and
+// then by the end of its earliest lecture).
+fn latest_chain<'a>(m: &'a ConceptMap, s: &Schedule, exam: &'a Concept) -> Vec<&'a Concept> {
+    let mut chain = Vec::new();
+    let mut c = exam;
+
+    let finish = |c: &Concept| {
+        (
+            s.week(c),
+            c.modes[0].range.earliest_start + c.modes[0].weight,
+        )
+    };
+    while let Some(last) = c
+        .dependencies
+        .iter()
+        .map(|d| m.dependency_to_concept(d))
+        .max_by(|a, b| {
+            let ((aw, a), (bw, b)) = (finish(a), finish(b));
+            aw.cmp(&bw).then(a.total_cmp(&b))
+        })
+    {
+        // cycles are reported elsewhere
+        if chain.iter().any(|p: &&Concept| p.offset == last.offset) {
+            break;
+        }
+        chain.push(last);
+        c = last;
+    }
+    chain
+}
+
+/// Requires the map to be solved.
+pub fn exams(m: &ConceptMap) -> String {
+    let s = Schedule::new(m);
+    let mut out = String::new();
+    let exams: Vec<_> = m.concepts.iter().filter(|c| c.kind == Kind::Exam).collect();
+
+    if exams.is_empty() {
+        out.push_str("No exams; add exam rows, or --exam.\n");
+    }
+    for e in exams {
+        let covered = covered(m, e);
+        let late: Vec<String> = covered
+            .iter()
+            .map(|&o| &m.concepts[o])
+            .filter(|c| s.week(c) >= s.week(e))
+            .map(|c| format!("{} (week {})", c.concept, s.week(c)))
+            .collect();
+
+        if late.is_empty() {
+            out.push_str(&format!(
+                "{}, week {}: {} taught before it.\n",
+                e.concept,
+                s.week(e),
+                match covered.len() {
+                    1 => String::from("its covered concept is"),
+                    n => format!("all {} covered concepts are", n),
+                }
+            ));
+        } else {
+            out.push_str(&format!(
+                "{}, week {}: {} of {} {} taught before it: {}\n",
+                e.concept,
+                s.week(e),
+                late.len(),
+                plural(covered.len(), "covered concept", "covered concepts"),
+                if late.len() == 1 { "isn't" } else { "aren't" },
+                late.join(", ")
+            ));
+        }
+        let chain: Vec<String> = latest_chain(m, &s, e)
+            .iter()
+            .map(|c| format!("{} (week {})", c.concept, s.week(c)))
+            .collect();
+        if !chain.is_empty() {
+            out.push_str(&format!("  Taught last: {}\n", chain.join(" <- ")));
+        }
+    }
+
+    out
+}
+
+fn with_week(s: &Schedule, c: &Concept) -> String {
+    format!("{} (week {})", c.concept, s.week(c))
+}
+
+/// Requires the map to be solved.
+pub fn projects(m: &ConceptMap) -> String {
+    let s = Schedule::new(m);
+    let mut out = String::new();
+    let mut projects: Vec<_> = m
+        .concepts
+        .iter()
+        .filter(|c| c.kind == Kind::Project)
+        .collect();
+    projects.sort_by_key(|p| s.week(p));
+
+    if projects.is_empty() {
+        out.push_str("No project milestones; add rows of type project.\n");
+    }
+    for p in projects {
+        let deps: Vec<_> = p
+            .dependencies
+            .iter()
+            .map(|d| m.dependency_to_concept(d))
+            .collect();
+        let earlier: Vec<_> = deps.iter().filter(|d| d.kind.is_milestone()).collect();
+        // what the earlier milestones haven't already required
+        let before: HashSet<usize> = earlier
+            .iter()
+            .flat_map(|e| covered(m, e))
+            .chain(earlier.iter().map(|e| e.offset))
+            .collect();
+        let adds: Vec<String> = covered(m, p)
+            .into_iter()
+            .filter(|o| !before.contains(o) && !m.concepts[*o].kind.is_milestone())
+            .map(|o| m.concepts[o].concept.clone())
+            .collect();
+        let late: Vec<String> = covered(m, p)
+            .into_iter()
+            .map(|o| &m.concepts[o])
+            .filter(|c| s.week(c) >= s.week(p))
+            .map(|c| with_week(&s, c))
+            .collect();
+
+        out.push_str(&format!("week {:>3}  {}\n", s.week(p), p.concept));
+        if !earlier.is_empty() {
+            let earlier: Vec<String> = earlier.iter().map(|e| with_week(&s, e)).collect();
+            out.push_str(&format!("          builds on {}\n", earlier.join(", ")));
+        }
+        if !adds.is_empty() {
+            out.push_str(&format!("          requires {}\n", adds.join(", ")));
+        }
+        if !late.is_empty() {
+            out.push_str(&format!(
+                "          outpaces the lectures: {} not taught before it\n",
+                late.join(", ")
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exam_specs_put_the_concepts_last() {
+        let e = parse_exam("name=Midterm:week=5:concepts=C: the language;pointers").unwrap();
+        let r = e.record();
+
+        assert_eq!(
+            (r.concept.as_str(), r.dependencies.as_str(), r.week),
+            ("Midterm", "C: the language;pointers", Some(5))
+        );
+        assert_eq!(
+            parse_exam("week=2:concepts=a").unwrap().record().concept,
+            "Exam in week 2"
+        );
+        assert_eq!(
+            parse_exam("week=two:concepts=a").err().as_deref(),
+            Some("expected a week number, found \"two\"")
+        );
+        assert_eq!(
+            parse_exam("day=2:concepts=a").err().as_deref(),
+            Some("unexpected \"day=2\" in the exam")
+        );
+        assert!(parse_exam("week=2").is_err());
+    }
+
+    #[test]
+    fn exams_list_what_isnt_taught_before_them() {
+        let m = crate::test_map(
+            "concept,dependencies,week,lecture weight,type
+a,,,1,
+b,a,,1,
+c,b,,1,
+quiz,a,,0,exam
+midterm,c,2,0,exam
+",
+        );
+
+        assert_eq!(
+            exams(&m).lines().collect::<Vec<_>>(),
+            [
+                "quiz, week 2: its covered concept is taught before it.",
+                "  Taught last: a (week 1)",
+                "midterm, week 2: 2 of 3 covered concepts aren't taught before it: c (week 3), b (week 2)",
+                "  Taught last: c (week 3) <- b (week 2) <- a (week 1)",
+            ]
+        );
+        assert_eq!(
+            exams(&crate::test_map("concept,dependencies\na,\n")),
+            "No exams; add exam rows, or --exam.\n"
+        );
+    }
+
+    #[test]
+    fn projects_build_on_earlier_milestones() {
+        let m = crate::test_map(
+            "concept,dependencies,week,lecture weight,type
+a,,,1,
+b,a,,1,
+c,b,,1,
+shell,a,,0,project
+allocator,shell;b;c,3,0,project
+",
+        );
+
+        assert_eq!(
+            projects(&m).lines().collect::<Vec<_>>(),
+            [
+                "week   2  shell",
+                "          requires a",
+                "week   3  allocator",
+                "          builds on shell (week 2)",
+                "          requires b, c",
+                "          outpaces the lectures: c (week 3) not taught before it",
+            ]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+// Names typed or pasted from different places spell the same thing
+// with different code points: composed or decomposed accents, curly
+// or straight quotes, and any of the dashes. Normalizing them (to NFC,
+// and to ASCII quotes and hyphens) makes them look up the same.
+
+use crate::input;
+use unicode_normalization::UnicodeNormalization;
+
+pub fn name(s: &str) -> String {
+    s.nfc()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => '\'',
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' => '"',
+            '\u{2010}'..='\u{2015}' | '\u{2212}' | '\u{fe58}' | '\u{fe63}' | '\u{ff0d}' => '-',
+            c => c,
+        })
+        .collect()
+}
+
+/// What names match as across courses' maps, which also differ in case
+/// and surrounding space.
+pub fn key(s: &str) -> String {
+    name(s.trim()).to_lowercase()
+}
+
+/// The list's items normalized, after splitting it: a straight double
+/// quote is the list's quoting, so a curly one in a name, e.g., “Big” O,
+/// has to become a quoted item's doubled quote. Unchanged, unless
+/// normalizing changes an item.
+pub fn list(cell: &str, sep: char) -> String {
+    let items = input::split_list(cell, sep);
+    let normalized: Vec<String> = items.iter().map(|i| name(i)).collect();
+
+    if normalized == items {
+        cell.to_string()
+    } else {
+        input::join_list(&normalized, sep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curly_quotes_in_lists_stay_in_names() {
+        let deps = list("\u{201c}Big\u{201d} O,sorting", ',');
+
+        assert_eq!(input::split_list(&deps, ','), ["\"Big\" O", "sorting"]);
+        assert_eq!(list("a, \"b,c\"", ','), "a, \"b,c\"");
+    }
+
+    #[test]
+    fn keys_ignore_case_and_spelling() {
+        assert_eq!(key(" Pointers"), key("pointers"));
+        assert_eq!(key("Caf\u{e9}\u{2019}s"), key("cafe\u{301}'s"));
+        assert_ne!(key("pointer"), key("pointers"));
+    }
+}
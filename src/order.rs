@@ -0,0 +1,214 @@
+// Teaching orders. Any topological order of the concepts respects
+// their dependencies, but some are easier to teach from than others:
+// ones that stay in a category for a while, rather than switching back
+// and forth, or that teach each concept soon after what it builds on.
+// Finding the best is expensive, so the orders are chosen greedily, one
+// concept at a time.
+
+use crate::{category_label, plural, ConceptMap};
+use clap::{Args, ValueEnum};
+use std::cmp::Reverse;
+
+#[derive(Args)]
+pub struct OrderArgs {
+    /// Rather than following the csv's order (where the dependencies
+    /// allow), choose the order to do well on this measure
+    #[arg(long, value_enum)]
+    pub optimize: Option<Goal>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Goal {
+    /// Fewest switches between categories
+    Switches,
+    /// Each concept as soon as possible after its dependencies
+    Distance,
+}
+
+// The offsets of the concepts, in an order respecting their
+// dependencies. Requires them to be acyclic.
+fn order(m: &ConceptMap, goal: Option<Goal>) -> Vec<usize> {
+    let dependents = m.dependents();
+    let mut waiting_on: Vec<usize> = m.concepts.iter().map(|c| c.dependencies.len()).collect();
+    let mut available: Vec<usize> = (0..m.concepts.len())
+        .filter(|&o| waiting_on[o] == 0)
+        .collect();
+    let mut position = vec![None; m.concepts.len()];
+    let mut order = Vec::with_capacity(m.concepts.len());
+
+    while !available.is_empty() {
+        let last = order.last().map(|&o: &usize| &m.concepts[o].category);
+        // unwrap: available isn't empty
+        let &next = match goal {
+            None => available.iter().min(),
+            Some(Goal::Switches) => available
+                .iter()
+                .min_by_key(|&&o| (Some(&m.concepts[o].category) != last, o)),
+            // the concept whose dependencies were taught most recently
+            Some(Goal::Distance) => available.iter().max_by_key(|&&o| {
+                let latest = m.concepts[o]
+                    .dependencies
+                    .iter()
+                    .map(|d| position[m.dependency_to_concept(d).offset])
+                    .max()
+                    .flatten();
+                (latest, Reverse(o))
+            }),
+        }
+        .unwrap();
+
+        available.retain(|&o| o != next);
+        position[next] = Some(order.len());
+        order.push(next);
+        for &d in &dependents[next] {
+            waiting_on[d] -= 1;
+            if waiting_on[d] == 0 {
+                available.push(d);
+            }
+        }
+    }
+    order
+}
+
+// The number of category switches in the order, and the average
+// distance (in concepts) from each concept back to its dependencies.
+fn score(m: &ConceptMap, order: &[usize]) -> (usize, f64) {
+    let mut position = vec![0; m.concepts.len()];
+    for (i, &o) in order.iter().enumerate() {
+        position[o] = i;
+    }
+
+    let switches = order
+        .windows(2)
+        .filter(|w| m.concepts[w[0]].category != m.concepts[w[1]].category)
+        .count();
+    let distances: Vec<usize> = m
+        .concepts
+        .iter()
+        .flat_map(|c| {
+            c.dependencies
+                .iter()
+                .map(|d| position[c.offset] - position[m.dependency_to_concept(d).offset])
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let distance = if distances.is_empty() {
+        0.0
+    } else {
+        distances.iter().sum::<usize>() as f64 / distances.len() as f64
+    };
+
+    (switches, distance)
+}
+
+/// The recommended teaching order, numbered, and its score, compared
+/// to the csv's order's when optimizing. Requires the dependencies to
+/// be acyclic.
+pub fn report(m: &ConceptMap, args: &OrderArgs) -> String {
+    let chosen = order(m, args.optimize);
+    let (switches, distance) = score(m, &chosen);
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "Teaching order{}:\n",
+        match args.optimize {
+            None => "",
+            Some(Goal::Switches) => " (fewest category switches)",
+            Some(Goal::Distance) => " (closest to the dependencies)",
+        }
+    ));
+    for (i, &o) in chosen.iter().enumerate() {
+        let c = &m.concepts[o];
+        out.push_str(&format!(
+            "{:>4}. {} ({})\n",
+            i + 1,
+            c.concept,
+            category_label(&c.category)
+        ));
+    }
+
+    out.push_str(&format!(
+        "\nScore: {}, and {:.2} concepts on average from each concept back to its dependencies.\n",
+        plural(switches, "category switch", "category switches"),
+        distance
+    ));
+    if args.optimize.is_some() {
+        let (switches, distance) = score(m, &order(m, None));
+        out.push_str(&format!(
+            "In the csv's order: {}, and {:.2} concepts on average.\n",
+            plural(switches, "category switch", "category switches"),
+            distance
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Teaching both categories' first concepts first switches back and
+    // forth, and leaves the dependents far from their dependencies.
+    const INTERLEAVED: &str = "concept,dependencies,category,lecture weight
+a,,x,1
+b,,y,1
+c,a,x,1
+d,b,y,1
+";
+
+    fn names(m: &ConceptMap, goal: Option<Goal>) -> Vec<&str> {
+        order(m, goal)
+            .into_iter()
+            .map(|o| m.concepts[o].concept.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn orders_follow_the_csv_where_the_dependencies_allow() {
+        let m = crate::test_map("concept,dependencies\nb,a\na,\nc,\n");
+
+        assert_eq!(names(&m, None), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn optimized_orders_switch_less_and_stay_closer() {
+        let m = crate::test_map(INTERLEAVED);
+
+        assert_eq!(names(&m, None), ["a", "b", "c", "d"]);
+        assert_eq!(score(&m, &order(&m, None)), (3, 2.0));
+        assert_eq!(names(&m, Some(Goal::Switches)), ["a", "c", "b", "d"]);
+        assert_eq!(score(&m, &order(&m, Some(Goal::Switches))), (1, 1.0));
+
+        // staying in the category, or teaching the dependent next
+        let m = crate::test_map("concept,dependencies,category\na,,x\nb,,x\nc,a,y\n");
+        assert_eq!(names(&m, Some(Goal::Switches)), ["a", "b", "c"]);
+        assert_eq!(names(&m, Some(Goal::Distance)), ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn reports_compare_to_the_csvs_order() {
+        let m = crate::test_map(INTERLEAVED);
+        let report = report(
+            &m,
+            &OrderArgs {
+                optimize: Some(Goal::Switches),
+            },
+        );
+
+        assert!(
+            report.starts_with("Teaching order (fewest category switches):\n   1. a (x)\n"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("\nScore: 1 category switch, and 1.00 concepts"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("In the csv's order: 3 category switches, and 2.00 concepts"),
+            "{}",
+            report
+        );
+    }
+}
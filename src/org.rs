@@ -0,0 +1,118 @@
+// The schedule as an Emacs Org file, for instructors who plan in Org:
+// a heading per week, and under it a TODO per concept, with its
+// category and weights as properties.
+
+use crate::schedule::Schedule;
+use crate::{category_label, ConceptMap};
+
+// Org tags can't hold spaces or most punctuation.
+fn tag(category: &str) -> String {
+    category
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Requires the map to be solved.
+pub fn render(m: &ConceptMap) -> String {
+    let schedule = Schedule::new(m);
+    let mut out = String::new();
+
+    out.push_str("#+TITLE: Concept map schedule\n#+TODO: TODO | DONE\n");
+    for week in 1..=schedule.nweeks() {
+        let concepts: Vec<_> = m
+            .concepts
+            .iter()
+            .filter(|c| c.is_taught() && schedule.week(c) == week)
+            .collect();
+        if concepts.is_empty() {
+            continue;
+        }
+        let lecture: f64 = concepts.iter().map(|c| c.modes[0].weight).sum();
+
+        out.push_str(&format!(
+            "* Week {}\n:PROPERTIES:\n:LECTURE_WEIGHT: {:.2}\n:END:\n",
+            week,
+            m.shown_weight(0, lecture)
+        ));
+        for c in concepts {
+            let tags = if c.category.is_empty() {
+                String::new()
+            } else {
+                format!(" :{}:", tag(&c.category))
+            };
+            out.push_str(&format!("** TODO {}{}\n", c.concept, tags));
+            out.push_str(":PROPERTIES:\n");
+            out.push_str(&format!(":CATEGORY: {}\n", category_label(&c.category)));
+            for (mode, name) in ["LECTURE", "LAB", "HW"].iter().enumerate() {
+                let weight = m.shown_weight(mode, c.modes[mode].weight);
+                out.push_str(&format!(":{}_WEIGHT: {:.2}\n", name, weight));
+            }
+            out.push_str(&format!(
+                ":EARLIEST_START: {:.2}\n",
+                c.modes[0].range.earliest_start
+            ));
+            out.push_str(&format!(":SLACK: {:.2}\n", c.slack()));
+            if !c.dependencies.is_empty() {
+                out.push_str(&format!(":DEPENDS_ON: {}\n", c.dependencies.join("; ")));
+            }
+            out.push_str(":END:\n");
+            if !c.description.is_empty() {
+                out.push_str(&format!("{}\n", c.description));
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weeks_are_headings_of_todos() {
+        let m = crate::test_map(
+            "concept,dependencies,category,description,lecture weight,status
+a,,data structures,Lists and trees,1,
+b,a,,,0.5,
+old,,x,,1,retired
+",
+        );
+
+        assert_eq!(
+            render(&m),
+            "#+TITLE: Concept map schedule
+#+TODO: TODO | DONE
+* Week 1
+:PROPERTIES:
+:LECTURE_WEIGHT: 1.00
+:END:
+** TODO a :data_structures:
+:PROPERTIES:
+:CATEGORY: data structures
+:LECTURE_WEIGHT: 1.00
+:LAB_WEIGHT: 0.00
+:HW_WEIGHT: 0.00
+:EARLIEST_START: 0.00
+:SLACK: 0.00
+:END:
+Lists and trees
+* Week 2
+:PROPERTIES:
+:LECTURE_WEIGHT: 0.50
+:END:
+** TODO b
+:PROPERTIES:
+:CATEGORY: (uncategorized)
+:LECTURE_WEIGHT: 0.50
+:LAB_WEIGHT: 0.00
+:HW_WEIGHT: 0.00
+:EARLIEST_START: 1.00
+:SLACK: 0.00
+:DEPENDS_ON: a
+:END:
+"
+        );
+    }
+}
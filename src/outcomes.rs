@@ -0,0 +1,170 @@
+// Mapping the concepts to the course's learning outcomes, from the
+// "outcomes" column (outcome ids separated by semicolons), for
+// accreditation reviews: each outcome's concepts and weights, the
+// concepts that don't map to any, and the outcome-by-concept coverage
+// matrix as csv.
+
+use crate::{category_label, plural, Concept, ConceptMap, Status};
+use clap::Args;
+use itertools::Itertools;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct OutcomesArgs {
+    /// Also write the coverage matrix as csv: a row per outcome, a
+    /// column per concept, and each concept's weight in the outcomes it
+    /// maps to, in weeks (lecture + lab + HW)
+    #[arg(long, value_name = "FILE")]
+    pub matrix_csv: Option<PathBuf>,
+}
+
+// The outcomes, in order of first appearance.
+fn outcomes(m: &ConceptMap) -> Vec<&String> {
+    m.concepts
+        .iter()
+        .flat_map(|c| &c.outcomes)
+        .unique()
+        .collect()
+}
+
+fn weight(c: &Concept) -> f64 {
+    c.modes
+        .iter()
+        .map(|mode| mode.weight)
+        .fold(0.0, |p, n| p + n)
+}
+
+// The retired concepts don't count toward any outcome.
+fn covering<'a>(m: &'a ConceptMap, outcome: &'a str) -> impl Iterator<Item = &'a Concept> {
+    m.concepts
+        .iter()
+        .filter(move |c| c.status != Status::Retired && c.outcomes.iter().any(|o| o == outcome))
+}
+
+pub fn report(m: &ConceptMap) -> String {
+    let mut out = String::new();
+    let outcomes = outcomes(m);
+
+    if outcomes.is_empty() {
+        out.push_str("No concepts map to learning outcomes.\n");
+        return out;
+    }
+    out.push_str("Learning outcomes (lecture / lab / HW):\n");
+    for o in outcomes {
+        let concepts: Vec<&Concept> = covering(m, o).collect();
+        let mut ws = [0.0; 3];
+        for c in &concepts {
+            for (w, mode) in ws.iter_mut().zip(&c.modes) {
+                *w += mode.weight;
+            }
+        }
+        out.push_str(&format!(
+            "- {}: {}, {} / {} / {}\n",
+            o,
+            plural(concepts.len(), "concept", "concepts"),
+            m.weight_label(0, ws[0]),
+            m.weight_label(1, ws[1]),
+            m.weight_label(2, ws[2])
+        ));
+        for c in concepts {
+            out.push_str(&format!(
+                "  - \"{}\" [{}]\n",
+                c.concept,
+                category_label(&c.category)
+            ));
+        }
+    }
+
+    let unmapped: Vec<String> = m
+        .concepts
+        .iter()
+        .filter(|c| c.is_taught() && !c.kind.is_milestone() && c.outcomes.is_empty())
+        .map(|c| format!("\"{}\"", c.concept))
+        .collect();
+    if !unmapped.is_empty() {
+        out.push_str(&format!(
+            "\n{} without outcomes: {}\n",
+            plural(unmapped.len(), "taught concept", "taught concepts"),
+            unmapped.join(", ")
+        ));
+    }
+    out
+}
+
+/// The coverage matrix, with a row per outcome, and a column per
+/// concept mapping to any.
+pub fn matrix_csv<W: io::Write>(m: &ConceptMap, out: W) -> anyhow::Result<()> {
+    let mut w = csv::Writer::from_writer(out);
+    let concepts: Vec<&Concept> = m
+        .concepts
+        .iter()
+        .filter(|c| c.status != Status::Retired && !c.outcomes.is_empty())
+        .collect();
+
+    w.write_record(
+        std::iter::once("outcome")
+            .chain(concepts.iter().map(|c| c.concept.as_str()))
+            .chain(std::iter::once("total")),
+    )?;
+    for o in outcomes(m) {
+        let mut row = vec![o.clone()];
+        let mut total = 0.0;
+        for c in &concepts {
+            if c.outcomes.contains(o) {
+                row.push(format!("{:.2}", weight(c)));
+                total += weight(c);
+            } else {
+                row.push(String::new());
+            }
+        }
+        row.push(format!("{:.2}", total));
+        w.write_record(&row)?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPED: &str = "concept,dependencies,outcomes,lecture weight,lab weight,status
+a,,LO1,1,0.5,
+b,a,LO1;LO2,0.5,,
+c,,,1,,
+old,,LO2,1,,retired
+";
+
+    #[test]
+    fn outcomes_list_their_concepts_and_weights() {
+        assert_eq!(
+            report(&crate::test_map(MAPPED)).lines().collect::<Vec<_>>(),
+            [
+                "Learning outcomes (lecture / lab / HW):",
+                "- LO1: 2 concepts, 1.50 weeks / 0.50 weeks / 0.00 weeks",
+                "  - \"a\" [(uncategorized)]",
+                "  - \"b\" [(uncategorized)]",
+                "- LO2: 1 concept, 0.50 weeks / 0.00 weeks / 0.00 weeks",
+                "  - \"b\" [(uncategorized)]",
+                "",
+                "1 taught concept without outcomes: \"c\"",
+            ]
+        );
+        assert_eq!(
+            report(&crate::test_map("concept,dependencies\na,\n")),
+            "No concepts map to learning outcomes.\n"
+        );
+    }
+
+    #[test]
+    fn the_matrix_has_a_row_per_outcome() {
+        let mut out = Vec::new();
+        matrix_csv(&crate::test_map(MAPPED), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "outcome,a,b,total\nLO1,1.50,0.50,2.00\nLO2,,0.50,0.50\n"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+// The overlap between two courses' maps: the concepts both cover,
+// matched by their normalized names and aliases, with their weights in
+// each, to find redundant coverage across the curriculum.
+
+use crate::{normalize, plural, Concept, ConceptMap};
+use clap::Args;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct OverlapArgs {
+    /// One course's csv file
+    pub a: PathBuf,
+    /// The other course's csv file
+    pub b: PathBuf,
+}
+
+// The lecture, lab, and HW weights, as `m`'s summaries show them.
+fn weights(m: &ConceptMap, ws: [f64; 3]) -> String {
+    format!(
+        "{} / {} / {}",
+        m.weight_label(0, ws[0]),
+        m.weight_label(1, ws[1]),
+        m.weight_label(2, ws[2])
+    )
+}
+
+fn concept_weights(m: &ConceptMap, c: &Concept) -> String {
+    weights(m, [c.modes[0].weight, c.modes[1].weight, c.modes[2].weight])
+}
+
+/// The concepts in both `a` and `b`, in `a`'s order, as a list.
+pub fn report(a: &ConceptMap, b: &ConceptMap, args: &OverlapArgs) -> String {
+    let (a_name, b_name) = (args.a.display(), args.b.display());
+    let mut b_names = HashMap::new();
+    for c in &b.concepts {
+        for n in c.names() {
+            b_names.entry(normalize::key(n)).or_insert(c);
+        }
+    }
+    let both: Vec<(&Concept, &Concept)> = a
+        .concepts
+        .iter()
+        .filter(|c| !c.kind.is_milestone())
+        .filter_map(|c| {
+            c.names()
+                .find_map(|n| b_names.get(&normalize::key(n)))
+                .map(|&d| (c, d))
+        })
+        .filter(|(_, d)| !d.kind.is_milestone())
+        .collect();
+    let mut out = String::new();
+
+    if both.is_empty() {
+        out.push_str(&format!("No concepts in both {} and {}.\n", a_name, b_name));
+        return out;
+    }
+    out.push_str(&format!(
+        "{} in both {} and {} (lecture / lab / HW):\n",
+        plural(both.len(), "concept", "concepts"),
+        a_name,
+        b_name
+    ));
+    let mut totals = [[0.0; 3]; 2];
+    for (c, d) in &both {
+        let name = if c.concept == d.concept {
+            format!("\"{}\"", c.concept)
+        } else {
+            format!("\"{}\" (\"{}\" in {})", c.concept, d.concept, b_name)
+        };
+        out.push_str(&format!(
+            "- {}: {} in {}, {} in {}\n",
+            name,
+            concept_weights(a, c),
+            a_name,
+            concept_weights(b, d),
+            b_name
+        ));
+        for (t, m) in totals.iter_mut().zip([c, d]) {
+            for (w, mode) in t.iter_mut().zip(&m.modes) {
+                *w += mode.weight;
+            }
+        }
+    }
+    out.push_str(&format!(
+        "\nOverlapping weight: {} in {} (of {}), and {} in {} (of {}).\n",
+        weights(a, totals[0]),
+        a_name,
+        weights(a, a.total_weights),
+        weights(b, totals[1]),
+        b_name,
+        weights(b, b.total_weights)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concepts_match_by_name_and_alias() {
+        let a = crate::test_map(
+            "concept,dependencies,aliases,lecture weight,lab weight,type
+Pointers,,,1,0.5,
+Virtual Memory,,,0.5,,
+Midterm,,,0,,exam
+Sets,,,1,,
+",
+        );
+        let b = crate::test_map(
+            "concept,dependencies,aliases,lecture weight,type
+pointers,,,0.5,
+paging,,virtual memory,1,
+Midterm,,,0,exam
+",
+        );
+        let args = OverlapArgs {
+            a: PathBuf::from("a.csv"),
+            b: PathBuf::from("b.csv"),
+        };
+
+        assert_eq!(
+            report(&a, &b, &args).lines().collect::<Vec<_>>(),
+            [
+                "2 concepts in both a.csv and b.csv (lecture / lab / HW):",
+                "- \"Pointers\" (\"pointers\" in b.csv): 1.00 weeks / 0.50 weeks / 0.00 weeks in a.csv, 0.50 weeks / 0.00 weeks / 0.00 weeks in b.csv",
+                "- \"Virtual Memory\" (\"paging\" in b.csv): 0.50 weeks / 0.00 weeks / 0.00 weeks in a.csv, 1.00 weeks / 0.00 weeks / 0.00 weeks in b.csv",
+                "",
+                "Overlapping weight: 1.50 weeks / 0.50 weeks / 0.00 weeks in a.csv (of 2.50 weeks / 0.50 weeks / 0.00 weeks), and 1.50 weeks / 0.00 weeks / 0.00 weeks in b.csv (of 1.50 weeks / 0.00 weeks / 0.00 weeks).",
+            ]
+        );
+        assert_eq!(
+            report(
+                &a,
+                &crate::test_map("concept,dependencies\nTrees,\n"),
+                &args
+            ),
+            "No concepts in both a.csv and b.csv.\n"
+        );
+    }
+}
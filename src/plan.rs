@@ -0,0 +1,243 @@
+// Planning the weeks of an over-constrained map, whose `earliest` and
+// `latest` windows and weekly capacity can't all be met. The plan is
+// greedy: week by week, it teaches the concepts whose dependencies have
+// been taught, those due soonest first, until the week's lecture weight
+// reaches the capacity. A concept due this week goes over the capacity
+// when that's less than a week over, rather than being a week late.
+// Waiting out an `earliest` week is always possible, so those windows
+// are kept, as are pinned weeks; the `latest` weeks, the capacity, and
+// the semester's length are relaxed, and the plan lists by how much.
+// Corequisites aren't kept in the same week.
+
+use crate::{plural, Concept, ConceptMap};
+use clap::Args;
+
+// Weights within rounding of each other are the same.
+const EPSILON: f64 = 1e-9;
+
+#[derive(Args)]
+pub struct PlanArgs {
+    /// The lecture weight that fits in a week, in weeks
+    #[arg(long, value_name = "WEEKS", default_value = "1")]
+    pub capacity: f64,
+}
+
+// The weeks of the concepts, by offset, with 0 for the untaught ones,
+// which are never waited for.
+fn weeks(m: &ConceptMap, capacity: f64) -> Vec<u64> {
+    let mut weeks: Vec<Option<u64>> = m
+        .concepts
+        .iter()
+        .map(|c| if c.is_taught() { None } else { Some(0) })
+        .collect();
+    let pinned: Vec<Option<u64>> = m
+        .concepts
+        .iter()
+        .map(|c| c.week.filter(|_| c.is_taught()).map(|w| w.max(1)))
+        .collect();
+    let due = |c: &Concept| c.latest.unwrap_or(u64::MAX);
+    let mut week = 1;
+
+    while weeks.iter().any(Option::is_none) {
+        for c in m.concepts.iter().filter(|c| pinned[c.offset] == Some(week)) {
+            weeks[c.offset] = Some(week);
+        }
+        let mut load: f64 = m
+            .concepts
+            .iter()
+            .filter(|c| pinned[c.offset] == Some(week))
+            .map(|c| c.modes[0].weight)
+            .fold(0.0, |p, n| p + n);
+
+        // Teaching a concept can ready its dependents for the same week.
+        loop {
+            let ready = m
+                .dependency_order
+                .iter()
+                .map(|n| m.dependency_to_concept(n))
+                .filter(|c| weeks[c.offset].is_none() && pinned[c.offset].is_none())
+                .filter(|c| c.earliest.unwrap_or(1) <= week)
+                .filter(|c| {
+                    c.dependencies.iter().all(|d| {
+                        weeks[m.dependency_to_concept(d).offset].is_some_and(|w| {
+                            if c.kind.is_milestone() {
+                                w < week
+                            } else {
+                                w <= week
+                            }
+                        })
+                    })
+                })
+                // the first of those due soonest, in dependency order
+                .min_by_key(|c| due(c));
+            let Some(c) = ready else { break };
+            let over = load + c.modes[0].weight - capacity;
+            let fits = over < EPSILON || load < EPSILON || (due(c) <= week && over < 1.0);
+            if !fits {
+                break;
+            }
+            weeks[c.offset] = Some(week);
+            load += c.modes[0].weight;
+        }
+        week += 1;
+    }
+    weeks.into_iter().map(|w| w.unwrap_or(0)).collect()
+}
+
+/// The planned weeks, and the constraints relaxed for them. Requires
+/// the dependencies to be acyclic.
+pub fn report(m: &ConceptMap, args: &PlanArgs) -> String {
+    let weeks = weeks(m, args.capacity);
+    let nweeks = weeks.iter().copied().max().unwrap_or(0);
+    let mut relaxed = Vec::new();
+    let mut violation = 0.0;
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "Plan for {} of lecture a week:\n",
+        m.units.weeks(args.capacity)
+    ));
+    for week in 1..=nweeks {
+        let concepts: Vec<&Concept> = m
+            .dependency_order
+            .iter()
+            .map(|n| m.dependency_to_concept(n))
+            .filter(|c| c.is_taught() && weeks[c.offset] == week)
+            .collect();
+        let load = concepts
+            .iter()
+            .map(|c| c.modes[0].weight)
+            .fold(0.0, |p, n| p + n); // not sum(), which starts from -0.0
+        let names: Vec<String> = concepts
+            .iter()
+            .map(|c| format!("\"{}\"", c.concept))
+            .collect();
+        out.push_str(&format!(
+            "- Week {} ({}): {}\n",
+            week,
+            m.units.weeks(load),
+            if names.is_empty() {
+                String::from("no new concepts")
+            } else {
+                names.join(", ")
+            }
+        ));
+
+        let over = load - args.capacity;
+        if over > EPSILON {
+            violation += over;
+            relaxed.push(format!(
+                "- Week {} has {} of lecture, {} over capacity.",
+                week,
+                m.units.weeks(load),
+                m.units.weeks(over)
+            ));
+        }
+        if m.semester_weeks.is_some_and(|s| week as f64 > s) {
+            violation += 1.0;
+            relaxed.push(format!(
+                "- Week {} is past the {}-week semester.",
+                week,
+                m.semester_weeks.unwrap() // checked above
+            ));
+        }
+    }
+    for c in m.concepts.iter().filter(|c| c.is_taught()) {
+        let week = weeks[c.offset];
+        match c.latest {
+            Some(latest) if week > latest => {
+                let late = week - latest;
+                violation += late as f64;
+                relaxed.push(format!(
+                    "- \"{}\" on {} is taught in week {}, {} after its latest week, {}.",
+                    c.concept,
+                    c.location,
+                    week,
+                    plural(late as usize, "week", "weeks"),
+                    latest
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    if relaxed.is_empty() {
+        out.push_str("\nNo constraints had to be relaxed.\n");
+    } else {
+        out.push_str(&format!(
+            "\n{} relaxed, by {:.2} weeks in all:\n",
+            plural(relaxed.len(), "constraint", "constraints"),
+            violation
+        ));
+        for r in relaxed {
+            out.push_str(&format!("{}\n", r));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CROWDED: &str = "concept,dependencies,week,earliest,latest,lecture weight,status
+a,,,,1,1,
+b,a,,,1,0.5,
+c,,,3,,0.5,
+d,a,,,,1,
+p,,2,,,0.5,
+old,,,,,1,retired
+";
+
+    fn weeks_of(m: &ConceptMap, capacity: f64) -> Vec<(&str, u64)> {
+        let weeks = weeks(m, capacity);
+        m.concepts
+            .iter()
+            .map(|c| (c.concept.as_str(), weeks[c.offset]))
+            .collect()
+    }
+
+    #[test]
+    fn concepts_due_go_over_capacity_and_the_rest_wait() {
+        let m = crate::test_map(CROWDED);
+
+        assert_eq!(
+            weeks_of(&m, 1.0),
+            [("a", 1), ("b", 1), ("c", 3), ("d", 4), ("p", 2), ("old", 0)]
+        );
+        assert_eq!(
+            weeks_of(&m, 2.0),
+            [("a", 1), ("b", 1), ("c", 3), ("d", 2), ("p", 2), ("old", 0)]
+        );
+    }
+
+    #[test]
+    fn exams_are_the_week_after_their_concepts() {
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight,type
+a,,0.5,
+midterm,a,0,exam
+",
+        );
+
+        assert_eq!(weeks_of(&m, 1.0), [("a", 1), ("midterm", 2)]);
+    }
+
+    #[test]
+    fn relaxed_constraints_are_listed() {
+        let m = crate::test_map(CROWDED);
+        let plan = report(&m, &PlanArgs { capacity: 1.0 });
+
+        assert!(
+            plan.contains("- Week 1 has 1.50 weeks of lecture, 0.50 weeks over capacity.\n"),
+            "{}",
+            plan
+        );
+        assert!(
+            plan.contains("1 constraint relaxed, by 0.50 weeks in all:\n"),
+            "{}",
+            plan
+        );
+        assert!(!plan.contains("old"), "{}", plan);
+    }
+}
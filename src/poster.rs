@@ -0,0 +1,275 @@
+// Poster output: tile a (huge) rendering of the map across a grid of
+// letter or A4 pages, with crop marks and overlap between adjacent
+// pages, so that the printed pages can be trimmed and pinned up as a
+// wall poster. Graphviz does the rendering, and we only tile its
+// PostScript, e.g.:
+//
+//     concept_map < map.csv | dot -Teps > map.eps
+//     concept_map poster --pages 3x2 map.eps | ps2pdf - poster.pdf
+
+use clap::{Args, ValueEnum};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// PostScript units are points.
+const INCH: f64 = 72.0;
+const MARGIN: f64 = 0.5 * INCH;
+const CROP_MARK: f64 = 0.25 * INCH;
+
+#[derive(Args)]
+pub struct PosterArgs {
+    /// EPS or PostScript rendering of the map (e.g. from `dot -Teps`)
+    eps: PathBuf,
+    /// Paper size of each page
+    #[arg(long, value_enum, default_value = "letter")]
+    paper: Paper,
+    /// Grid of pages to tile across, as COLUMNSxROWS
+    #[arg(long, default_value = "2x2", value_parser = parse_grid)]
+    pages: (usize, usize),
+    /// Overlap between adjacent pages, in inches
+    #[arg(long, default_value_t = 0.5)]
+    overlap: f64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Paper {
+    Letter,
+    A4,
+}
+
+impl Paper {
+    fn size(&self) -> (f64, f64) {
+        match self {
+            Self::Letter => (612.0, 792.0),
+            Self::A4 => (595.0, 842.0),
+        }
+    }
+}
+
+fn parse_grid(s: &str) -> Result<(usize, usize), String> {
+    let err = || format!("expected COLUMNSxROWS (e.g. 3x2), found \"{}\"", s);
+    let (c, r) = s.split_once('x').ok_or_else(err)?;
+    let cols: usize = c.trim().parse().map_err(|_| err())?;
+    let rows: usize = r.trim().parse().map_err(|_| err())?;
+
+    if cols == 0 || rows == 0 {
+        return Err(err());
+    }
+    Ok((cols, rows))
+}
+
+// The last numeric bounding box wins, which handles `(atend)`.
+fn bounding_box(ps: &str) -> Option<[f64; 4]> {
+    let mut bb = None;
+
+    for line in ps.lines() {
+        if let Some(rest) = line.strip_prefix("%%BoundingBox:") {
+            let nums: Vec<f64> = rest
+                .split_whitespace()
+                .filter_map(|n| n.parse().ok())
+                .collect();
+            if nums.len() == 4 {
+                bb = Some([nums[0], nums[1], nums[2], nums[3]]);
+            }
+        }
+    }
+    bb
+}
+
+/// Write the tiled PostScript document.
+pub fn write<W: io::Write>(args: &PosterArgs, mut out: W) -> anyhow::Result<()> {
+    let eps = fs::read_to_string(&args.eps)?;
+    let [llx, lly, urx, ury] = bounding_box(&eps)
+        .ok_or_else(|| anyhow::anyhow!("no %%BoundingBox found in {}", args.eps.display()))?;
+    let (paper_w, paper_h) = args.paper.size();
+    let (cols, rows) = args.pages;
+    let overlap = args.overlap * INCH;
+
+    // Each page prints an area inside its margins, and neighboring
+    // pages share `overlap` of it.
+    let (w, h) = (paper_w - 2.0 * MARGIN, paper_h - 2.0 * MARGIN);
+    if overlap < 0.0 || overlap >= w.min(h) {
+        anyhow::bail!("overlap must be smaller than the printable area of a page");
+    }
+    let poster_w = cols as f64 * (w - overlap) + overlap;
+    let poster_h = rows as f64 * (h - overlap) + overlap;
+    let (bb_w, bb_h) = ((urx - llx).max(1.0), (ury - lly).max(1.0));
+    let scale = (poster_w / bb_w).min(poster_h / bb_h);
+    // center the map on the poster
+    let off_x = (poster_w - bb_w * scale) / 2.0;
+    let off_y = (poster_h - bb_h * scale) / 2.0;
+
+    let mut ps = String::new();
+    ps.push_str(&format!("%!PS-Adobe-3.0\n%%Creator: concept_map poster\n%%Pages: {}\n%%BoundingBox: 0 0 {} {}\n%%EndComments\n",
+        cols * rows,
+        paper_w as i64,
+        paper_h as i64
+    ));
+    // The usual EPS inclusion protocol, additionally ignoring any
+    // page device changes made by the included document.
+    ps.push_str(&format!(
+        "%%BeginProlog
+/BeginEPSF {{
+  /b4_Inc_state save def /dict_count countdictstack def /op_count count 1 sub def
+  userdict begin /showpage {{ }} def /setpagedevice {{ pop }} def
+  0 setgray 0 setlinecap 1 setlinewidth 0 setlinejoin 10 setmiterlimit [ ] 0 setdash newpath
+}} bind def
+/EndEPSF {{
+  count op_count sub {{ pop }} repeat countdictstack dict_count sub {{ end }} repeat
+  b4_Inc_state restore
+}} bind def
+/cropmark {{ newpath moveto rlineto stroke }} bind def
+%%EndProlog
+%%BeginSetup
+<< /PageSize [{} {}] >> setpagedevice
+%%EndSetup\n",
+        paper_w, paper_h
+    ));
+
+    for r in 0..rows {
+        for c in 0..cols {
+            let page = r * cols + c + 1;
+            // offset of this page's printable area in the poster,
+            // counting rows from the top
+            let x0 = c as f64 * (w - overlap);
+            let y0 = (rows - 1 - r) as f64 * (h - overlap);
+            let (x1, y1) = (MARGIN + w, MARGIN + h);
+
+            ps.push_str(&format!("%%Page: {} {}\ngsave\n", page, page));
+            // crop marks at the corners of the printable area, in the margin
+            ps.push_str("0.3 setlinewidth\n");
+            for (x, y, dx, dy) in [
+                (MARGIN, MARGIN, -1.0, -1.0),
+                (x1, MARGIN, 1.0, -1.0),
+                (MARGIN, y1, -1.0, 1.0),
+                (x1, y1, 1.0, 1.0),
+            ] {
+                ps.push_str(&format!(
+                    "{} 0 {} {} cropmark 0 {} {} {} cropmark\n",
+                    dx * CROP_MARK,
+                    x,
+                    y,
+                    dy * CROP_MARK,
+                    x,
+                    y
+                ));
+            }
+            ps.push_str(&format!("/Helvetica findfont 7 scalefont setfont {} {} moveto (page {} of {}: row {}, column {}) show\n",
+                MARGIN,
+                MARGIN / 3.0,
+                page,
+                cols * rows,
+                r + 1,
+                c + 1
+            ));
+            ps.push_str(&format!("newpath {} {} moveto {} 0 rlineto 0 {} rlineto {} 0 rlineto closepath clip newpath\n",
+                MARGIN, MARGIN, w, h, -w
+            ));
+            ps.push_str(&format!(
+                "{} {} translate {} {} scale {} {} translate\n",
+                MARGIN - x0 + off_x,
+                MARGIN - y0 + off_y,
+                scale,
+                scale,
+                -llx,
+                -lly
+            ));
+            ps.push_str(&format!(
+                "BeginEPSF\n%%BeginDocument: {}\n{}\n%%EndDocument\nEndEPSF\ngrestore\nshowpage\n",
+                args.eps.display(),
+                eps.trim_end()
+            ));
+        }
+    }
+    ps.push_str("%%EOF\n");
+
+    out.write_all(ps.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_and_bounding_boxes() {
+        assert_eq!(parse_grid("3x2"), Ok((3, 2)));
+        assert_eq!(parse_grid(" 1 x 4 "), Ok((1, 4)));
+        assert_eq!(
+            parse_grid("0x2"),
+            Err(String::from(
+                "expected COLUMNSxROWS (e.g. 3x2), found \"0x2\""
+            ))
+        );
+        assert!(parse_grid("3").is_err());
+        assert!(parse_grid("ax2").is_err());
+
+        assert_eq!(bounding_box("%!PS\n"), None);
+        assert_eq!(
+            bounding_box("%%BoundingBox: (atend)\nstuff\n%%BoundingBox: 1 2 3.5 4\n"),
+            Some([1.0, 2.0, 3.5, 4.0])
+        );
+    }
+
+    #[test]
+    fn pages_tile_the_scaled_rendering() {
+        let eps =
+            std::env::temp_dir().join(format!("concept_map_poster_{}.eps", std::process::id()));
+        fs::write(
+            &eps,
+            "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 10 20 110 120\nshowpage\n",
+        )
+        .unwrap();
+        let mut args = PosterArgs {
+            eps: eps.clone(),
+            paper: Paper::Letter,
+            pages: (2, 1),
+            overlap: 0.5,
+        };
+        let mut out = Vec::new();
+        write(&args, &mut out).unwrap();
+        let ps = String::from_utf8(out).unwrap();
+
+        assert!(ps.starts_with("%!PS-Adobe-3.0\n%%Creator: concept_map poster\n%%Pages: 2\n%%BoundingBox: 0 0 612 792\n"), "{}", ps);
+        assert!(
+            ps.contains("<< /PageSize [612 792] >> setpagedevice\n"),
+            "{}",
+            ps
+        );
+        assert!(ps.contains("%%Page: 1 1\n"), "{}", ps);
+        assert!(
+            ps.contains("(page 2 of 2: row 1, column 2) show\n"),
+            "{}",
+            ps
+        );
+        assert!(
+            ps.contains("\n198 36 translate 7.2 7.2 scale -10 -20 translate\n"),
+            "{}",
+            ps
+        );
+        assert!(
+            ps.contains("\n-306 36 translate 7.2 7.2 scale -10 -20 translate\n"),
+            "{}",
+            ps
+        );
+        assert!(
+            ps.contains("\n-18 0 36 36 cropmark 0 -18 36 36 cropmark\n"),
+            "{}",
+            ps
+        );
+        assert!(
+            ps.ends_with("EndEPSF\ngrestore\nshowpage\n%%EOF\n"),
+            "{}",
+            ps
+        );
+
+        args.overlap = 10.0;
+        let err = write(&args, Vec::new()).unwrap_err();
+        fs::remove_file(&eps).unwrap();
+        assert_eq!(
+            err.to_string(),
+            "overlap must be smaller than the printable area of a page"
+        );
+    }
+}
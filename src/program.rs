@@ -0,0 +1,135 @@
+// The program-level graph, for curriculum reviews: one node per course,
+// and edges for the concepts a course builds on that another course
+// teaches, labeled by how many of its concept-level dependencies cross
+// over. A course's concepts without weight are the ones it expects
+// from elsewhere, and are matched to the courses teaching them by their
+// normalized names and aliases.
+
+use crate::{dot_escape, dot_id, normalize, plural, ConceptMap, RenderOpts};
+use clap::Args;
+use dot_writer::{Attributes, DotWriter, Style};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ProgramArgs {
+    /// The courses' csv files, each named after its file
+    #[arg(required = true, num_args = 2..)]
+    pub files: Vec<PathBuf>,
+}
+
+/// The course name for a csv file.
+pub fn course_name(file: &std::path::Path) -> String {
+    file.file_stem()
+        .unwrap_or(file.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn graph(courses: &[(String, ConceptMap)], opts: &RenderOpts) -> Vec<u8> {
+    // the first course teaching each concept
+    let mut teaches = HashMap::new();
+    for (i, (_, m)) in courses.iter().enumerate() {
+        for c in m.concepts.iter().filter(|c| c.is_taught()) {
+            for n in c.names() {
+                teaches.entry(normalize::key(n)).or_insert(i);
+            }
+        }
+    }
+    // the number of dependencies from each course on each other one
+    let mut edges: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    for (i, (_, m)) in courses.iter().enumerate() {
+        for c in &m.concepts {
+            for d in &c.dependencies {
+                let d = m.dependency_to_concept(d);
+                if d.is_taught() {
+                    continue;
+                }
+                if let Some(&j) = d.names().find_map(|n| teaches.get(&normalize::key(n))) {
+                    if j != i {
+                        *edges.entry((i, j)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+    }
+    let id = |i: usize| dot_id(&format!("k{}", i), &courses[i].0);
+    let mut output_bytes = Vec::new();
+    {
+        let mut writer = DotWriter::from(&mut output_bytes);
+        let mut digraph = writer.digraph();
+
+        opts.set_layout(&mut digraph);
+        opts.set_fonts(&mut digraph);
+        opts.set_titles(&mut digraph);
+        opts.set_edge_style(&mut digraph);
+        digraph.node_attributes().set("penwidth", "2.5", false);
+        for (i, (name, m)) in courses.iter().enumerate() {
+            digraph
+                .node_named(id(i))
+                .set_style(Style::Rounded)
+                .set("shape", "box", false)
+                .set_label(&dot_escape(&format!(
+                    "{}\n{}\nLecture {}\nLab {}\nHW {}",
+                    name,
+                    plural(
+                        m.concepts.iter().filter(|c| c.is_taught()).count(),
+                        "concept",
+                        "concepts"
+                    ),
+                    m.weight_label(0, m.total_weights[0]),
+                    m.weight_label(1, m.total_weights[1]),
+                    m.weight_label(2, m.total_weights[2])
+                )));
+        }
+        for ((course, prerequisite), count) in &edges {
+            let (from, to) = opts.edge(course, prerequisite);
+            digraph
+                .edge(id(*from), id(*to))
+                .attributes()
+                .set_label(&count.to_string())
+                .set("weight", &count.to_string(), false);
+        }
+    }
+    output_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser as _;
+
+    #[test]
+    fn edges_count_the_dependencies_on_other_courses() {
+        let intro = crate::test_map(
+            "concept,dependencies,aliases,lecture weight
+C programming,,C,1
+pointers,C programming,,1
+",
+        );
+        let os = crate::test_map(
+            "concept,dependencies,lecture weight
+c,,0
+Pointers,,0
+paging,c;Pointers,1
+",
+        );
+        let courses = [(String::from("intro"), intro), (String::from("os"), os)];
+        let dot =
+            String::from_utf8(graph(&courses, &Cli::parse_from(["concept_map"]).render)).unwrap();
+
+        assert!(
+            dot.contains("  k1_os -> k0_intro [label=\"2\", weight=2];\n"),
+            "{}",
+            dot
+        );
+        assert!(
+            dot.contains("label=\"os\n1 concept\nLecture 1.00 weeks\n"),
+            "{}",
+            dot
+        );
+        assert_eq!(dot.matches("->").count(), 1, "{}", dot);
+        assert_eq!(course_name(std::path::Path::new("courses/os.csv")), "os");
+    }
+}
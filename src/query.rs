@@ -0,0 +1,405 @@
+// Queries about single concepts from the terminal: why a concept's
+// earliest start is what it is, what it depends on, and what depends
+// on it. Also, how many concepts each one blocks, for which slipping
+// would cascade the most.
+
+use crate::{category_label, normalize, plural, Concept, ConceptMap};
+use clap::Args;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Args)]
+pub struct DepsArgs {
+    /// The concept's name
+    pub concept: String,
+    /// Only list the concepts it directly depends on
+    #[arg(long)]
+    pub direct: bool,
+}
+
+#[derive(Args)]
+pub struct RdepsArgs {
+    /// The concept's name
+    pub concept: String,
+}
+
+#[derive(Args)]
+pub struct BlockingArgs {
+    /// How many concepts to list
+    #[arg(long, short = 'n', value_name = "N", default_value_t = 10)]
+    pub top: usize,
+}
+
+fn lookup<'a>(m: &'a ConceptMap, name: &str) -> anyhow::Result<&'a Concept> {
+    let name = normalize::name(name.trim());
+
+    match m.lookup.get(&name) {
+        Some(&offset) => Ok(&m.concepts[offset]),
+        None => anyhow::bail!("No concept named \"{}\" in the csv file.", name),
+    }
+}
+
+// Breadth-first from the concept at offset `start` through `edges`
+// (indexed by offset), so each concept reached maps to the concept
+// through which it was most directly reached (`start` itself for its
+// neighbors), by offset.
+fn reached_via(start: usize, edges: &[Vec<usize>]) -> HashMap<usize, usize> {
+    let mut via = HashMap::new();
+    let mut queue = VecDeque::from(vec![start]);
+
+    while let Some(o) = queue.pop_front() {
+        for &next in &edges[o] {
+            if next != start && !via.contains_key(&next) {
+                via.insert(next, o);
+                queue.push_back(next);
+            }
+        }
+    }
+    via
+}
+
+// Each transitive dependency of `c`, mapped to the concept through
+// which it's most directly required.
+fn required_via(m: &ConceptMap, c: &Concept) -> HashMap<usize, usize> {
+    let deps: Vec<Vec<usize>> = m
+        .concepts
+        .iter()
+        .map(|c| {
+            c.dependencies
+                .iter()
+                .map(|d| m.dependency_to_concept(d).offset)
+                .collect()
+        })
+        .collect();
+
+    reached_via(c.offset, &deps)
+}
+
+// The chain of dependencies from `c` down to `d`, e.g. "A <- B <- d".
+fn chain(m: &ConceptMap, via: &HashMap<usize, usize>, c: &Concept, d: usize) -> String {
+    let mut names = vec![m.concepts[d].concept.as_str()];
+    let mut o = d;
+
+    while let Some(&next) = via.get(&o) {
+        if next == c.offset {
+            break;
+        }
+        names.push(&m.concepts[next].concept);
+        o = next;
+    }
+    names.reverse();
+    names.join(" <- ")
+}
+
+/// The lecture weights that add up to a concept's earliest lecture
+/// start: all of its transitive dependencies have to be taught first.
+/// Requires the map to be solved.
+pub fn explain(m: &ConceptMap, name: &str) -> anyhow::Result<String> {
+    let c = lookup(m, name)?;
+    let via = required_via(m, c);
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "\"{}\" [{}] has an earliest lecture start of {:.2} weeks.\n",
+        c.concept,
+        category_label(&c.category),
+        c.modes[0].range.earliest_start
+    ));
+    if via.is_empty() {
+        out.push_str("It has no dependencies, so it can be taught first.\n");
+        return Ok(out);
+    }
+    out.push_str(&format!(
+        "The lectures of its {} come first:\n",
+        plural(
+            via.len(),
+            "transitive dependency",
+            "transitive dependencies"
+        )
+    ));
+    out.push_str(&format!(
+        "{:>8} {:>8}  dependency chain\n",
+        "weight", "total"
+    ));
+
+    let deps = in_dependency_order(m, &via);
+    let mut total = 0.0;
+    for d in deps {
+        total += m.concepts[d].modes[0].weight;
+        out.push_str(&format!(
+            "{:>8.2} {:>8.2}  {}\n",
+            m.concepts[d].modes[0].weight,
+            total,
+            chain(m, &via, c, d)
+        ));
+    }
+
+    Ok(out)
+}
+
+// The offsets of the concepts in `via`, in dependency order. Concepts
+// in cycles aren't in the dependency order, so they come last.
+fn in_dependency_order(m: &ConceptMap, via: &HashMap<usize, usize>) -> Vec<usize> {
+    let mut deps: Vec<usize> = m
+        .dependency_order
+        .iter()
+        .map(|n| m.dependency_to_concept(n).offset)
+        .filter(|o| via.contains_key(o))
+        .collect();
+    let mut rest: Vec<usize> = via.keys().copied().filter(|o| !deps.contains(o)).collect();
+
+    rest.sort_unstable();
+    deps.append(&mut rest);
+    deps
+}
+
+/// A concept's dependencies, with their weights adding up.
+pub fn deps(m: &ConceptMap, args: &DepsArgs) -> anyhow::Result<String> {
+    let c = lookup(m, &args.concept)?;
+    let mut via = required_via(m, c);
+    if args.direct {
+        via.retain(|_, &mut v| v == c.offset);
+    }
+    let mut out = String::new();
+    let mut total = [0.0; 3];
+
+    out.push_str(&format!(
+        "\"{}\" [{}] {} depends on {}:\n",
+        c.concept,
+        category_label(&c.category),
+        if args.direct {
+            "directly"
+        } else {
+            "transitively"
+        },
+        plural(via.len(), "concept", "concepts")
+    ));
+    out.push_str(&format!(
+        "{:>8} {:>8} {:>8}  concept (cumulative lecture weight)\n",
+        "lecture", "lab", "HW"
+    ));
+    for d in in_dependency_order(m, &via) {
+        let d = &m.concepts[d];
+        for (t, mode) in total.iter_mut().zip(&d.modes) {
+            *t += mode.weight;
+        }
+        out.push_str(&format!(
+            "{:>8.2} {:>8.2} {:>8.2}  {} [{}] ({:.2})\n",
+            d.modes[0].weight,
+            d.modes[1].weight,
+            d.modes[2].weight,
+            d.concept,
+            category_label(&d.category),
+            total[0]
+        ));
+    }
+    out.push_str(&format!(
+        "{:>8.2} {:>8.2} {:>8.2}  total\n",
+        total[0], total[1], total[2]
+    ));
+
+    Ok(out)
+}
+
+/// How many concepts transitively depend on each concept, by offset.
+pub fn blocked(m: &ConceptMap) -> Vec<usize> {
+    let dependents = m.dependents();
+
+    (0..m.concepts.len())
+        .map(|o| reached_via(o, &dependents).len())
+        .collect()
+}
+
+/// The `top` concepts blocking the most others.
+pub fn blocking(m: &ConceptMap, args: &BlockingArgs) -> String {
+    let blocked = blocked(m);
+    let mut concepts: Vec<&Concept> = m.concepts.iter().collect();
+    concepts.sort_by_key(|c| (std::cmp::Reverse(blocked[c.offset]), c.offset));
+    let mut out = String::new();
+
+    out.push_str("Concepts by how many concepts depend on them:\n");
+    for c in concepts.into_iter().take(args.top) {
+        out.push_str(&format!(
+            "{:>5} ({:>3.0}%)  {} [{}]\n",
+            blocked[c.offset],
+            blocked[c.offset] as f64 / m.concepts.len() as f64 * 100.0,
+            c.concept,
+            category_label(&c.category)
+        ));
+    }
+    out
+}
+
+/// Everything that transitively requires a concept, and how much
+/// weight that adds up to: how foundational the concept is.
+pub fn rdeps(m: &ConceptMap, args: &RdepsArgs) -> anyhow::Result<String> {
+    let c = lookup(m, &args.concept)?;
+    let via = reached_via(c.offset, &m.dependents());
+    let direct = via.values().filter(|&&v| v == c.offset).count();
+    let mut out = String::new();
+    let mut total = [0.0; 3];
+
+    out.push_str(&format!(
+        "{} ({} directly) {} on \"{}\" [{}]:\n",
+        plural(via.len(), "concept", "concepts"),
+        direct,
+        if via.len() == 1 { "depends" } else { "depend" },
+        c.concept,
+        category_label(&c.category)
+    ));
+    out.push_str(&format!(
+        "{:>8} {:>8} {:>8}  concept\n",
+        "lecture", "lab", "HW"
+    ));
+    for d in in_dependency_order(m, &via) {
+        let d = &m.concepts[d];
+        for (t, mode) in total.iter_mut().zip(&d.modes) {
+            *t += mode.weight;
+        }
+        out.push_str(&format!(
+            "{:>8.2} {:>8.2} {:>8.2}  {} [{}]{}\n",
+            d.modes[0].weight,
+            d.modes[1].weight,
+            d.modes[2].weight,
+            d.concept,
+            category_label(&d.category),
+            if via[&d.offset] == c.offset {
+                " (direct)"
+            } else {
+                ""
+            }
+        ));
+    }
+    out.push_str(&format!(
+        "{:>8.2} {:>8.2} {:>8.2}  total downstream weight\n",
+        total[0], total[1], total[2]
+    ));
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIAMOND: &str = "concept,dependencies,category,lecture weight,lab weight
+a,,x,1,1
+b,a,x,0.5,
+c,b;d,y,1,
+d,a,y,0.25,
+e,,y,1,
+";
+
+    #[test]
+    fn explanations_chain_back_to_each_dependency() {
+        let m = crate::test_map(DIAMOND);
+
+        assert_eq!(
+            explain(&m, "c").unwrap().lines().collect::<Vec<_>>(),
+            [
+                "\"c\" [y] has an earliest lecture start of 1.75 weeks.",
+                "The lectures of its 3 transitive dependencies come first:",
+                "  weight    total  dependency chain",
+                "    1.00     1.00  b <- a",
+                "    0.50     1.50  b",
+                "    0.25     1.75  d",
+            ]
+        );
+        assert_eq!(
+            explain(&m, "e").unwrap(),
+            "\"e\" [y] has an earliest lecture start of 0.00 weeks.\n\
+             It has no dependencies, so it can be taught first.\n"
+        );
+        assert_eq!(
+            explain(&m, "f").unwrap_err().to_string(),
+            "No concept named \"f\" in the csv file."
+        );
+    }
+
+    #[test]
+    fn dependencies_add_up_their_weights() {
+        let m = crate::test_map(DIAMOND);
+        let all = deps(
+            &m,
+            &DepsArgs {
+                concept: String::from("c"),
+                direct: false,
+            },
+        )
+        .unwrap();
+        let direct = deps(
+            &m,
+            &DepsArgs {
+                concept: String::from("c"),
+                direct: true,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            all.lines().collect::<Vec<_>>(),
+            [
+                "\"c\" [y] transitively depends on 3 concepts:",
+                " lecture      lab       HW  concept (cumulative lecture weight)",
+                "    1.00     1.00     0.00  a [x] (1.00)",
+                "    0.50     0.00     0.00  b [x] (1.50)",
+                "    0.25     0.00     0.00  d [y] (1.75)",
+                "    1.75     1.00     0.00  total",
+            ]
+        );
+        assert_eq!(
+            direct.lines().collect::<Vec<_>>(),
+            [
+                "\"c\" [y] directly depends on 2 concepts:",
+                " lecture      lab       HW  concept (cumulative lecture weight)",
+                "    0.50     0.00     0.00  b [x] (0.50)",
+                "    0.25     0.00     0.00  d [y] (0.75)",
+                "    0.75     0.00     0.00  total",
+            ]
+        );
+    }
+
+    #[test]
+    fn dependents_add_up_downstream() {
+        let m = crate::test_map(DIAMOND);
+        let report = rdeps(
+            &m,
+            &RdepsArgs {
+                concept: String::from("a"),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.lines().collect::<Vec<_>>(),
+            [
+                "3 concepts (2 directly) depend on \"a\" [x]:",
+                " lecture      lab       HW  concept",
+                "    0.50     0.00     0.00  b [x] (direct)",
+                "    0.25     0.00     0.00  d [y] (direct)",
+                "    1.00     0.00     0.00  c [y]",
+                "    1.75     0.00     0.00  total downstream weight",
+            ]
+        );
+        let report = rdeps(
+            &m,
+            &RdepsArgs {
+                concept: String::from("b"),
+            },
+        )
+        .unwrap();
+        assert!(report.starts_with("1 concept (1 directly) depends on \"b\" [x]:\n"));
+    }
+
+    #[test]
+    fn blocking_counts_transitive_dependents() {
+        let m = crate::test_map(DIAMOND);
+
+        assert_eq!(blocked(&m), [3, 1, 0, 1, 0]);
+        assert_eq!(
+            blocking(&m, &BlockingArgs { top: 2 }),
+            "Concepts by how many concepts depend on them:\n\
+             \x20   3 ( 60%)  a [x]\n\
+             \x20   1 ( 20%)  b [x]\n"
+        );
+    }
+}
@@ -0,0 +1,350 @@
+// Assigning concepts to the (1-based) weeks of the semester. A concept
+// is taught in the week given in the csv, if any. Otherwise, it's
+// taught as early as possible: in the week its earliest lecture start
+// falls in, but no earlier than its `earliest` week, nor than the
+// weeks of the concepts it depends on. Milestones (exams and projects)
+// come the week after the last of their dependencies. Corequisites are
+// taught in the same week, the latest any of them could be, unless
+// it's pinned. External concepts are taught in other courses, before
+// this one, so they're in week 0.
+//
+// The `earliest` and `latest` weeks are a window for the concept's
+// lecture to start in, which its dependencies can close: they have to
+// be taught first, and can have windows of their own.
+
+use crate::{Concept, ConceptMap, Kind};
+use std::collections::HashSet;
+
+// Weeks within rounding of each other are the same.
+const EPSILON: f64 = 1e-9;
+
+pub struct Schedule {
+    weeks: Vec<u64>, // indexed by concept offset
+}
+
+impl Schedule {
+    // Requires the map's earliest starts to be solved.
+    pub fn new(m: &ConceptMap) -> Self {
+        let asap = |c: &Concept| {
+            let week = 1 + c.modes[0].range.earliest_start.floor() as u64;
+            week.max(c.earliest.unwrap_or(1))
+        };
+        let pinned = |c: &Concept| {
+            if c.kind == Kind::External {
+                Some(0)
+            } else {
+                c.week.map(|w| w.max(1))
+            }
+        };
+
+        // Concepts in dependency cycles aren't in the dependency
+        // order, so they're scheduled without regard to their
+        // dependencies.
+        let mut weeks: Vec<u64> = m
+            .concepts
+            .iter()
+            .map(|c| pinned(c).unwrap_or_else(|| asap(c)))
+            .collect();
+        // A corequisite can depend on concepts later in the dependency
+        // order, so pushing it back takes more passes, until nothing
+        // moves. Weeks only grow, but a corequisite of a milestone's
+        // dependency would push it back forever, so the passes are
+        // bounded.
+        let coreqs = m.concepts.iter().any(|c| !c.corequisites.is_empty());
+        let passes = if coreqs { m.concepts.len() + 1 } else { 1 };
+        for _ in 0..passes {
+            let mut moved = false;
+
+            for n in &m.dependency_order {
+                let c = m.dependency_to_concept(n);
+                if pinned(c).is_some() {
+                    continue;
+                }
+                let after = c
+                    .dependencies
+                    .iter()
+                    .map(|d| {
+                        let week = weeks[m.dependency_to_concept(d).offset];
+                        if c.kind.is_milestone() {
+                            week + 1
+                        } else {
+                            week
+                        }
+                    })
+                    .chain(
+                        c.corequisites
+                            .iter()
+                            .map(|d| weeks[m.dependency_to_concept(d).offset]),
+                    )
+                    .max()
+                    .unwrap_or(1);
+                let week = asap(c).max(after);
+                moved |= week != weeks[c.offset];
+                weeks[c.offset] = week;
+            }
+            if !moved {
+                break;
+            }
+        }
+
+        Schedule { weeks }
+    }
+
+    pub fn week(&self, c: &Concept) -> u64 {
+        self.weeks[c.offset]
+    }
+
+    // Milestones require everything they (transitively) depend on to
+    // be taught before their week, which pinned weeks can violate.
+    pub fn check_milestones(&self, m: &ConceptMap) -> String {
+        let mut warnings = String::new();
+
+        for c in m.concepts.iter().filter(|c| c.kind.is_milestone()) {
+            let mut seen = HashSet::new();
+            let mut pending: Vec<&Concept> = vec![c];
+
+            while let Some(p) = pending.pop() {
+                for d in &p.dependencies {
+                    let d = m.dependency_to_concept(d);
+                    if !seen.insert(d.offset) {
+                        continue;
+                    }
+                    if d.is_taught() && self.week(d) >= self.week(c) {
+                        let when = if d.kind.is_milestone() {
+                            format!(
+                                "{} \"{}\", which isn't due",
+                                d.kind.name().to_lowercase(),
+                                d.concept
+                            )
+                        } else {
+                            format!("\"{}\", which isn't taught", d.concept)
+                        };
+                        warnings.push_str(&format!(
+                            "- {} \"{}\" in week {} depends on {} until week {}.\n",
+                            c.kind.name(),
+                            c.concept,
+                            self.week(c),
+                            when,
+                            self.week(d)
+                        ));
+                    }
+                    pending.push(d);
+                }
+            }
+        }
+        warnings
+    }
+
+    // Corequisites that can't be taught in the same week, as one of
+    // them has its week pinned earlier.
+    pub fn check_corequisites(&self, m: &ConceptMap) -> String {
+        let mut warnings = String::new();
+
+        for c in &m.concepts {
+            for d in &c.corequisites {
+                let d = m.dependency_to_concept(d);
+                if c.offset < d.offset && self.week(c) != self.week(d) {
+                    warnings.push_str(&format!(
+                        "- Corequisites \"{}\" and \"{}\" are taught in different weeks, {} and {}.\n",
+                        c.concept,
+                        d.concept,
+                        self.week(c),
+                        self.week(d)
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+
+    // The last week with any scheduled concept.
+    pub fn nweeks(&self) -> u64 {
+        self.weeks.iter().copied().max().unwrap_or(0)
+    }
+}
+
+// Why a concept can't start before a (fractional, 1-based) week.
+enum Bound<'a> {
+    Lectures, // of its (transitive) dependencies, one at a time
+    Earliest,
+    Dependency(&'a Concept, f64),
+}
+
+/// Warnings for the concepts whose `latest` week comes before they can
+/// start, given their dependencies' weights and `earliest` weeks, or
+/// before their own `earliest` week. Requires the map's earliest starts
+/// to be solved, and the dependencies to be acyclic.
+pub fn check_windows(m: &ConceptMap) -> String {
+    let mut warnings = String::new();
+    let mut starts = vec![1.0; m.concepts.len()];
+
+    // dependencies come first in the dependency order
+    for n in &m.dependency_order {
+        let c = m.dependency_to_concept(n);
+        let mut start = 1.0 + c.modes[0].range.earliest_start;
+        let mut bound = Bound::Lectures;
+
+        if let Some(earliest) = c.earliest {
+            if earliest as f64 > start + EPSILON {
+                start = earliest as f64;
+                bound = Bound::Earliest;
+            }
+        }
+        for d in &c.dependencies {
+            let d = m.dependency_to_concept(d);
+            let after = starts[d.offset] + d.modes[0].weight;
+            // naming the dependency, rather than all of them, on ties
+            if after > start - EPSILON {
+                start = start.max(after);
+                bound = Bound::Dependency(d, starts[d.offset]);
+            }
+        }
+        starts[c.offset] = start;
+
+        let Some(latest) = c.latest else { continue };
+        if c.kind == Kind::External {
+            continue;
+        }
+        if let Some(earliest) = c.earliest.filter(|&e| e > latest) {
+            warnings.push_str(&format!(
+                "- \"{}\" on {} has its earliest week, {}, after its latest, {}.\n",
+                c.concept, c.location, earliest, latest
+            ));
+            continue;
+        }
+        // a lecture starting in week `latest` can start part way through it
+        if start < latest as f64 + 1.0 - EPSILON {
+            continue;
+        }
+        let why = match bound {
+            Bound::Lectures => String::from(
+                "its dependencies' lectures come first; drop some of them, or lighten them",
+            ),
+            Bound::Earliest => unreachable!("the earliest week is checked above"),
+            Bound::Dependency(d, d_start) => format!(
+                "it depends on \"{}\", which can't start before week {:.2}, and takes {} of lectures; teach it earlier or in less time, or drop the dependency",
+                d.concept,
+                d_start,
+                m.units.weeks(d.modes[0].weight)
+            ),
+        };
+        warnings.push_str(&format!(
+            "- \"{}\" on {} can't start before week {:.2}, but its latest week is {}: {}.\n",
+            c.concept, c.location, start, latest, why
+        ));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weeks(csv: &str) -> Vec<(String, u64)> {
+        let m = crate::test_map(csv);
+        let s = Schedule::new(&m);
+        m.concepts
+            .iter()
+            .map(|c| (c.concept.clone(), s.week(c)))
+            .collect()
+    }
+
+    fn named(weeks: &[(&str, u64)]) -> Vec<(String, u64)> {
+        weeks.iter().map(|&(c, w)| (c.to_string(), w)).collect()
+    }
+
+    #[test]
+    fn concepts_are_taught_as_early_as_possible_or_when_pinned() {
+        let csv = "concept,dependencies,category,week,earliest,lecture weight
+C,,extern:C,,,1
+a,C,x,,,1
+b,a,x,,,0.5
+c,b,x,,,1
+pinned,a,x,6,,1
+late,,x,,3,0.5
+";
+
+        assert_eq!(
+            weeks(csv),
+            named(&[
+                ("C", 0),
+                ("a", 1),
+                ("b", 2),
+                ("c", 2),
+                ("pinned", 6),
+                ("late", 3)
+            ])
+        );
+    }
+
+    #[test]
+    fn milestones_are_due_the_week_after_their_dependencies() {
+        let csv = "concept,dependencies,week,lecture weight,type,status
+a,,,1,,
+b,a,,0.5,,
+old,,,1,,retired
+midterm,b;old,,0,exam,
+project,midterm,,0,project,
+early,b,1,0,exam,
+";
+        let m = crate::test_map(csv);
+        let s = Schedule::new(&m);
+
+        assert_eq!(
+            weeks(csv),
+            named(&[
+                ("a", 1),
+                ("b", 2),
+                ("old", 1),
+                ("midterm", 3),
+                ("project", 4),
+                ("early", 1)
+            ])
+        );
+        assert_eq!(
+            s.check_milestones(&m),
+            "- Exam \"early\" in week 1 depends on \"b\", which isn't taught until week 2.\n\
+             - Exam \"early\" in week 1 depends on \"a\", which isn't taught until week 1.\n"
+        );
+    }
+
+    #[test]
+    fn corequisites_share_the_latest_of_their_weeks() {
+        let csv = "concept,dependencies,corequisites,week,lecture weight
+a,,,,1
+b,a,,,1
+c,,b,,0.5
+pinned,,d,1,0.5
+d,b,,,0.5
+";
+        let m = crate::test_map(csv);
+        let s = Schedule::new(&m);
+
+        assert_eq!(
+            weeks(csv),
+            named(&[("a", 1), ("b", 2), ("c", 2), ("pinned", 1), ("d", 3)])
+        );
+        assert_eq!(
+            s.check_corequisites(&m),
+            "- Corequisites \"pinned\" and \"d\" are taught in different weeks, 1 and 3.\n"
+        );
+    }
+
+    #[test]
+    fn windows_closed_by_dependencies_say_which() {
+        let m = crate::test_map(
+            "concept,dependencies,earliest,latest,lecture weight
+a,,,,2
+b,a,,1,1
+c,,4,2,1
+d,a,,3,1
+",
+        );
+
+        assert_eq!(
+            check_windows(&m),
+            "- \"b\" on line 3 can't start before week 3.00, but its latest week is 1: it depends on \"a\", which can't start before week 1.00, and takes 2.00 weeks of lectures; teach it earlier or in less time, or drop the dependency.\n\
+             - \"c\" on line 4 has its earliest week, 4, after its latest, 2.\n"
+        );
+    }
+}
@@ -0,0 +1,248 @@
+// A static site for the concept map, to publish as is (e.g., on GitHub
+// Pages): an index page with the graph and a search over the concepts'
+// names, aliases, and descriptions, and a page per concept with its
+// description, week, dependencies, dependents, and material. The graph
+// is drawn in the browser from its DOT, with viz.js from a CDN, and
+// clicking a node opens its concept's page. The search and the pages
+// work without it.
+
+use crate::html::{css_color, escape};
+use crate::schedule::Schedule;
+use crate::{category_label, plural, Concept, ConceptMap, RenderOpts};
+use std::fs;
+use std::path::Path;
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; max-width: 60em; }
+nav { margin-bottom: 1em; }
+#_graph svg { max-width: 100%; height: auto; }
+#_graph .node { cursor: pointer; }
+#_search { font-size: 1.1em; padding: 0.3em; width: 100%; max-width: 30em; }
+.category { border-left: 0.6em solid; padding-left: 0.6em; }
+.meta { color: #555; }
+";
+
+// Filtering the concepts by the search box, and linking the graph's
+// nodes (by their DOT ids, in the SVG's titles) to their pages.
+const SCRIPT: &str = "
+function search(query) {
+  var q = query.trim().toLowerCase();
+  var results = document.getElementById('_results');
+  results.innerHTML = '';
+  if (!q) { return; }
+  CONCEPTS.filter(function (c) {
+    return c.text.indexOf(q) >= 0;
+  }).forEach(function (c) {
+    var li = document.createElement('li');
+    var a = document.createElement('a');
+    a.href = c.page;
+    a.textContent = c.name;
+    li.appendChild(a);
+    li.appendChild(document.createTextNode(' (' + c.category + ')'));
+    results.appendChild(li);
+  });
+  if (!results.children.length) {
+    results.innerHTML = '<li>No matching concepts.</li>';
+  }
+}
+window.addEventListener('load', function () {
+  if (typeof Viz === 'undefined') { return; }
+  Viz.instance().then(function (viz) {
+    var svg = viz.renderSVGElement(DOT);
+    svg.querySelectorAll('g.node').forEach(function (g) {
+      var page = PAGES[g.querySelector('title').textContent];
+      if (page) { g.addEventListener('click', function () { location.href = page; }); }
+    });
+    document.getElementById('_graph').appendChild(svg);
+  });
+});
+";
+
+// The page of each concept, relative to the site's root. The index's
+// own element ids start with underscores, which slugs never do, so that
+// "index.html#slug" links to the concept in the list there, too.
+fn page(c: &Concept) -> String {
+    format!("concepts/{}.html", c.slug)
+}
+
+// A value for a script, as JSON that can't close the script element.
+fn script_json(value: &serde_json::Value) -> String {
+    value.to_string().replace("</", "<\\/")
+}
+
+fn head(title: &str, root: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{}style.css\">\n",
+        escape(title),
+        root
+    )
+}
+
+fn concept_links(s: &Schedule, concepts: &[&Concept]) -> String {
+    if concepts.is_empty() {
+        return String::from("<p>None.</p>\n");
+    }
+    let mut out = String::from("<ul>\n");
+    for c in concepts {
+        out.push_str(&format!(
+            "<li><a href=\"../{}\">{}</a> <span class=\"meta\">({}, week {})</span></li>\n",
+            page(c),
+            escape(&c.concept),
+            escape(category_label(&c.category)),
+            s.week(c)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn index(m: &ConceptMap, opts: &RenderOpts) -> String {
+    let colors = m.category_colors();
+    let concepts = serde_json::Value::Array(
+        m.concepts
+            .iter()
+            .map(|c| {
+                let text = std::iter::once(&c.concept)
+                    .chain(&c.aliases)
+                    .chain(std::iter::once(&c.description))
+                    .map(|s| s.to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                serde_json::json!({
+                    "name": c.concept,
+                    "category": category_label(&c.category),
+                    "page": page(c),
+                    "text": text,
+                })
+            })
+            .collect(),
+    );
+    let pages: serde_json::Map<String, serde_json::Value> = m
+        .concepts
+        .iter()
+        .map(|c| (c.node_id.clone(), serde_json::Value::from(page(c))))
+        .collect();
+    let mut out = head("Concept map", "");
+
+    out.push_str(&format!("<script>\nvar CONCEPTS = {};\nvar PAGES = {};\nvar DOT = {};\n{}</script>\n<script src=\"https://cdn.jsdelivr.net/npm/@viz-js/viz@3/lib/viz-standalone.js\"></script>\n</head>\n<body>\n<main>\n",
+        script_json(&concepts),
+        script_json(&serde_json::Value::Object(pages)),
+        script_json(&serde_json::Value::from(m.render(opts))),
+        SCRIPT
+    ));
+    out.push_str(&format!(
+        "<h1>Concept map</h1>\n<p>{}; lecture {}, lab {}, HW {}.</p>\n",
+        plural(m.concepts.len(), "concept", "concepts"),
+        m.weight_label(0, m.total_weights[0]),
+        m.weight_label(1, m.total_weights[1]),
+        m.weight_label(2, m.total_weights[2])
+    ));
+    out.push_str("<label for=\"_search\">Search the concepts:</label><br>\n<input id=\"_search\" type=\"search\" oninput=\"search(this.value)\">\n<ul id=\"_results\" aria-live=\"polite\"></ul>\n<div id=\"_graph\" role=\"img\" aria-label=\"The concepts' dependency graph\"></div>\n");
+    out.push_str("<h2>Concepts</h2>\n");
+    for cat in m.categories() {
+        out.push_str(&format!(
+            "<section class=\"category\" style=\"border-color: {}\">\n<h3>{}</h3>\n<ul>\n",
+            css_color(&colors[cat]),
+            escape(category_label(cat))
+        ));
+        for c in m.concepts.iter().filter(|c| &c.category == cat) {
+            out.push_str(&format!(
+                "<li id=\"{}\"><a href=\"{}\">{}</a></li>\n",
+                c.slug,
+                page(c),
+                escape(&c.concept)
+            ));
+        }
+        out.push_str("</ul>\n</section>\n");
+    }
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn concept_page(m: &ConceptMap, s: &Schedule, c: &Concept) -> String {
+    let dependencies: Vec<&Concept> = c
+        .dependencies
+        .iter()
+        .map(|d| m.dependency_to_concept(d))
+        .collect();
+    let dependents: Vec<&Concept> = m
+        .concepts
+        .iter()
+        .filter(|d| d.dependencies.contains(&c.concept))
+        .collect();
+    let mut out = head(&c.concept, "../");
+
+    out.push_str(&format!("</head>\n<body>\n<nav><a href=\"../index.html\">Concept map</a></nav>\n<main>\n<h1>{}</h1>\n<p class=\"meta\">{}, week {}; lecture {}, lab {}, HW {}</p>\n",
+        escape(&c.concept),
+        escape(category_label(&c.category)),
+        s.week(c),
+        m.weight_label(0, c.modes[0].weight),
+        m.weight_label(1, c.modes[1].weight),
+        m.weight_label(2, c.modes[2].weight)
+    ));
+    if !c.aliases.is_empty() {
+        let aliases: Vec<String> = c.aliases.iter().map(|a| escape(a)).collect();
+        out.push_str(&format!("<p>Also known as {}.</p>\n", aliases.join(", ")));
+    }
+    if !c.description.is_empty() {
+        out.push_str(&format!("<p>{}</p>\n", escape(&c.description)));
+    }
+    if let Some(url) = &c.url {
+        out.push_str(&format!(
+            "<p><a href=\"{}\">Material</a></p>\n",
+            escape(url)
+        ));
+    }
+    out.push_str("<h2>Builds on</h2>\n");
+    out.push_str(&concept_links(s, &dependencies));
+    out.push_str("<h2>Needed for</h2>\n");
+    out.push_str(&concept_links(s, &dependents));
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+/// Write the site into `dir`. Requires the map to be solved.
+pub fn write(m: &ConceptMap, opts: &RenderOpts, dir: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(dir.join("concepts"))?;
+    fs::write(dir.join("style.css"), STYLE)?;
+    fs::write(dir.join("index.html"), index(m, opts))?;
+    let s = Schedule::new(m);
+    for c in &m.concepts {
+        fs::write(dir.join(page(c)), concept_page(m, &s, c))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser as _;
+
+    #[test]
+    fn pages_link_dependencies_and_dependents() {
+        let dir = std::env::temp_dir().join(format!("concept_map_site_{}", std::process::id()));
+        let m = crate::test_map(
+            "concept,dependencies,category,aliases,description,url,lecture weight
+a <b>,,x,ab;A,</script> tags,https://e.org/?a&b,1
+c,a <b>,,,,,0.5
+",
+        );
+        write(&m, &crate::Cli::parse_from(["concept_map"]).render, &dir).unwrap();
+        let index = fs::read_to_string(dir.join("index.html")).unwrap();
+        let a = fs::read_to_string(dir.join("concepts/a-b.html")).unwrap();
+        let c = fs::read_to_string(dir.join("concepts/c.html")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(index.contains("var CONCEPTS = [{\"category\":\"x\",\"name\":\"a <b>\",\"page\":\"concepts/a-b.html\",\"text\":\"a <b>\\nab\\na\\n<\\/script> tags\"},"), "{}", index);
+        assert!(
+            index.contains(
+                "var PAGES = {\"n0_a_b\":\"concepts/a-b.html\",\"n1_c\":\"concepts/c.html\"};\n"
+            ),
+            "{}",
+            index
+        );
+        assert!(index.contains("<h3>(uncategorized)</h3>\n<ul>\n<li id=\"c\"><a href=\"concepts/c.html\">c</a></li>\n"), "{}", index);
+        assert!(a.contains("<p class=\"meta\">x, week 1; lecture 1.00 weeks, lab 0.00 weeks, HW 0.00 weeks</p>\n<p>Also known as ab, A.</p>\n<p>&lt;/script&gt; tags</p>\n<p><a href=\"https://e.org/?a&amp;b\">Material</a></p>\n<h2>Builds on</h2>\n<p>None.</p>\n<h2>Needed for</h2>\n<ul>\n<li><a href=\"../concepts/c.html\">c</a> <span class=\"meta\">((uncategorized), week 2)</span></li>\n"), "{}", a);
+        assert!(c.contains("<h2>Builds on</h2>\n<ul>\n<li><a href=\"../concepts/a-b.html\">a &lt;b&gt;</a> <span class=\"meta\">(x, week 1)</span></li>\n</ul>\n<h2>Needed for</h2>\n<p>None.</p>\n"), "{}", c);
+        assert_eq!(script_json(&serde_json::Value::from("</b>")), "\"<\\/b>\"");
+    }
+}
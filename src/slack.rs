@@ -0,0 +1,90 @@
+// The slack report: concepts sorted by how far their lectures can
+// slip (latest minus earliest start), so it's clear which topics can
+// absorb a snow day, and which can't slip at all.
+
+use crate::{category_label, hundredths, ConceptMap};
+
+// Slack within rounding of zero is none at all.
+const EPSILON: f64 = 1e-9;
+
+/// Warnings for the concepts with negative slack, whose lectures can't
+/// start early enough to fit. Requires the map to be solved.
+pub fn check(m: &ConceptMap) -> String {
+    let mut warnings = String::new();
+
+    for c in m
+        .concepts
+        .iter()
+        .filter(|c| c.is_taught() && c.slack() < -EPSILON)
+    {
+        warnings.push_str(&format!(
+            "- \"{}\" on {} can't be taught in time: its lecture can't start before {:.2} weeks, but has to by {:.2} weeks.\n",
+            c.concept,
+            c.location,
+            c.modes[0].range.earliest_start,
+            c.latest_start()
+        ));
+    }
+    warnings
+}
+
+pub fn report(m: &ConceptMap) -> String {
+    let mut concepts: Vec<_> = m.concepts.iter().filter(|c| c.is_taught()).collect();
+    // Earliest starts are only summed to within floating-point noise,
+    // which would otherwise order the ties differently from run to run.
+    concepts.sort_by(|a, b| {
+        a.slack()
+            .total_cmp(&b.slack())
+            .then(
+                hundredths(a.modes[0].range.earliest_start)
+                    .total_cmp(&hundredths(b.modes[0].range.earliest_start)),
+            )
+            .then(a.offset.cmp(&b.offset))
+    });
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "Lecture slack in a {:.2}-week course, in weeks:\n",
+        m.course_weeks()
+    ));
+    out.push_str(&format!(
+        "{:>8} {:>8} {:>8}  concept\n",
+        "slack", "earliest", "latest"
+    ));
+    for c in concepts {
+        let note = if c.slack() < -EPSILON {
+            " (infeasible)"
+        } else if c.slack() < EPSILON {
+            " (cannot slip)"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "{:>8.2} {:>8.2} {:>8.2}  {} [{}]{}\n",
+            c.slack(),
+            c.modes[0].range.earliest_start,
+            c.latest_start(),
+            c.concept,
+            category_label(&c.category),
+            note
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn critical_path_slack_isnt_negative() {
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight\na,,0.1\nb,a,0.2\nc,b,0.3\nd,c,0.7\n",
+        );
+        let out = report(&m);
+
+        assert!(!out.contains("-0.00"), "{}", out);
+        assert_eq!(out.matches("(cannot slip)").count(), 4);
+    }
+}
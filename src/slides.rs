@@ -0,0 +1,124 @@
+// Slide deck skeletons, to start the semester's slides from: a section
+// per week of the schedule, and a slide per concept taught in it, in
+// dependency order, with its description and what it builds on. As a
+// reveal.js page (loading reveal.js from a CDN), or as a Beamer
+// document.
+
+use crate::html::escape;
+use crate::schedule::Schedule;
+use crate::tikz::latex_escape;
+use crate::{Concept, ConceptMap};
+
+// Each week with concepts, and its concepts. Requires the map to be
+// solved.
+fn weeks(m: &ConceptMap) -> Vec<(u64, Vec<&Concept>)> {
+    let s = Schedule::new(m);
+
+    (1..=s.nweeks())
+        .map(|week| {
+            let concepts: Vec<&Concept> = m
+                .dependency_order
+                .iter()
+                .map(|n| m.dependency_to_concept(n))
+                .filter(|c| c.is_taught() && !c.kind.is_milestone() && s.week(c) == week)
+                .collect();
+            (week, concepts)
+        })
+        .filter(|(_, concepts)| !concepts.is_empty())
+        .collect()
+}
+
+pub fn reveal(m: &ConceptMap) -> String {
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Slides</title>\n<link rel=\"stylesheet\" href=\"https://cdn.jsdelivr.net/npm/reveal.js@5/dist/reveal.css\">\n<link rel=\"stylesheet\" href=\"https://cdn.jsdelivr.net/npm/reveal.js@5/dist/theme/white.css\">\n</head>\n<body>\n<div class=\"reveal\">\n<div class=\"slides\">\n");
+    for (week, concepts) in weeks(m) {
+        out.push_str(&format!(
+            "<section>\n<section>\n<h1>Week {}</h1>\n</section>\n",
+            week
+        ));
+        for c in concepts {
+            out.push_str(&format!("<section>\n<h2>{}</h2>\n", escape(&c.concept)));
+            if !c.description.is_empty() {
+                out.push_str(&format!("<p>{}</p>\n", escape(&c.description)));
+            }
+            if !c.dependencies.is_empty() {
+                out.push_str("<p>Builds on:</p>\n<ul>\n");
+                for d in &c.dependencies {
+                    out.push_str(&format!(
+                        "<li>{}</li>\n",
+                        escape(&m.dependency_to_concept(d).concept)
+                    ));
+                }
+                out.push_str("</ul>\n");
+            }
+            out.push_str("</section>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</div>\n</div>\n<script src=\"https://cdn.jsdelivr.net/npm/reveal.js@5/dist/reveal.js\"></script>\n<script>Reveal.initialize();</script>\n</body>\n</html>\n");
+    out
+}
+
+pub fn beamer(m: &ConceptMap) -> String {
+    let mut out =
+        String::from("% Generated by concept_map\n\\documentclass{beamer}\n\\begin{document}\n");
+
+    for (week, concepts) in weeks(m) {
+        out.push_str(&format!("\n\\section{{Week {}}}\n", week));
+        for c in concepts {
+            out.push_str(&format!(
+                "\n\\begin{{frame}}{{{}}}\n",
+                latex_escape(&c.concept)
+            ));
+            if !c.description.is_empty() {
+                out.push_str(&format!("  {}\n", latex_escape(&c.description)));
+            }
+            if !c.dependencies.is_empty() {
+                out.push_str("\n  Builds on:\n  \\begin{itemize}\n");
+                for d in &c.dependencies {
+                    out.push_str(&format!(
+                        "    \\item {}\n",
+                        latex_escape(&m.dependency_to_concept(d).concept)
+                    ));
+                }
+                out.push_str("  \\end{itemize}\n");
+            }
+            out.push_str("\\end{frame}\n");
+        }
+    }
+    out.push_str("\n\\end{document}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK: &str = "concept,dependencies,description,lecture weight,type
+a & b,,First <steps>,1,
+c,a & b,,1,
+midterm,c,,0,exam
+";
+
+    #[test]
+    fn weeks_are_sections_of_concept_slides() {
+        let html = reveal(&crate::test_map(DECK));
+
+        assert!(html.contains("<section>\n<section>\n<h1>Week 1</h1>\n</section>\n<section>\n<h2>a &amp; b</h2>\n<p>First &lt;steps&gt;</p>\n</section>\n</section>\n"), "{}", html);
+        assert!(
+            html.contains("<h2>c</h2>\n<p>Builds on:</p>\n<ul>\n<li>a &amp; b</li>\n</ul>\n"),
+            "{}",
+            html
+        );
+        assert!(!html.contains("midterm"), "{}", html);
+    }
+
+    #[test]
+    fn beamer_frames_are_escaped() {
+        let tex = beamer(&crate::test_map(DECK));
+
+        assert!(tex.contains("\n\\section{Week 2}\n\n\\begin{frame}{c}\n\n  Builds on:\n  \\begin{itemize}\n    \\item a \\& b\n  \\end{itemize}\n\\end{frame}\n"), "{}", tex);
+        assert!(tex.ends_with("\n\\end{document}\n"), "{}", tex);
+    }
+}
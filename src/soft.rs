@@ -0,0 +1,77 @@
+// The advisory report on soft dependencies ("recursion?"): those that
+// would help with a concept, but aren't required, so they don't hold
+// up its schedule. It lists whether the schedule happens to teach each
+// one first anyway, so that those it doesn't can be moved up, or
+// mentioned in passing.
+
+use crate::schedule::Schedule;
+use crate::{plural, ConceptMap};
+
+/// Each soft dependency, and the weeks it and its concept are taught
+/// in. Requires the map to be solved.
+pub fn report(m: &ConceptMap) -> String {
+    let s = Schedule::new(m);
+    let mut out = String::new();
+    let (mut total, mut later) = (0, 0);
+
+    for c in &m.concepts {
+        for d in &c.soft_dependencies {
+            let d = m.dependency_to_concept(d);
+            let (week, dep_week) = (s.week(c), s.week(d));
+            let when = if dep_week < week {
+                "before"
+            } else if dep_week == week {
+                "the same week as"
+            } else {
+                later += 1;
+                "after"
+            };
+            total += 1;
+
+            out.push_str(&format!(
+                "- \"{}\" would help with \"{}\": taught in week {}, {} it (week {}).\n",
+                d.concept, c.concept, dep_week, when, week
+            ));
+            if let Some(note) = c.dependency_notes.get(&d.concept) {
+                out.push_str(&format!("  {}\n", note));
+            }
+        }
+    }
+    if total == 0 {
+        return String::from("No soft dependencies.\n");
+    }
+    format!(
+        "{}, {} taught after the concepts they'd help with:\n{}",
+        plural(total, "soft dependency", "soft dependencies"),
+        later,
+        out
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_dependencies_dont_hold_up_their_concepts() {
+        let m = crate::test_map(
+            "concept,dependencies,lecture weight
+a,,1
+b,a;c?,1
+c,b,1
+d,a?;c,1
+",
+        );
+
+        assert_eq!(
+            report(&m),
+            "2 soft dependencies, 1 taught after the concepts they'd help with:\n\
+             - \"c\" would help with \"b\": taught in week 3, after it (week 2).\n\
+             - \"a\" would help with \"d\": taught in week 1, before it (week 4).\n"
+        );
+        assert_eq!(
+            report(&crate::test_map("concept,dependencies\na,\n")),
+            "No soft dependencies.\n"
+        );
+    }
+}
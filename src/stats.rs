@@ -0,0 +1,549 @@
+// Statistics about the concept map, as a text report.
+
+use crate::{category_label, degree, plural, Concept, ConceptMap, Status, MODE_NAMES};
+use clap::Args;
+use std::path::PathBuf;
+
+const BINS: usize = 10;
+const BAR_WIDTH: usize = 40;
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Also write the weight histograms as an SVG image
+    #[arg(long, value_name = "FILE")]
+    pub histogram_svg: Option<PathBuf>,
+    /// Also write the in- and out-degree distributions, and the
+    /// density, as csv
+    #[arg(long, value_name = "FILE")]
+    pub degrees_csv: Option<PathBuf>,
+    /// Also list the roots, the concepts without prerequisites, where
+    /// the course starts, and the leaves, those nothing depends on,
+    /// where it ends
+    #[arg(long)]
+    pub roots_and_leaves: bool,
+}
+
+// Percentile `p` (0-100) of sorted values, interpolating between the
+// closest ranks.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+// The distribution of one modality's weights: zero weights get their
+// own bucket so that they stand out, and the rest are split into
+// equal-width bins up to the maximum.
+struct Distribution {
+    sorted: Vec<f64>,
+    zeros: usize,
+    bins: [usize; BINS],
+    bin_width: f64,
+}
+
+impl Distribution {
+    fn new(m: &ConceptMap, mode: usize) -> Self {
+        let mut sorted: Vec<f64> = m.concepts.iter().map(|c| c.modes[mode].weight).collect();
+        sorted.sort_by(f64::total_cmp);
+        let max = sorted.last().copied().unwrap_or(0.0);
+        let bin_width = if max > 0.0 { max / BINS as f64 } else { 1.0 };
+        let mut bins = [0; BINS];
+        let mut zeros = 0;
+
+        for &w in &sorted {
+            if w <= 0.0 {
+                zeros += 1;
+            } else {
+                bins[((w / bin_width).ceil() as usize).clamp(1, BINS) - 1] += 1;
+            }
+        }
+
+        Distribution {
+            sorted,
+            zeros,
+            bins,
+            bin_width,
+        }
+    }
+
+    fn p(&self, p: f64) -> f64 {
+        percentile(&self.sorted, p)
+    }
+
+    // Tukey's fences: more than 1.5 interquartile ranges above the
+    // upper quartile. Zero weights are already called out, and would
+    // otherwise make every weighted concept an outlier in sparsely
+    // used modalities.
+    fn outlier_threshold(&self) -> f64 {
+        let nonzero = &self.sorted[self.zeros..];
+        let (q1, q3) = (percentile(nonzero, 25.0), percentile(nonzero, 75.0));
+
+        q3 + 1.5 * (q3 - q1)
+    }
+}
+
+fn weight_distributions(m: &ConceptMap, out: &mut String) {
+    for (mode, name) in MODE_NAMES.iter().enumerate() {
+        let d = Distribution::new(m, mode);
+        let most = d
+            .bins
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(d.zeros)
+            .max(1);
+        let bar = |n: usize| "#".repeat((n * BAR_WIDTH).div_ceil(most));
+
+        out.push_str(&format!(
+            "\n{} weights ({}, {} with zero weight):\n",
+            name,
+            plural(d.sorted.len(), "concept", "concepts"),
+            d.zeros
+        ));
+        out.push_str(&format!(
+            "  min {:.2}, p25 {:.2}, median {:.2}, p75 {:.2}, p90 {:.2}, max {:.2}\n",
+            d.p(0.0),
+            d.p(25.0),
+            d.p(50.0),
+            d.p(75.0),
+            d.p(90.0),
+            d.p(100.0)
+        ));
+        out.push_str(&format!("  {:>13} | {} {}\n", "0", bar(d.zeros), d.zeros));
+        for (i, &n) in d.bins.iter().enumerate() {
+            let range = format!(
+                "{:.2}-{:.2}",
+                i as f64 * d.bin_width,
+                (i + 1) as f64 * d.bin_width
+            );
+            out.push_str(&format!("  {:>13} | {} {}\n", range, bar(n), n));
+        }
+
+        let threshold = d.outlier_threshold();
+        let outliers: Vec<String> = m
+            .concepts
+            .iter()
+            .filter(|c| c.modes[mode].weight > threshold)
+            .map(|c| format!("{} ({:.2})", c.concept, c.modes[mode].weight))
+            .collect();
+        if !outliers.is_empty() {
+            out.push_str(&format!(
+                "  Outliers (over {:.2}): {}\n",
+                threshold,
+                outliers.join(", ")
+            ));
+        }
+    }
+}
+
+fn category_weights(m: &ConceptMap, out: &mut String) {
+    let cats = m.categories();
+    let width = cats
+        .iter()
+        .map(|c| category_label(c).chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("category".len());
+
+    if m.units.percent_of_course {
+        out.push_str("\nWeights by category, in percent of the course:\n");
+    } else {
+        out.push_str("\nWeights by category, in weeks:\n");
+    }
+    out.push_str(&format!(
+        "  {:<width$} {:>8} {:>8} {:>8} {:>8}\n",
+        "category",
+        "concepts",
+        "lecture",
+        "lab",
+        "HW",
+        width = width
+    ));
+    for cat in cats {
+        let ws = m.category_weights(cat);
+        let n = m.concepts.iter().filter(|c| &c.category == cat).count();
+
+        out.push_str(&format!(
+            "  {:<width$} {:>8} {:>8.2} {:>8.2} {:>8.2}\n",
+            category_label(cat),
+            n,
+            m.shown_weight(0, ws[0]),
+            m.shown_weight(1, ws[1]),
+            m.shown_weight(2, ws[2]),
+            width = width
+        ));
+    }
+    out.push_str(&format!(
+        "  {:<width$} {:>8} {:>8.2} {:>8.2} {:>8.2}\n",
+        "total",
+        m.concepts.len(),
+        m.shown_weight(0, m.total_weights[0]),
+        m.shown_weight(1, m.total_weights[1]),
+        m.shown_weight(2, m.total_weights[2]),
+        width = width
+    ));
+    if let Some(legend) = m.units.legend() {
+        out.push_str(&format!(
+            "  {}\n  Lecture {}, lab {}, HW {}.\n",
+            legend,
+            m.units.weeks(m.total_weights[0]),
+            m.units.weeks(m.total_weights[1]),
+            m.units.weeks(m.total_weights[2])
+        ));
+    }
+}
+
+// The roll-up of the unit and topic hierarchy, if the csv has one: each
+// level's concepts and weights, and its coverage, the share of the
+// course's total weight (over all modalities) it accounts for.
+fn hierarchy_weights(m: &ConceptMap, out: &mut String) {
+    if !m.has_hierarchy() {
+        return;
+    }
+    let label = |name: &str, none: &str| {
+        if name.is_empty() {
+            none.to_string()
+        } else {
+            name.to_string()
+        }
+    };
+    let mut rows = Vec::new(); // (indented name, concepts, weights)
+    for unit in m.units() {
+        let in_unit = |c: &Concept| &c.unit == unit;
+        let n = m.concepts.iter().filter(|c| in_unit(c)).count();
+        rows.push((label(unit, "(no unit)"), n, m.weights_where(in_unit)));
+        let topics = m.topics(unit);
+        if topics.iter().all(|t| t.is_empty()) {
+            continue;
+        }
+        for topic in topics {
+            let in_topic = |c: &Concept| in_unit(c) && &c.topic == topic;
+            let n = m.concepts.iter().filter(|c| in_topic(c)).count();
+            rows.push((
+                format!("  {}", label(topic, "(no topic)")),
+                n,
+                m.weights_where(in_topic),
+            ));
+        }
+    }
+    let width = rows
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("unit / topic".len());
+    let total: f64 = m.total_weights.iter().sum();
+
+    if m.units.percent_of_course {
+        out.push_str("\nWeights by unit and topic, in percent of the course:\n");
+    } else {
+        out.push_str("\nWeights by unit and topic, in weeks:\n");
+    }
+    out.push_str(&format!(
+        "  {:<width$} {:>8} {:>8} {:>8} {:>8} {:>8}\n",
+        "unit / topic",
+        "concepts",
+        "lecture",
+        "lab",
+        "HW",
+        "coverage",
+        width = width
+    ));
+    for (name, n, ws) in rows {
+        let coverage = if total > 0.0 {
+            ws.iter().sum::<f64>() / total * 100.0
+        } else {
+            0.0
+        };
+        out.push_str(&format!(
+            "  {:<width$} {:>8} {:>8.2} {:>8.2} {:>8.2} {:>7.1}%\n",
+            name,
+            n,
+            m.shown_weight(0, ws[0]),
+            m.shown_weight(1, ws[1]),
+            m.shown_weight(2, ws[2]),
+            coverage,
+            width = width
+        ));
+    }
+}
+
+// How many of each category's (unretired) concepts are ready, if the
+// csv has statuses.
+fn readiness(m: &ConceptMap, out: &mut String) {
+    if m.concepts.iter().all(|c| c.status == Status::Ready) {
+        return;
+    }
+
+    out.push_str("\nReadiness by category:\n");
+    for cat in m.categories() {
+        let current: Vec<_> = m
+            .concepts
+            .iter()
+            .filter(|c| &c.category == cat && c.status != Status::Retired)
+            .collect();
+        let ready = current.iter().filter(|c| c.status == Status::Ready).count();
+        let percent = if current.is_empty() {
+            100.0
+        } else {
+            ready as f64 / current.len() as f64 * 100.0
+        };
+
+        out.push_str(&format!(
+            "  {}: {} of {} ready ({:.0}%)\n",
+            category_label(cat),
+            ready,
+            current.len(),
+            percent
+        ));
+    }
+}
+
+pub fn report(m: &ConceptMap) -> String {
+    let mut out = String::new();
+
+    out.push_str(&format!("Concepts: {}\n", m.concepts.len()));
+    out.push_str(&format!(
+        "Dependencies: {} (density {:.3})\n",
+        degree::edges(m),
+        degree::density(m)
+    ));
+    structure(m, &mut out);
+    category_weights(m, &mut out);
+    hierarchy_weights(m, &mut out);
+    readiness(m, &mut out);
+    weight_distributions(m, &mut out);
+
+    out
+}
+
+// The concepts with the most of something, and how many that is.
+fn most(m: &ConceptMap, count: impl Fn(&Concept) -> usize) -> (usize, Vec<&Concept>) {
+    let max = m.concepts.iter().map(&count).max().unwrap_or(0);
+    let concepts = m.concepts.iter().filter(|c| count(c) == max).collect();
+
+    (max, concepts)
+}
+
+// The shape of the graph: how many dependencies concepts have, how
+// long its longest chain of them is, and which concepts have the most
+// dependencies (fan-out) and the most dependents (fan-in).
+fn structure(m: &ConceptMap, out: &mut String) {
+    if m.concepts.is_empty() {
+        return;
+    }
+    let dependents = m.dependents();
+    let (nout, fan_out) = most(m, |c| c.dependencies.len());
+    let (nin, fan_in) = most(m, |c| dependents[c.offset].len());
+
+    out.push_str(&format!(
+        "Dependencies per concept: {:.2} on average\n",
+        degree::edges(m) as f64 / m.concepts.len() as f64
+    ));
+    out.push_str(&format!(
+        "Depth: {} (the longest chain of dependencies)\n",
+        m.depths().into_iter().max().unwrap_or(0)
+    ));
+    out.push_str(&format!(
+        "Most direct dependencies ({}): {}\n",
+        nout,
+        names(&fan_out)
+    ));
+    out.push_str(&format!(
+        "Most direct dependents ({}): {}\n",
+        nin,
+        names(&fan_in)
+    ));
+}
+
+fn names(concepts: &[&Concept]) -> String {
+    if concepts.is_empty() {
+        return String::from("none");
+    }
+    concepts
+        .iter()
+        .map(|c| format!("\"{}\"", c.concept))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The concepts without dependencies, and those without dependents,
+/// other than milestones, which only mark where the course is.
+pub fn roots_and_leaves(m: &ConceptMap) -> String {
+    let dependents = m.dependents();
+    let concepts = || m.concepts.iter().filter(|c| !c.kind.is_milestone());
+    let roots: Vec<&Concept> = concepts().filter(|c| c.dependencies.is_empty()).collect();
+    let leaves: Vec<&Concept> = concepts()
+        .filter(|c| {
+            dependents[c.offset]
+                .iter()
+                .all(|&d| m.concepts[d].kind.is_milestone())
+        })
+        .collect();
+
+    format!(
+        "\nRoots ({}): {}\nLeaves ({}): {}\n",
+        roots.len(),
+        names(&roots),
+        leaves.len(),
+        names(&leaves)
+    )
+}
+
+/// The weight histograms as an SVG image, one panel per modality.
+pub fn histogram_svg(m: &ConceptMap) -> String {
+    const PANEL_W: f64 = 320.0;
+    const PANEL_H: f64 = 220.0;
+    const PLOT_H: f64 = 150.0;
+    let bar_w = (PANEL_W - 40.0) / (BINS + 1) as f64;
+    let mut svg = String::new();
+
+    svg.push_str(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"10\" role=\"img\" aria-labelledby=\"histogram-title histogram-desc\">\n",
+        PANEL_W * 3.0,
+        PANEL_H
+    ));
+    let zeros: Vec<String> = ["lecture", "lab", "HW"]
+        .iter()
+        .enumerate()
+        .map(|(mode, name)| {
+            format!(
+                "{} without {} weight",
+                plural(Distribution::new(m, mode).zeros, "concept", "concepts"),
+                name
+            )
+        })
+        .collect();
+    svg.push_str(&format!("<title id=\"histogram-title\">Concept weight histograms</title>\n<desc id=\"histogram-desc\">Bars of how many of the {} have each lecture, lab, and HW weight, in {} bins, with the concepts without the weight first: {}.</desc>\n",
+        plural(m.concepts.len(), "concept", "concepts"),
+        BINS,
+        zeros.join(", ")
+    ));
+    for (mode, name) in MODE_NAMES.iter().enumerate() {
+        let d = Distribution::new(m, mode);
+        let counts: Vec<usize> = std::iter::once(d.zeros)
+            .chain(d.bins.iter().copied())
+            .collect();
+        let most = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+        let x0 = mode as f64 * PANEL_W + 20.0;
+
+        svg.push_str(&format!("<text x=\"{}\" y=\"16\" font-size=\"13\">{} weights (median {:.2}, p90 {:.2})</text>\n",
+            x0,
+            name,
+            d.p(50.0),
+            d.p(90.0)
+        ));
+        for (i, &n) in counts.iter().enumerate() {
+            let h = n as f64 / most * PLOT_H;
+            let x = x0 + i as f64 * bar_w;
+            let y = 30.0 + PLOT_H - h;
+            let label = if i == 0 {
+                String::from("0")
+            } else {
+                format!("{:.2}", i as f64 * d.bin_width)
+            };
+            // zero-weight concepts get a warning color
+            let color = if i == 0 { "#e4572e" } else { "#4e79a7" };
+
+            svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}</title></rect>\n",
+                x,
+                y,
+                bar_w - 2.0,
+                h,
+                color,
+                plural(n, "concept", "concepts")
+            ));
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+                x + bar_w / 2.0,
+                y - 3.0,
+                n
+            ));
+            svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" transform=\"rotate(-45 {:.1} {:.1})\">{}</text>\n",
+                x + bar_w / 2.0,
+                PLOT_H + 45.0,
+                x + bar_w / 2.0,
+                PLOT_H + 45.0,
+                label
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPREAD: &str = "concept,dependencies,lecture weight,lab weight
+a,,0.5,
+b,a,1,
+c,b,1,1
+d,c,1.5,
+e,,5,
+";
+
+    #[test]
+    fn percentiles_interpolate_between_ranks() {
+        let sorted = [1.0, 2.0, 4.0, 8.0];
+
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 50.0), 3.0);
+        assert_eq!(percentile(&sorted, 100.0), 8.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn zero_weights_get_their_own_bucket() {
+        let m = crate::test_map(SPREAD);
+        let lecture = Distribution::new(&m, 0);
+        let lab = Distribution::new(&m, 1);
+
+        assert_eq!(
+            (lecture.zeros, lecture.bins),
+            (0, [1, 2, 1, 0, 0, 0, 0, 0, 0, 1])
+        );
+        assert_eq!(lecture.outlier_threshold(), 2.25);
+        assert_eq!((lab.zeros, lab.bins[BINS - 1]), (4, 1));
+        assert!(report(&m).contains("\nLecture weights (5 concepts, 0 with zero weight):\n  min 0.50, p25 1.00, median 1.00, p75 1.50, p90 3.60, max 5.00\n"));
+    }
+
+    #[test]
+    fn roots_and_leaves_leave_out_milestones() {
+        let m = crate::test_map(
+            "concept,dependencies,type
+a,,
+b,a,
+d,b,
+e,,
+final,d,exam
+",
+        );
+
+        assert_eq!(
+            roots_and_leaves(&m),
+            "\nRoots (2): \"a\", \"e\"\nLeaves (2): \"d\", \"e\"\n"
+        );
+    }
+
+    #[test]
+    fn histograms_are_labeled_images() {
+        let svg = histogram_svg(&crate::test_map(SPREAD));
+
+        assert!(svg.starts_with("<svg "), "{}", svg);
+        assert!(
+            svg.contains(r#"role="img" aria-labelledby="histogram-title histogram-desc""#),
+            "{}",
+            svg
+        );
+        assert!(svg.contains("<title id=\"histogram-title\">"), "{}", svg);
+        assert!(svg.contains("0 concepts without lecture weight, 4 concepts without lab weight, 5 concepts without HW weight."), "{}", svg);
+        assert!(svg.ends_with("</svg>\n"), "{}", svg);
+    }
+}
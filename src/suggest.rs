@@ -0,0 +1,194 @@
+// Category suggestions for concepts that have a blank category.  We
+// run label propagation on the undirected projection of the
+// dependency graph: concepts with a category keep it, and seed the
+// propagation, while uncategorized concepts repeatedly adopt the most
+// common label among their neighbors (both their dependencies, and
+// their dependents).
+
+use crate::{normalize, ConceptMap, ConceptName};
+use csv::StringRecord;
+use std::collections::HashMap;
+use std::io;
+
+// Label propagation usually settles in a handful of rounds, but can
+// oscillate between tied labels, so bound the iterations.
+const MAX_ROUNDS: usize = 100;
+
+const PROPOSED_HEADER: &str = "proposed category";
+
+/// Compute a category for each concept. Concepts that already have a
+/// category keep it. Groups of uncategorized concepts that aren't
+/// pulled into an existing category are named after their first
+/// concept (in csv order).
+pub fn propose(m: &ConceptMap) -> HashMap<ConceptName, String> {
+    let n = m.concepts.len();
+    let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); n];
+
+    for c in &m.concepts {
+        for d in &c.dependencies {
+            let dep = m.dependency_to_concept(d).offset;
+
+            adjacent[c.offset].push(dep);
+            adjacent[dep].push(c.offset);
+        }
+    }
+
+    // Each existing category gets a label, and each uncategorized
+    // concept starts out in its own community.
+    let mut names: Vec<Option<String>> = Vec::new();
+    let mut category_labels: HashMap<&String, usize> = HashMap::new();
+    let mut labels: Vec<usize> = Vec::new();
+    let mut fixed: Vec<bool> = Vec::new();
+    for c in &m.concepts {
+        if c.category.is_empty() {
+            labels.push(names.len());
+            names.push(None);
+            fixed.push(false);
+        } else {
+            let l = *category_labels.entry(&c.category).or_insert_with(|| {
+                names.push(Some(c.category.clone()));
+                names.len() - 1
+            });
+            labels.push(l);
+            fixed.push(true);
+        }
+    }
+
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+
+        for i in 0..n {
+            if fixed[i] || adjacent[i].is_empty() {
+                continue;
+            }
+            let mut counts: HashMap<usize, usize> = HashMap::new();
+            for &a in &adjacent[i] {
+                *counts.entry(labels[a]).or_insert(0) += 1;
+            }
+            let most = *counts.values().max().unwrap(); // non-empty adjacency
+
+            // Prefer to keep our label on ties to avoid oscillation,
+            // otherwise take the lowest label to stay deterministic.
+            let best = if counts.get(&labels[i]) == Some(&most) {
+                labels[i]
+            } else {
+                *counts
+                    .iter()
+                    .filter(|(_, &count)| count == most)
+                    .map(|(l, _)| l)
+                    .min()
+                    .unwrap() // at least one label has the max count
+            };
+            if best != labels[i] {
+                labels[i] = best;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let mut proposals = HashMap::new();
+    for c in &m.concepts {
+        let l = labels[c.offset];
+        let name = names[l]
+            .get_or_insert_with(|| format!("{} group", c.concept))
+            .clone();
+        proposals.insert(c.concept.clone(), name);
+    }
+
+    proposals
+}
+
+/// Write the csv back out, with a `proposed category` column holding
+/// the suggestion for each record (replacing any previous suggestions).
+pub fn write_suggestions<W: io::Write>(
+    m: &ConceptMap,
+    headers: &StringRecord,
+    rows: &[StringRecord],
+    out: W,
+) -> anyhow::Result<()> {
+    let proposals = propose(m);
+    // The headers are already the fields, whatever the csv's locale or
+    // --column mapping named them.
+    let concept_col = headers
+        .iter()
+        .position(|h| h == "concept")
+        .ok_or_else(|| anyhow::anyhow!("The csv is missing the required concept column."))?;
+    let proposed_col = headers.iter().position(|h| h == PROPOSED_HEADER);
+    let mut wtr = csv::Writer::from_writer(out);
+
+    let mut hs = headers.clone();
+    if proposed_col.is_none() {
+        hs.push_field(PROPOSED_HEADER);
+    }
+    wtr.write_record(&hs)?;
+
+    for row in rows {
+        // as the concept is named in the map
+        let name = normalize::name(row.get(concept_col).unwrap_or(""));
+        let proposal = proposals.get(name.trim()).map(|p| p.as_str()).unwrap_or("");
+        let mut r: Vec<&str> = row.iter().collect();
+
+        match proposed_col {
+            Some(col) => r[col] = proposal,
+            None => r.push(proposal),
+        }
+        wtr.write_record(&r)?;
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_map, Cli};
+    use clap::Parser as _;
+
+    // "stack" is pulled into "memory", while "proofs" and "induction"
+    // form a group of their own.
+    const PARTLY: &str = "concept,dependencies,category
+pointers,,memory
+heap,pointers,memory
+stack,pointers;heap,
+proofs,,
+induction,proofs,
+";
+
+    #[test]
+    fn uncategorized_concepts_take_their_neighbors_category() {
+        let (m, _, _) = read_map(&Cli::parse_from(["concept_map"]), PARTLY.as_bytes()).unwrap();
+        let proposals = propose(&m);
+
+        assert_eq!(proposals["stack"], "memory");
+        assert_eq!(proposals["heap"], "memory");
+        assert_eq!(proposals["proofs"], "proofs group");
+        assert_eq!(proposals["induction"], "proofs group");
+    }
+
+    #[test]
+    fn suggestions_are_a_column() {
+        let cli = Cli::parse_from(["concept_map"]);
+        let (m, headers, rows) = read_map(&cli, PARTLY.as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_suggestions(&m, &headers, &rows, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.starts_with(
+                "concept,dependencies,category,proposed category\npointers,,memory,memory\n"
+            ),
+            "{}",
+            out
+        );
+
+        // and its suggestions are replaced
+        let (m, headers, rows) = read_map(&cli, out.as_bytes()).unwrap();
+        let mut again = Vec::new();
+        write_suggestions(&m, &headers, &rows, &mut again).unwrap();
+        assert_eq!(String::from_utf8(again).unwrap(), out);
+    }
+}
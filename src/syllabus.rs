@@ -0,0 +1,130 @@
+// The syllabus, as a Markdown document to start the course's from: a
+// section per week of the schedule, with the concepts taught in it (in
+// dependency order), their categories, and the load so far, the
+// lecture, lab, and HW weight of everything up to and including the
+// week.
+
+use crate::glossary::md_escape;
+use crate::schedule::Schedule;
+use crate::{category_label, ConceptMap};
+use itertools::Itertools;
+
+/// Requires the map to be solved.
+pub fn markdown(m: &ConceptMap) -> String {
+    let s = Schedule::new(m);
+    let mut load = [0.0; 3];
+    let mut out = String::from("# Syllabus\n");
+
+    for week in 1..=s.nweeks() {
+        let concepts: Vec<_> = m
+            .dependency_order
+            .iter()
+            .map(|n| m.dependency_to_concept(n))
+            .filter(|c| c.is_taught() && !c.kind.is_milestone() && s.week(c) == week)
+            .collect();
+        let milestones: Vec<_> = m
+            .concepts
+            .iter()
+            .filter(|c| c.is_taught() && c.kind.is_milestone() && s.week(c) == week)
+            .collect();
+        for c in &concepts {
+            for (l, mode) in load.iter_mut().zip(&c.modes) {
+                *l += mode.weight;
+            }
+        }
+
+        out.push_str(&format!("\n## Week {}\n\n", week));
+        if concepts.is_empty() {
+            out.push_str("No new concepts.\n");
+        } else {
+            let categories = concepts
+                .iter()
+                .map(|c| category_label(&c.category))
+                .unique();
+            out.push_str(&format!(
+                "Categories: {}\n\n",
+                categories.map(md_escape).join(", ")
+            ));
+            for c in &concepts {
+                if c.description.is_empty() {
+                    out.push_str(&format!("- {}\n", md_escape(&c.concept)));
+                } else {
+                    out.push_str(&format!(
+                        "- {}: {}\n",
+                        md_escape(&c.concept),
+                        md_escape(&c.description)
+                    ));
+                }
+            }
+        }
+        for c in milestones {
+            out.push_str(&format!(
+                "\n**{}: {}**\n",
+                c.kind.name(),
+                md_escape(&c.concept)
+            ));
+        }
+        out.push_str(&format!(
+            "\nLoad so far: lecture {}, lab {}, HW {}.\n",
+            m.weight_label(0, load[0]),
+            m.weight_label(1, load[1]),
+            m.weight_label(2, load[2])
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weeks_list_their_concepts_and_the_load_so_far() {
+        let m = crate::test_map(
+            "concept,dependencies,category,description,week,lecture weight,lab weight,type
+a,,x,The *first*,,1,0.5,
+b,a,y,,,1,,
+c,b,,,4,0.5,,
+midterm,a,,,,0,,exam
+",
+        );
+
+        assert_eq!(
+            markdown(&m),
+            "# Syllabus
+
+## Week 1
+
+Categories: x
+
+- a: The \\*first\\*
+
+Load so far: lecture 1.00 weeks, lab 0.50 weeks, HW 0.00 weeks.
+
+## Week 2
+
+Categories: y
+
+- b
+
+**Exam: midterm**
+
+Load so far: lecture 2.00 weeks, lab 0.50 weeks, HW 0.00 weeks.
+
+## Week 3
+
+No new concepts.
+
+Load so far: lecture 2.00 weeks, lab 0.50 weeks, HW 0.00 weeks.
+
+## Week 4
+
+Categories: (uncategorized)
+
+- c
+
+Load so far: lecture 2.50 weeks, lab 0.50 weeks, HW 0.00 weeks.
+"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+// The dependency graph as a TikZ picture, to \input into Beamer slides
+// and the printed syllabus, typeset in the document's own fonts. It
+// only needs the tikz package: concepts are laid out in rows by their
+// depth in the dependency graph, rather than relying on TikZ's graph
+// drawing library (which needs LuaLaTeX).
+
+use crate::html::css_color;
+use crate::ConceptMap;
+
+const COLUMN_CM: f64 = 3.2;
+const ROW_CM: f64 = 1.6;
+
+pub fn latex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for ch in s.chars() {
+        match ch {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\\' => out.push_str("\\textbackslash{}"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+pub fn render(m: &ConceptMap) -> String {
+    let colors = m.category_colors();
+    let cats = m.categories();
+    let depths = m.depths();
+    let mut columns = vec![0; depths.iter().copied().max().unwrap_or(0) + 1];
+    let mut out = String::new();
+
+    out.push_str("% Generated by concept_map; needs \\usepackage{tikz}\n");
+    out.push_str("\\begin{tikzpicture}[\n");
+    out.push_str(&format!("  concept/.style={{draw, rounded corners, align=center, text width={:.1}cm, font=\\small}},\n",
+        COLUMN_CM - 0.4
+    ));
+    out.push_str("  dependency/.style={->, >=stealth, gray}]\n");
+    for (i, cat) in cats.iter().enumerate() {
+        let hex = css_color(colors.get(cat).unwrap()); // unwrap: all categories have colors
+        out.push_str(&format!(
+            "  \\definecolor{{category{}}}{{HTML}}{{{}}}\n",
+            i,
+            hex.trim_start_matches('#').to_uppercase()
+        ));
+    }
+    for c in &m.concepts {
+        let depth = depths[c.offset];
+        let cat = cats.iter().position(|cat| *cat == &c.category).unwrap(); // all categories present
+
+        out.push_str(&format!(
+            "  \\node[concept, fill=category{}!30] (c{}) at ({:.1}cm, {:.1}cm) {{{}}};\n",
+            cat,
+            c.offset,
+            columns[depth] as f64 * COLUMN_CM,
+            -(depth as f64) * ROW_CM,
+            latex_escape(&c.concept)
+        ));
+        columns[depth] += 1;
+    }
+    for c in &m.concepts {
+        for d in &c.dependencies {
+            out.push_str(&format!(
+                "  \\draw[dependency] (c{}) -- (c{});\n",
+                m.dependency_to_concept(d).offset,
+                c.offset
+            ));
+        }
+    }
+    out.push_str("\\end{tikzpicture}\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(
+            latex_escape(r"50% of {x_1} & ~a^b\c"),
+            r"50\% of \{x\_1\} \& \textasciitilde{}a\textasciicircum{}b\textbackslash{}c"
+        );
+    }
+
+    #[test]
+    fn concepts_are_rows_by_depth() {
+        let m = crate::test_map("concept,dependencies,category\na,,x\nb,a,x\nc,a,y\n");
+        let tikz = render(&m);
+
+        assert!(
+            tikz.contains("  \\node[concept, fill=category0!30] (c0) at (0.0cm, -0.0cm) {a};\n"),
+            "{}",
+            tikz
+        );
+        assert!(
+            tikz.contains("  \\node[concept, fill=category1!30] (c2) at (3.2cm, -1.6cm) {c};\n"),
+            "{}",
+            tikz
+        );
+        assert!(
+            tikz.contains("  \\draw[dependency] (c0) -- (c2);\n"),
+            "{}",
+            tikz
+        );
+        assert!(tikz.ends_with("\\end{tikzpicture}\n"), "{}", tikz);
+    }
+}
@@ -0,0 +1,132 @@
+// Typst markup for syllabi written in Typst: the dependency graph as a
+// diagram, and the schedule as a table. Both are bound to variables
+// (`concept-map` and `concept-schedule`) so that a document can
+// `#include` or `#import` the file and place them itself. The diagram
+// only uses Typst's built-in shapes, with the same layered layout as
+// the TikZ export.
+
+use crate::html::css_color;
+use crate::schedule::Schedule;
+use crate::{category_label, ConceptMap};
+
+const COLUMN_CM: f64 = 3.2;
+const ROW_CM: f64 = 1.6;
+const NODE_W_CM: f64 = 2.8;
+const NODE_H_CM: f64 = 1.0;
+
+// A Typst string literal.
+fn string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn diagram(m: &ConceptMap, out: &mut String) {
+    let colors = m.category_colors();
+    let depths = m.depths();
+    let mut columns = vec![0; depths.iter().copied().max().unwrap_or(0) + 1];
+    // the top-left corner of each concept's node, by offset
+    let corners: Vec<(f64, f64)> = depths
+        .iter()
+        .map(|&d| {
+            let corner = (columns[d] as f64 * COLUMN_CM, d as f64 * ROW_CM);
+            columns[d] += 1;
+            corner
+        })
+        .collect();
+    let width = columns.iter().copied().max().unwrap_or(0) as f64 * COLUMN_CM;
+
+    out.push_str(&format!(
+        "#let concept-map = block(width: {:.1}cm, height: {:.1}cm, {{\n",
+        width,
+        columns.len() as f64 * ROW_CM
+    ));
+    for c in &m.concepts {
+        let (x, y) = corners[c.offset];
+        for d in &c.dependencies {
+            let (dx, dy) = corners[m.dependency_to_concept(d).offset];
+            out.push_str(&format!(
+                "  place(line(start: ({:.2}cm, {:.2}cm), end: ({:.2}cm, {:.2}cm), stroke: gray))\n",
+                dx + NODE_W_CM / 2.0,
+                dy + NODE_H_CM,
+                x + NODE_W_CM / 2.0,
+                y
+            ));
+        }
+    }
+    for c in &m.concepts {
+        let (x, y) = corners[c.offset];
+        out.push_str(&format!("  place(dx: {:.2}cm, dy: {:.2}cm, box(width: {}cm, height: {}cm, radius: 3pt, stroke: black, fill: rgb({}).lighten(60%), align(center + horizon, text(size: 8pt, {}))))\n",
+            x,
+            y,
+            NODE_W_CM,
+            NODE_H_CM,
+            string(css_color(colors.get(&c.category).unwrap())), // unwrap: all categories have colors
+            string(&c.concept)
+        ));
+    }
+    out.push_str("})\n");
+}
+
+// Requires the map to be solved.
+fn schedule_table(m: &ConceptMap, out: &mut String) {
+    let schedule = Schedule::new(m);
+    let mut concepts: Vec<_> = m.concepts.iter().filter(|c| c.is_taught()).collect();
+    concepts.sort_by_key(|c| schedule.week(c));
+
+    out.push_str("#let concept-schedule = table(\n  columns: 6,\n  [*Week*], [*Concept*], [*Category*], [*Lecture*], [*Lab*], [*HW*],\n");
+    for c in concepts {
+        out.push_str(&format!(
+            "  [{}], {}, {}, [{:.2}], [{:.2}], [{:.2}],\n",
+            schedule.week(c),
+            string(&c.concept),
+            string(category_label(&c.category)),
+            m.shown_weight(0, c.modes[0].weight),
+            m.shown_weight(1, c.modes[1].weight),
+            m.shown_weight(2, c.modes[2].weight)
+        ));
+    }
+    out.push_str(")\n");
+}
+
+/// Requires the map to be solved.
+pub fn render(m: &ConceptMap) -> String {
+    let mut out = String::from("// Generated by concept_map.\n");
+
+    diagram(m, &mut out);
+    schedule_table(m, &mut out);
+    out.push_str("\n#concept-map\n\n#concept-schedule\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_schedule_is_a_table_of_strings() {
+        let m = crate::test_map(
+            "concept,dependencies,category,lecture weight,status
+\"say \"\"hi\"\"\",,x,1,
+C:\\,,,0.5,
+d,C:\\,x,0.5,
+old,,x,1,retired
+",
+        );
+        let typst = render(&m);
+
+        assert!(typst.contains("  [1], \"say \\\"hi\\\"\", \"x\", [1.00], [0.00], [0.00],\n  [1], \"C:\\\\\", \"(uncategorized)\", [0.50], [0.00], [0.00],\n  [1], \"d\", \"x\", [0.50], [0.00], [0.00],\n)\n"), "{}", typst);
+        assert!(!typst.contains(", \"old\", "), "{}", typst);
+        assert!(
+            typst.contains(
+                "  place(line(start: (4.60cm, 1.00cm), end: (1.40cm, 1.60cm), stroke: gray))\n"
+            ),
+            "{}",
+            typst
+        );
+        assert!(
+            typst.ends_with("\n#concept-map\n\n#concept-schedule\n"),
+            "{}",
+            typst
+        );
+    }
+}
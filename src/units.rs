@@ -0,0 +1,149 @@
+// The unit of the csv's weights. Everything is solved and scheduled in
+// weeks, so weights given in lectures or in schedule slots are
+// converted on the way in, and the summaries show both, e.g., "2.50
+// weeks ≈ 5 lectures".
+
+use crate::ConceptRecord;
+use clap::{Args, ValueEnum};
+
+#[derive(Args, Clone, Copy, Debug)]
+#[command(next_help_heading = "Units")]
+pub struct Units {
+    /// Unit of the csv's weights
+    #[arg(long, value_enum, global = true, default_value = "weeks")]
+    pub weight_unit: Unit,
+    /// Lectures in a week, to convert from and to lectures
+    #[arg(long, global = true, value_name = "N", default_value = "2")]
+    pub lectures_per_week: f64,
+    /// Schedule slots in a week, to convert from and to slots
+    #[arg(long, global = true, value_name = "N", default_value = "5")]
+    pub slots_per_week: f64,
+    /// Show weights in labels, summaries, and exports as percentages of
+    /// each modality's total, rather than in weeks
+    #[arg(long, global = true)]
+    pub percent_of_course: bool,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Units {
+            weight_unit: Unit::Weeks,
+            lectures_per_week: 2.0,
+            slots_per_week: 5.0,
+            percent_of_course: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Unit {
+    Weeks,
+    /// Lecture-hours, or class meetings
+    #[value(alias = "lecture-hours")]
+    Lectures,
+    /// Schedule slots, e.g., the days of the week
+    Slots,
+}
+
+impl Units {
+    fn per_week(&self) -> f64 {
+        match self.weight_unit {
+            Unit::Weeks => 1.0,
+            Unit::Lectures => self.lectures_per_week,
+            Unit::Slots => self.slots_per_week,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.weight_unit {
+            Unit::Weeks => "weeks",
+            Unit::Lectures => "lectures",
+            Unit::Slots => "slots",
+        }
+    }
+
+    /// The record, with its weights converted to weeks.
+    pub fn in_weeks(&self, mut r: ConceptRecord) -> ConceptRecord {
+        let per_week = self.per_week();
+
+        for w in [&mut r.lecture_weight, &mut r.lab_weight, &mut r.hw_weight] {
+            *w = w.map(|w| w / per_week);
+        }
+        r
+    }
+
+    /// `weeks` for the summaries, along with the csv's unit if it
+    /// isn't weeks.
+    pub fn weeks(&self, weeks: f64) -> String {
+        match self.weight_unit {
+            Unit::Weeks => format!("{:.2} weeks", weeks),
+            _ => {
+                let n = trimmed(weeks * self.per_week());
+                let name = if n == "1" {
+                    self.name().trim_end_matches('s')
+                } else {
+                    self.name()
+                };
+                format!("{:.2} weeks ≈ {} {}", weeks, n, name)
+            }
+        }
+    }
+
+    /// A note on the conversion, if there is one.
+    pub fn legend(&self) -> Option<String> {
+        match self.weight_unit {
+            Unit::Weeks => None,
+            _ => Some(format!(
+                "Weights in weeks, at {} {} a week.",
+                trimmed(self.per_week()),
+                self.name()
+            )),
+        }
+    }
+}
+
+// To the hundredth, without trailing zeros: "5", not "5.00".
+fn trimmed(n: f64) -> String {
+    let s = format!("{:.2}", n);
+
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_lectures() -> Units {
+        Units {
+            weight_unit: Unit::Lectures,
+            lectures_per_week: 3.0,
+            ..Units::default()
+        }
+    }
+
+    #[test]
+    fn weights_are_converted_to_weeks() {
+        let r = in_lectures().in_weeks(ConceptRecord {
+            lecture_weight: Some(1.5),
+            hw_weight: Some(3.0),
+            ..ConceptRecord::default()
+        });
+
+        assert_eq!(
+            (r.lecture_weight, r.lab_weight, r.hw_weight),
+            (Some(0.5), None, Some(1.0))
+        );
+    }
+
+    #[test]
+    fn summaries_show_the_csvs_unit_too() {
+        assert_eq!(Units::default().weeks(2.5), "2.50 weeks");
+        assert_eq!(Units::default().legend(), None);
+        assert_eq!(in_lectures().weeks(0.5), "0.50 weeks ≈ 1.5 lectures");
+        assert_eq!(in_lectures().weeks(1.0 / 3.0), "0.33 weeks ≈ 1 lecture");
+        assert_eq!(
+            in_lectures().legend().as_deref(),
+            Some("Weights in weeks, at 3 lectures a week.")
+        );
+    }
+}
@@ -0,0 +1,143 @@
+// An Excel workbook of the map for administrators: the concepts as
+// read (after validation), the computed schedule, the weekly loads by
+// category, and the warnings about the csv.
+
+use crate::load::{weekly_loads, ModeFilter};
+use crate::schedule::Schedule;
+use crate::{capacity, category_label, export, ConceptMap};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+fn concepts(m: &ConceptMap, sheet: &mut Worksheet, bold: &Format) -> anyhow::Result<()> {
+    let headers = [
+        "concept",
+        "category",
+        "dependencies",
+        "aliases",
+        "week",
+        "earliest",
+        "latest",
+        "lecture weight",
+        "lab weight",
+        "hw weight",
+        "description",
+    ];
+    sheet.write_row_with_format(0, 0, headers, bold)?;
+
+    for (row, c) in (1..).zip(&m.concepts) {
+        sheet.write(row, 0, &c.concept)?;
+        sheet.write(row, 1, &c.category)?;
+        sheet.write(row, 2, c.dependencies.join("; "))?;
+        sheet.write(row, 3, c.aliases.join("; "))?;
+        for (col, week) in [(4, c.week), (5, c.earliest), (6, c.latest)] {
+            if let Some(week) = week {
+                sheet.write(row, col, week as f64)?;
+            }
+        }
+        for (mode, col) in (7..10).enumerate() {
+            sheet.write(row, col, m.shown_weight(mode, c.modes[mode].weight))?;
+        }
+        sheet.write(row, 10, &c.description)?;
+    }
+    Ok(())
+}
+
+fn loads(m: &ConceptMap, sheet: &mut Worksheet, bold: &Format) -> anyhow::Result<()> {
+    let cats = m.categories();
+    let schedule = Schedule::new(m);
+
+    sheet.write_with_format(0, 0, "week", bold)?;
+    for (col, cat) in (1..).zip(&cats) {
+        sheet.write_with_format(0, col, category_label(cat), bold)?;
+    }
+    sheet.write_with_format(0, cats.len() as u16 + 1, "total", bold)?;
+    for (row, load) in (1..).zip(weekly_loads(m, &schedule, ModeFilter::All)) {
+        sheet.write(row, 0, row)?;
+        for (col, w) in (1..).zip(&load) {
+            sheet.write(row, col, *w)?;
+        }
+        sheet.write(row, cats.len() as u16 + 1, load.iter().sum::<f64>())?;
+    }
+    Ok(())
+}
+
+fn warnings(m: &ConceptMap, sheet: &mut Worksheet, bold: &Format) -> anyhow::Result<()> {
+    let capacity = m
+        .semester_weeks
+        .map(|weeks| capacity::check(m, weeks))
+        .unwrap_or_default();
+
+    sheet.write_with_format(0, 0, "warning", bold)?;
+    for (row, w) in (1..).zip(
+        m.errors
+            .lines()
+            .chain(m.warnings.lines())
+            .chain(capacity.lines()),
+    ) {
+        sheet.write(row, 0, w.trim_start_matches("- "))?;
+    }
+    Ok(())
+}
+
+/// Requires the map to be solved.
+pub fn render(m: &ConceptMap) -> anyhow::Result<Vec<u8>> {
+    let mut book = Workbook::new();
+    let bold = Format::new().set_bold();
+
+    concepts(m, book.add_worksheet().set_name("Concepts")?, &bold)?;
+    let schedule = book.add_worksheet().set_name("Schedule")?;
+    let rows = export::rows(m);
+    if let Some(first) = rows.first() {
+        schedule.serialize_headers_with_format(0, 0, first, &bold)?;
+        for r in &rows {
+            schedule.serialize(r)?;
+        }
+    }
+    loads(m, book.add_worksheet().set_name("Weekly load")?, &bold)?;
+    warnings(m, book.add_worksheet().set_name("Warnings")?, &bold)?;
+
+    Ok(book.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{xlsx_input, Cli};
+    use clap::Parser as _;
+
+    fn sheet(book: &[u8], name: &str) -> Vec<Vec<String>> {
+        let cli = Cli::parse_from(["concept_map", "--from", "xlsx", "--sheet", name]);
+        let (headers, rows) = xlsx_input::read(book.to_vec(), &cli.input).unwrap();
+        std::iter::once(&headers)
+            .chain(&rows)
+            .map(|r| r.iter().map(String::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn workbooks_read_back() {
+        let m = crate::test_map(
+            "concept,dependencies,category,lecture weight,hw weight
+a,,x,1,0.5
+b,a,y,0.5,
+",
+        );
+        let book = render(&m).unwrap();
+
+        assert_eq!(
+            sheet(&book, "Concepts")[1..],
+            [
+                ["a", "x", "", "", "", "", "", "1", "0", "0.5", ""],
+                ["b", "y", "a", "", "", "", "", "0.5", "0", "0", ""],
+            ]
+        );
+        assert_eq!(
+            sheet(&book, "Weekly load"),
+            [
+                ["week", "x", "y", "total"],
+                ["1", "1.5", "0", "1.5"],
+                ["2", "0", "0.5", "0.5"],
+            ]
+        );
+        assert_eq!(sheet(&book, "Warnings"), [["warning"]]);
+    }
+}
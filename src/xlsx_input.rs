@@ -0,0 +1,398 @@
+// Reading a worksheet of an Excel workbook as the concept table, so
+// that maps kept in Excel don't have to be exported to csv, losing the
+// other sheets (and, with Excel's csv export, the accents) along the
+// way. A workbook is a zip archive of XML files; only the cells' values
+// are read (formulas' cached results, not the formulas), and everything
+// else (formatting, comments, hidden rows) is ignored.
+
+use crate::input::InputOpts;
+use csv::{Position, StringRecord};
+use std::io::{self, Read};
+
+enum Event<'a> {
+    // the element's local name and its attributes
+    Start(&'a str, &'a str),
+    End(&'a str),
+    Text(&'a str),
+}
+
+// Without the namespace prefix, which some writers add to every element.
+fn local(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+// The tree of a well-formed document, flattened. Self-closing elements
+// are a start and an end.
+fn events(xml: &str) -> anyhow::Result<Vec<Event<'_>>> {
+    let mut events = Vec::new();
+    let mut rest = xml;
+
+    while let Some(i) = rest.find('<') {
+        if i > 0 {
+            events.push(Event::Text(&rest[..i]));
+        }
+        rest = &rest[i + 1..];
+        // the end of the tag, other than in quoted attribute values
+        let mut quote = None;
+        let end = rest
+            .char_indices()
+            .find(|&(_, ch)| match quote {
+                Some(q) if ch == q => {
+                    quote = None;
+                    false
+                }
+                Some(_) => false,
+                None if ch == '"' || ch == '\'' => {
+                    quote = Some(ch);
+                    false
+                }
+                None => ch == '>',
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| anyhow::anyhow!("The workbook has an unterminated XML tag."))?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        // declarations, processing instructions, and comments
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            events.push(Event::End(local(name.trim())));
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        events.push(Event::Start(local(name), attrs));
+        if self_closing {
+            events.push(Event::End(local(name)));
+        }
+    }
+    Ok(events)
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let Some(end) = rest.find(';') else { break };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            e => e
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16).ok())
+                .unwrap_or_else(|| e.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// The value of the attribute, by its local name.
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs.trim_start();
+
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next()?;
+        let end = value[1..].find(quote)? + 1;
+        if local(key) == name {
+            return Some(unescape(&value[1..end]));
+        }
+        rest = value[end + 1..].trim_start();
+    }
+    None
+}
+
+struct Workbook {
+    archive: zip::ZipArchive<io::Cursor<Vec<u8>>>,
+}
+
+impl Workbook {
+    fn part(&mut self, name: &str) -> anyhow::Result<Option<String>> {
+        let mut file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut xml = String::new();
+        file.read_to_string(&mut xml)?;
+        Ok(Some(xml))
+    }
+
+    fn required_part(&mut self, name: &str) -> anyhow::Result<String> {
+        self.part(name)?
+            .ok_or_else(|| anyhow::anyhow!("The workbook has no {}; is it an .xlsx file?", name))
+    }
+
+    // The sheets' names and the archive's files for them, in order.
+    fn sheets(&mut self) -> anyhow::Result<Vec<(String, String)>> {
+        let rels = self.required_part("xl/_rels/workbook.xml.rels")?;
+        let mut targets = Vec::new();
+        for e in events(&rels)? {
+            if let Event::Start("Relationship", attrs) = e {
+                if let (Some(id), Some(target)) = (attr(attrs, "Id"), attr(attrs, "Target")) {
+                    // relative to the workbook's directory, unless absolute
+                    let target = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    targets.push((id, target));
+                }
+            }
+        }
+
+        let workbook = self.required_part("xl/workbook.xml")?;
+        let mut sheets = Vec::new();
+        for e in events(&workbook)? {
+            if let Event::Start("sheet", attrs) = e {
+                let (Some(name), Some(id)) = (attr(attrs, "name"), attr(attrs, "id")) else {
+                    continue;
+                };
+                if let Some((_, target)) = targets.iter().find(|(i, _)| *i == id) {
+                    sheets.push((name, target.clone()));
+                }
+            }
+        }
+        Ok(sheets)
+    }
+
+    // Cells with text refer to it by its index here. Phonetic runs
+    // (over Japanese names) aren't part of the text.
+    fn shared_strings(&mut self) -> anyhow::Result<Vec<String>> {
+        let Some(xml) = self.part("xl/sharedStrings.xml")? else {
+            return Ok(Vec::new());
+        };
+        let mut strings = Vec::new();
+        let (mut current, mut in_text, mut in_phonetic) = (String::new(), false, false);
+
+        for e in events(&xml)? {
+            match e {
+                Event::Start("si", _) => current.clear(),
+                Event::End("si") => strings.push(unescape(&current)),
+                Event::Start("t", _) => in_text = true,
+                Event::End("t") => in_text = false,
+                Event::Start("rPh", _) => in_phonetic = true,
+                Event::End("rPh") => in_phonetic = false,
+                Event::Text(text) if in_text && !in_phonetic => current.push_str(text),
+                _ => (),
+            }
+        }
+        Ok(strings)
+    }
+}
+
+// The zero-based column of a cell reference, e.g., 27 for "AB12".
+fn column(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let n = letters.iter().fold(0, |n, l| {
+        n * 26 + (l.to_ascii_uppercase() - b'A') as usize + 1
+    });
+    Some(n - 1)
+}
+
+// The sheet's rows, by their row numbers, with the empty ones left out.
+fn rows(xml: &str, strings: &[String]) -> anyhow::Result<Vec<(u64, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut row_number = 0;
+    let (mut col, mut kind, mut value) = (0, String::new(), String::new());
+    let mut in_value = false;
+
+    for e in events(xml)? {
+        match e {
+            Event::Start("row", attrs) => {
+                row_number = attr(attrs, "r")
+                    .and_then(|r| r.parse().ok())
+                    .unwrap_or(row_number + 1);
+                row.clear();
+            }
+            Event::End("row") if row.iter().any(|cell| !cell.trim().is_empty()) => {
+                rows.push((row_number, row.clone()));
+            }
+            Event::Start("c", attrs) => {
+                col = attr(attrs, "r")
+                    .and_then(|r| column(&r))
+                    .unwrap_or(row.len());
+                kind = attr(attrs, "t").unwrap_or_default();
+                value.clear();
+            }
+            Event::End("c") => {
+                let cell = match kind.as_str() {
+                    "s" => {
+                        let i: usize = value.trim().parse().map_err(|_| {
+                            anyhow::anyhow!("Row {} has an invalid shared string.", row_number)
+                        })?;
+                        strings.get(i).cloned().ok_or_else(|| {
+                            anyhow::anyhow!("Row {} has an invalid shared string.", row_number)
+                        })?
+                    }
+                    "b" if value.trim() == "1" => String::from("TRUE"),
+                    "b" => String::from("FALSE"),
+                    _ => unescape(&value),
+                };
+                if row.len() <= col {
+                    row.resize(col + 1, String::new());
+                }
+                row[col] = cell;
+            }
+            // the value of numbers, shared strings, and formulas, and
+            // the text of inline strings
+            Event::Start("v", _) | Event::Start("t", _) => in_value = true,
+            Event::End("v") | Event::End("t") => in_value = false,
+            Event::Text(text) if in_value => value.push_str(text),
+            _ => (),
+        }
+    }
+    Ok(rows)
+}
+
+/// The rows of the worksheet `--sheet` (the first, by default) as csv
+/// records, the first being the headers, positioned at their row
+/// numbers for errors.
+pub fn read(bytes: Vec<u8>, opts: &InputOpts) -> anyhow::Result<(StringRecord, Vec<StringRecord>)> {
+    let archive = zip::ZipArchive::new(io::Cursor::new(bytes))
+        .map_err(|e| anyhow::anyhow!("The input isn't an .xlsx workbook: {}.", e))?;
+    let mut book = Workbook { archive };
+    let sheets = book.sheets()?;
+    let (name, part) = match &opts.sheet {
+        Some(sheet) => sheets
+            .iter()
+            .find(|(name, _)| name == sheet)
+            .ok_or_else(|| {
+                let names: Vec<String> = sheets.iter().map(|(n, _)| format!("\"{}\"", n)).collect();
+                anyhow::anyhow!(
+                    "The workbook has no sheet \"{}\", only {}.",
+                    sheet,
+                    names.join(", ")
+                )
+            })?,
+        None => sheets
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("The workbook has no sheets."))?,
+    };
+    let xml = book.required_part(part)?;
+    let strings = book.shared_strings()?;
+
+    let mut rows = rows(&xml, &strings)?.into_iter();
+    let (_, mut headers) = rows
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The sheet \"{}\" is empty.", name))?;
+    while headers.last().is_some_and(|h| h.trim().is_empty()) {
+        headers.pop();
+    }
+    let records = rows
+        .enumerate()
+        .map(|(i, (line, mut row))| {
+            row.resize(headers.len(), String::new());
+            let mut record = StringRecord::from(row);
+            let mut pos = Position::new();
+            pos.set_line(line).set_record(i as u64 + 1);
+            record.set_position(Some(pos));
+            record
+        })
+        .collect();
+
+    Ok((StringRecord::from(headers), records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser as _;
+    use rust_xlsxwriter::Workbook;
+
+    fn opts(args: &[&str]) -> InputOpts {
+        let args = ["concept_map", "--from", "xlsx"].iter().chain(args);
+        Cli::parse_from(args).input
+    }
+
+    fn workbook() -> Vec<u8> {
+        let mut book = Workbook::new();
+        let notes = book.add_worksheet();
+        notes.set_name("Notes").unwrap();
+        notes.write(0, 0, "Not the map").unwrap();
+        let sheet = book.add_worksheet();
+        sheet.set_name("Map").unwrap();
+        sheet
+            .write_row(0, 0, ["concept", "dependencies", "lecture weight"])
+            .unwrap();
+        sheet.write_row(1, 0, ["Caf\u{e9} & <tags>", ""]).unwrap();
+        sheet.write(1, 2, 0.5).unwrap();
+        // a blank row, and a cell past the headers
+        sheet.write(3, 0, "Pointers").unwrap();
+        sheet.write(3, 1, "Caf\u{e9} & <tags>").unwrap();
+        sheet.write(3, 4, "ignored").unwrap();
+        book.save_to_buffer().unwrap()
+    }
+
+    #[test]
+    fn cell_references_are_columns() {
+        assert_eq!(column("A1"), Some(0));
+        assert_eq!(column("AB12"), Some(27));
+        assert_eq!(column("12"), None);
+    }
+
+    #[test]
+    fn entities_are_unescaped() {
+        assert_eq!(
+            unescape("a &amp; b &lt;c&gt; &#233;&#xe9; &bogus;"),
+            "a & b <c> éé &bogus;"
+        );
+        assert_eq!(attr(r#" r="B2" t='s'"#, "t").as_deref(), Some("s"));
+        assert_eq!(attr(r#"x:r="B2""#, "r").as_deref(), Some("B2"));
+    }
+
+    #[test]
+    fn sheets_are_read_as_records_at_their_rows() {
+        let (headers, rows) = read(workbook(), &opts(&["--sheet", "Map"])).unwrap();
+        let cells: Vec<Vec<&str>> = rows.iter().map(|r| r.iter().collect()).collect();
+
+        assert_eq!(headers, vec!["concept", "dependencies", "lecture weight"]);
+        assert_eq!(
+            cells,
+            [
+                vec!["Caf\u{e9} & <tags>", "", "0.5"],
+                vec!["Pointers", "Caf\u{e9} & <tags>", ""],
+            ]
+        );
+        assert_eq!(rows[1].position().map(|p| p.line()), Some(4));
+    }
+
+    #[test]
+    fn missing_sheets_are_named() {
+        let e = read(workbook(), &opts(&["--sheet", "Other"])).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "The workbook has no sheet \"Other\", only \"Notes\", \"Map\"."
+        );
+        assert!(read(b"concept\n".to_vec(), &opts(&[])).is_err());
+    }
+}